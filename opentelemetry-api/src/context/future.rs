@@ -0,0 +1,129 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//!
+//! Propagation of the current `SpanContext` into futures, which may be polled on any thread.
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use pin_project_lite::pin_project;
+
+use crate::context;
+use crate::trace::span_context::SpanContext;
+
+/// Extension of every `Future` to carry the current `SpanContext` across `.await` points.
+pub trait FutureExt: Future + Sized {
+    /// Captures the current `SpanContext`, and makes it current again each time the returned
+    /// future is polled, restoring the context of the polling thread afterwards.
+    ///
+    /// Use this on futures handed to an executor, e.g. spawned on `tokio`, so spans started
+    /// within them have the right parent.
+    fn with_current_context(self) -> WithContext<Self> {
+        WithContext { future: self, context: context::current_span_context() }
+    }
+}
+
+impl <F: Future> FutureExt for F {}
+
+pin_project! {
+    /// `Future` returned by `FutureExt::with_current_context`.
+    #[derive(Debug)]
+    pub struct WithContext<F> {
+        #[pin]
+        future: F,
+        context: Option<SpanContext<'static>>,
+    }
+}
+
+impl <F: Future> Future for WithContext<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let this = self.project();
+        let _scope = context::enter(this.context.clone());
+        this.future.poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::task::noop_waker;
+    use crate::trace::span_id::SpanId;
+    use crate::trace::trace_id::TraceId;
+    use crate::trace::trace_options::TraceOptions;
+    use crate::trace::trace_state::TraceState;
+
+    fn span_context(span_id: u64) -> SpanContext<'static> {
+        SpanContext::new(TraceId::from_bytes([1; 16]), SpanId::new(span_id), TraceOptions::IS_SAMPLED,
+                         TraceState::default())
+    }
+
+    /// Returns `Pending` on the first poll.
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                return Poll::Ready(());
+            }
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    async fn current_after_yield() -> Option<SpanContext<'static>> {
+        YieldOnce(false).await;
+        context::current_span_context()
+    }
+
+    #[test]
+    fn test_awaited_future_sees_captured_context() {
+        let future = {
+            let _scope = context::with_span_context(span_context(1));
+            async {
+                let before = context::current_span_context();
+                let after = current_after_yield().await;
+                (before, after)
+            }.with_current_context()
+        };
+        assert_eq!(context::current_span_context(), None);
+
+        let (before, after) = block_on(future);
+        assert_eq!(before, Some(span_context(1)));
+        assert_eq!(after, Some(span_context(1)));
+        assert_eq!(context::current_span_context(), None);
+    }
+
+    #[test]
+    fn test_polling_restores_context() {
+        let mut future = Box::pin(current_after_yield().with_current_context());
+        let _outer = context::with_span_context(span_context(2));
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(context::current_span_context(), Some(span_context(2)));
+        // Captured before `_outer` was entered, so no span is current within the future
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready(None));
+        assert_eq!(context::current_span_context(), Some(span_context(2)));
+    }
+}
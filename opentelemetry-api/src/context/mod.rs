@@ -1,3 +1,145 @@
+use std::cell::RefCell;
+use std::marker::PhantomData;
+
+use crate::trace::sampler::ParentContext;
+use crate::trace::span_context::SpanContext;
+
+pub mod propagation;
+pub mod text_map;
+#[cfg(feature = "futures")]
+pub mod future;
+
 pub trait Scope: Drop {
-    fn close(mut self);
-}
\ No newline at end of file
+    fn close(self);
+}
+
+thread_local! {
+    // A `const` initializer would require Rust 1.59.
+    #[allow(clippy::missing_const_for_thread_local)]
+    static CURRENT_SPAN_CONTEXT: RefCell<Option<SpanContext<'static>>> = RefCell::new(None);
+}
+
+/// Returns the `SpanContext` of the `Span` currently active on this thread, if any.
+pub fn current_span_context() -> Option<SpanContext<'static>> {
+    CURRENT_SPAN_CONTEXT.with(|current| current.borrow().clone())
+}
+
+/// Installs the given `SpanContext` as current for this thread.
+///
+/// The previous context is restored when the returned `Scope` is closed or dropped, so scopes
+/// must be exited in the reverse order they were entered.
+pub fn with_span_context(context: SpanContext<'static>) -> SpanContextScope {
+    enter(Some(context))
+}
+
+/// Installs the given context as current for this thread, no context at all if `None`.
+pub(crate) fn enter(context: Option<SpanContext<'static>>) -> SpanContextScope {
+    let previous = CURRENT_SPAN_CONTEXT.with(|current| current.replace(context));
+    SpanContextScope { previous, entered: true, _not_send: PhantomData }
+}
+
+/// Returns the `ParentContext` a new `Span` would implicitly use on this thread.
+///
+/// This is `ParentContext::Parent` when a local `Span` is current, `ParentContext::RemoteParent`
+/// when the current context was extracted from another process, `ParentContext::RootSpan`
+/// otherwise.
+pub fn current_parent() -> ParentContext<'static> {
+    match current_span_context() {
+        Some(context) => ParentContext::from(context),
+        None => ParentContext::RootSpan,
+    }
+}
+
+/// `Scope` returned by `with_span_context`, restoring the previous context on exit.
+pub struct SpanContextScope {
+    previous: Option<SpanContext<'static>>,
+    entered: bool,
+    // The scope manipulates thread-local state and must be exited on the thread that entered it.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl SpanContextScope {
+    /// Returns a scope that leaves the current context untouched, neither on entry nor on exit.
+    pub fn noop() -> Self {
+        SpanContextScope { previous: None, entered: false, _not_send: PhantomData }
+    }
+}
+
+impl Scope for SpanContextScope {
+    fn close(self) {}
+}
+
+impl Drop for SpanContextScope {
+    fn drop(&mut self) {
+        if !self.entered {
+            return;
+        }
+        let previous = self.previous.take();
+        CURRENT_SPAN_CONTEXT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::trace_id::TraceId;
+    use crate::trace::span_id::SpanId;
+    use crate::trace::trace_options::TraceOptions;
+    use crate::trace::trace_state::TraceState;
+
+    fn span_context(span_id: u64) -> SpanContext<'static> {
+        SpanContext::new(
+            TraceId::from_bytes([1; 16]),
+            SpanId::new(span_id),
+            TraceOptions::default(),
+            TraceState::default(),
+        )
+    }
+
+    #[test]
+    fn test_current_parent_without_span() {
+        assert!(current_span_context().is_none());
+        match current_parent() {
+            ParentContext::RootSpan => {},
+            _ => panic!("Expected a root span parent"),
+        }
+    }
+
+    #[test]
+    fn test_current_parent_with_span() {
+        let scope = with_span_context(span_context(1));
+        match current_parent() {
+            ParentContext::Parent(ctx) => assert_eq!(ctx, span_context(1)),
+            _ => panic!("Expected a local parent"),
+        }
+        scope.close();
+
+        match current_parent() {
+            ParentContext::RootSpan => {},
+            _ => panic!("Expected a root span parent"),
+        }
+    }
+
+    #[test]
+    fn test_current_parent_with_remote_context() {
+        let remote = SpanContext::new_remote(TraceId::from_bytes([1; 16]), SpanId::new(1),
+                                             TraceOptions::default(), TraceState::default());
+        let _scope = with_span_context(remote.clone());
+        match current_parent() {
+            ParentContext::RemoteParent(ctx) => assert_eq!(ctx, remote),
+            _ => panic!("Expected a remote parent"),
+        }
+    }
+
+    #[test]
+    fn test_nested_scopes_restore() {
+        let outer = with_span_context(span_context(1));
+        {
+            let _inner = with_span_context(span_context(2));
+            assert_eq!(current_span_context(), Some(span_context(2)));
+        }
+        assert_eq!(current_span_context(), Some(span_context(1)));
+        drop(outer);
+        assert_eq!(current_span_context(), None);
+    }
+}
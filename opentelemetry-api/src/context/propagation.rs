@@ -0,0 +1,216 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::trace::propagation::HttpTraceContext;
+use crate::trace::span_context::SpanContext;
+
+/// Injects and extracts a `SpanContext` as text into carriers that travel in-band across process
+/// boundaries, e.g. HTTP headers.
+///
+/// Carriers are accessed through a setter and a getter, so the format works with any header map
+/// without depending on a particular HTTP library.
+pub trait HttpTextFormat {
+    /// The header names this format reads and writes.
+    ///
+    /// Useful to clear fields before re-injecting, e.g. when a carrier is reused.
+    fn fields(&self) -> &'static [&'static str];
+
+    /// Injects the given `SpanContext` into the carrier by calling `setter` for every field.
+    fn inject<C, S>(&self, context: &SpanContext, carrier: &mut C, setter: S)
+        where S: Fn(&mut C, &str, &str);
+
+    /// Extracts a `SpanContext` from the carrier, using `getter` to read each field.
+    ///
+    /// Returns `None` if the carrier holds no context, or an invalid one.
+    fn extract<C, G>(&self, carrier: &C, getter: G) -> Option<SpanContext<'static>>
+        where G: Fn(&C, &str) -> Option<String>;
+}
+
+/// Error returned when a `SpanContext` cannot be extracted.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PropagationError {
+    /// The trace id is missing or all zeros.
+    InvalidTraceId,
+    /// The span id is missing or all zeros.
+    InvalidSpanId,
+    /// The data uses a version of the format that is not supported.
+    UnsupportedVersion(u8),
+    /// The data could not be parsed.
+    Malformed,
+}
+
+impl fmt::Display for PropagationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PropagationError::InvalidTraceId => write!(f, "invalid trace id"),
+            PropagationError::InvalidSpanId => write!(f, "invalid span id"),
+            PropagationError::UnsupportedVersion(v) => write!(f, "unsupported format version {}", v),
+            PropagationError::Malformed => write!(f, "malformed span context"),
+        }
+    }
+}
+
+impl Error for PropagationError {}
+
+/// Serializes a `SpanContext` to and from bytes, for protocols with binary metadata, e.g. gRPC.
+pub trait BinaryFormat {
+    /// Serializes the given `SpanContext`.
+    fn to_bytes(&self, context: &SpanContext) -> Vec<u8>;
+
+    /// Deserializes a `SpanContext`, reporting why the bytes do not hold a valid one.
+    #[allow(clippy::wrong_self_convention)]
+    fn from_bytes(&self, bytes: &[u8]) -> Result<SpanContext<'static>, PropagationError>;
+}
+
+/// `HttpTextFormat` that never touches carriers, so disabled tracing has no header overhead.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct NoopPropagator;
+
+impl HttpTextFormat for NoopPropagator {
+    fn fields(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn inject<C, S>(&self, _context: &SpanContext, _carrier: &mut C, _setter: S)
+        where S: Fn(&mut C, &str, &str) {}
+
+    fn extract<C, G>(&self, _carrier: &C, _getter: G) -> Option<SpanContext<'static>>
+        where G: Fn(&C, &str) -> Option<String>
+    {
+        None
+    }
+}
+
+static PROPAGATION_DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Switches the process wide `HttpTextFormat` to the `NoopPropagator` when `disabled`.
+pub fn set_propagation_disabled(disabled: bool) {
+    PROPAGATION_DISABLED.store(disabled, Ordering::SeqCst);
+}
+
+/// Returns the process wide `HttpTextFormat`.
+///
+/// This is the W3C `HttpTraceContext` unless propagation was disabled with
+/// `set_propagation_disabled`.
+pub fn global_http_text_format() -> GlobalHttpTextFormat {
+    if PROPAGATION_DISABLED.load(Ordering::SeqCst) {
+        GlobalHttpTextFormat::Noop(NoopPropagator)
+    } else {
+        GlobalHttpTextFormat::TraceContext(HttpTraceContext)
+    }
+}
+
+/// The `HttpTextFormat` selected by `global_http_text_format`.
+#[derive(Copy, Clone, Debug)]
+pub enum GlobalHttpTextFormat {
+    TraceContext(HttpTraceContext),
+    Noop(NoopPropagator),
+}
+
+impl HttpTextFormat for GlobalHttpTextFormat {
+    fn fields(&self) -> &'static [&'static str] {
+        match self {
+            GlobalHttpTextFormat::TraceContext(f) => f.fields(),
+            GlobalHttpTextFormat::Noop(f) => f.fields(),
+        }
+    }
+
+    fn inject<C, S>(&self, context: &SpanContext, carrier: &mut C, setter: S)
+        where S: Fn(&mut C, &str, &str)
+    {
+        match self {
+            GlobalHttpTextFormat::TraceContext(f) => f.inject(context, carrier, setter),
+            GlobalHttpTextFormat::Noop(f) => f.inject(context, carrier, setter),
+        }
+    }
+
+    fn extract<C, G>(&self, carrier: &C, getter: G) -> Option<SpanContext<'static>>
+        where G: Fn(&C, &str) -> Option<String>
+    {
+        match self {
+            GlobalHttpTextFormat::TraceContext(f) => f.extract(carrier, getter),
+            GlobalHttpTextFormat::Noop(f) => f.extract(carrier, getter),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+    use crate::trace::span_id::SpanId;
+    use crate::trace::trace_id::TraceId;
+    use crate::trace::trace_options::TraceOptions;
+    use crate::trace::trace_state::TraceState;
+
+    fn setter(carrier: &mut HashMap<String, String>, key: &str, value: &str) {
+        carrier.insert(key.to_string(), value.to_string());
+    }
+
+    fn getter(carrier: &HashMap<String, String>, key: &str) -> Option<String> {
+        carrier.get(key).cloned()
+    }
+
+    fn context() -> SpanContext<'static> {
+        SpanContext::new(
+            TraceId::from_bytes([1; 16]),
+            SpanId::new(1),
+            TraceOptions::IS_SAMPLED,
+            TraceState::default(),
+        )
+    }
+
+    #[test]
+    fn test_noop_propagator() {
+        let mut carrier = HashMap::new();
+        NoopPropagator.inject(&context(), &mut carrier, setter);
+        assert!(carrier.is_empty());
+
+        HttpTraceContext.inject(&context(), &mut carrier, setter);
+        let before = carrier.clone();
+        NoopPropagator.inject(&context(), &mut carrier, setter);
+        assert_eq!(carrier, before);
+        assert_eq!(NoopPropagator.extract(&carrier, getter), None);
+    }
+
+    /// Re-enables propagation when dropped, even if the test fails.
+    struct EnablePropagation;
+
+    impl Drop for EnablePropagation {
+        fn drop(&mut self) {
+            set_propagation_disabled(false);
+        }
+    }
+
+    #[test]
+    fn test_global_switch() {
+        let _enable = EnablePropagation;
+        let mut carrier = HashMap::new();
+        global_http_text_format().inject(&context(), &mut carrier, setter);
+        let extracted = global_http_text_format().extract(&carrier, getter);
+        assert_eq!(extracted, Some(SpanContext { is_remote: true, ..context() }));
+
+        set_propagation_disabled(true);
+        let mut disabled_carrier = HashMap::new();
+        global_http_text_format().inject(&context(), &mut disabled_carrier, setter);
+        assert!(disabled_carrier.is_empty());
+        assert_eq!(global_http_text_format().extract(&carrier, getter), None);
+    }
+}
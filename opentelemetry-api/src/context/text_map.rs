@@ -0,0 +1,211 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Object safe propagation of `SpanContext`s through text maps, e.g. HTTP headers or gRPC
+//! metadata.
+
+use crate::context::propagation::HttpTextFormat;
+use crate::trace::span_context::SpanContext;
+
+/// Object safe version of `HttpTextFormat`, implemented for every `HttpTextFormat` wrapped in an
+/// `HttpTextPropagator`.
+///
+/// Formats only see the fields through a setter and a getter, so any carrier can be used, e.g.
+/// `http::HeaderMap`, `HashMap<String, String>` or gRPC metadata, without this crate depending on
+/// them.
+pub trait TextMapPropagator {
+    /// Injects the given `SpanContext` by calling `setter` with every field name and value.
+    fn inject_dyn(&self, context: &SpanContext, setter: &mut dyn FnMut(&str, &str));
+
+    /// Extracts a `SpanContext`, using `getter` to read each field by name.
+    ///
+    /// Returns `None` if the fields hold no context, or an invalid one.
+    fn extract_dyn(&self, getter: &dyn Fn(&str) -> Option<String>) -> Option<SpanContext<'static>>;
+
+    /// Injects the given `SpanContext` into the carrier by calling `setter` for every field.
+    fn inject<C, S>(&self, context: &SpanContext, carrier: &mut C, setter: S)
+        where S: Fn(&mut C, &str, &str),
+              Self: Sized
+    {
+        self.inject_dyn(context, &mut |key, value| setter(carrier, key, value))
+    }
+
+    /// Extracts a `SpanContext` from the carrier, using `getter` to read each field.
+    fn extract<C, G>(&self, carrier: &C, getter: G) -> Option<SpanContext<'static>>
+        where G: Fn(&C, &str) -> Option<String>,
+              Self: Sized
+    {
+        self.extract_dyn(&|key| getter(carrier, key))
+    }
+}
+
+/// `TextMapPropagator` backed by an `HttpTextFormat`, e.g. `HttpTextPropagator(HttpTraceContext)`.
+///
+/// Formats are wrapped rather than implementing both traits, so calls to `inject` and `extract`
+/// stay unambiguous when both traits are in scope.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct HttpTextPropagator<F>(pub F);
+
+impl <F: HttpTextFormat> TextMapPropagator for HttpTextPropagator<F> {
+    fn inject_dyn(&self, context: &SpanContext, mut setter: &mut dyn FnMut(&str, &str)) {
+        // The setter itself is the carrier, as `HttpTextFormat` setters cannot be `FnMut`
+        self.0.inject(context, &mut setter, |setter, key, value| setter(key, value))
+    }
+
+    fn extract_dyn(&self, getter: &dyn Fn(&str) -> Option<String>) -> Option<SpanContext<'static>> {
+        self.0.extract(&getter, |getter, key| getter(key))
+    }
+}
+
+/// `TextMapPropagator` chaining several formats, e.g. to accept B3 and emit W3C trace context.
+///
+/// `inject` writes the fields of every propagator, while `extract` returns the first valid
+/// `SpanContext` found by the propagators, in order.
+#[derive(Default)]
+pub struct CompositePropagator {
+    propagators: Vec<Box<dyn TextMapPropagator>>,
+}
+
+impl CompositePropagator {
+    pub fn new(propagators: Vec<Box<dyn TextMapPropagator>>) -> Self {
+        CompositePropagator { propagators }
+    }
+
+    /// Appends a propagator, tried after the existing ones on extraction.
+    pub fn with<P: TextMapPropagator + 'static>(mut self, propagator: P) -> Self {
+        self.propagators.push(Box::new(propagator));
+        self
+    }
+}
+
+impl TextMapPropagator for CompositePropagator {
+    fn inject_dyn(&self, context: &SpanContext, setter: &mut dyn FnMut(&str, &str)) {
+        for propagator in self.propagators.iter() {
+            propagator.inject_dyn(context, setter);
+        }
+    }
+
+    fn extract_dyn(&self, getter: &dyn Fn(&str) -> Option<String>) -> Option<SpanContext<'static>> {
+        self.propagators.iter()
+            .filter_map(|propagator| propagator.extract_dyn(getter))
+            .find(SpanContext::is_valid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+    use crate::trace::propagation::b3::{B3Encoding, B3Propagator, B3_SINGLE_HEADER, B3_TRACE_ID_HEADER};
+    use crate::trace::propagation::http_trace_context::{TRACEPARENT, TRACESTATE};
+    use crate::trace::propagation::HttpTraceContext;
+    use crate::trace::span_id::SpanId;
+    use crate::trace::trace_id::TraceId;
+    use crate::trace::trace_options::TraceOptions;
+    use crate::trace::trace_state::TraceState;
+
+    fn setter(carrier: &mut HashMap<String, String>, key: &str, value: &str) {
+        carrier.insert(key.to_string(), value.to_string());
+    }
+
+    fn getter(carrier: &HashMap<String, String>, key: &str) -> Option<String> {
+        carrier.get(key).cloned()
+    }
+
+    fn context() -> SpanContext<'static> {
+        SpanContext::new(
+            TraceId::from_bytes([1; 16]),
+            SpanId::new(1),
+            TraceOptions::IS_SAMPLED,
+            TraceState::default(),
+        )
+    }
+
+    fn round_trip<P: TextMapPropagator>(propagator: &P) -> HashMap<String, String> {
+        let mut carrier = HashMap::new();
+        propagator.inject(&context(), &mut carrier, setter);
+        let extracted = propagator.extract(&carrier, getter);
+        assert_eq!(extracted, Some(SpanContext { is_remote: true, ..context() }));
+        carrier
+    }
+
+    #[test]
+    fn test_w3c_hash_map_carrier() {
+        let carrier = round_trip(&HttpTextPropagator(HttpTraceContext));
+        assert!(carrier.contains_key(TRACEPARENT));
+    }
+
+    #[test]
+    fn test_b3_hash_map_carrier() {
+        let carrier = round_trip(&HttpTextPropagator(B3Propagator::new(B3Encoding::MultipleHeader)));
+        assert!(carrier.contains_key(B3_TRACE_ID_HEADER));
+    }
+
+    #[test]
+    fn test_boxed_propagators() {
+        let propagators: Vec<Box<dyn TextMapPropagator>> = vec![
+            Box::new(HttpTextPropagator(HttpTraceContext)),
+            Box::new(HttpTextPropagator(B3Propagator::new(B3Encoding::SingleHeader))),
+        ];
+        for propagator in propagators.iter() {
+            let mut carrier = HashMap::new();
+            propagator.inject_dyn(&context(), &mut |key, value| setter(&mut carrier, key, value));
+            let extracted = propagator.extract_dyn(&|key| getter(&carrier, key));
+            assert_eq!(extracted, Some(SpanContext { is_remote: true, ..context() }));
+        }
+    }
+
+    #[test]
+    fn test_unambiguous_with_both_traits_in_scope() {
+        use crate::context::propagation::*;
+
+        let mut carrier = HashMap::new();
+        HttpTraceContext.inject(&context(), &mut carrier, setter);
+        let extracted = HttpTextPropagator(HttpTraceContext).extract(&carrier, getter);
+        assert_eq!(extracted, Some(SpanContext { is_remote: true, ..context() }));
+    }
+
+    #[test]
+    fn test_composite_injects_all() {
+        let composite = CompositePropagator::default()
+            .with(HttpTextPropagator(HttpTraceContext))
+            .with(HttpTextPropagator(B3Propagator::new(B3Encoding::SingleHeader)));
+        let carrier = round_trip(&composite);
+        assert!(carrier.contains_key(TRACEPARENT));
+        assert!(carrier.contains_key(B3_SINGLE_HEADER));
+    }
+
+    #[test]
+    fn test_composite_extract_falls_through() {
+        let composite = CompositePropagator::new(vec![
+            Box::new(HttpTextPropagator(HttpTraceContext)),
+            Box::new(HttpTextPropagator(B3Propagator::new(B3Encoding::MultipleHeader))),
+        ]);
+
+        let mut carrier = HashMap::new();
+        B3Propagator::new(B3Encoding::MultipleHeader).inject(&context(), &mut carrier, setter);
+        assert_eq!(composite.extract(&carrier, getter), Some(SpanContext { is_remote: true, ..context() }));
+
+        // The first valid context wins
+        let other = SpanContext { span_id: SpanId::new(2), ..context() };
+        HttpTraceContext.inject(&other, &mut carrier, setter);
+        assert_eq!(composite.extract(&carrier, getter), Some(SpanContext { is_remote: true, ..other }));
+
+        assert!(!carrier.contains_key(TRACESTATE));
+        assert_eq!(composite.extract(&HashMap::new(), getter), None);
+        assert_eq!(CompositePropagator::default().extract(&carrier, getter), None);
+    }
+}
@@ -1,7 +1,8 @@
-use std::borrow::Cow;
+use std::borrow::{Borrow, Cow};
 use std::convert::Into;
-use crate::internal::validate_and_convert_str;
+use crate::internal::{try_validate_str, validate_and_convert_str, ValidationError};
 
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub struct Entry<'a> {
     pub key: EntryKey<'a>,
     pub value: EntryValue<'a>,
@@ -18,27 +19,68 @@ impl <'a> Entry<'a> {
 pub struct EntryKey<'a>(Cow<'a, str>);
 
 impl <'a> EntryKey<'a> {
+    /// # Panics
+    /// * if the name is not a printable ASCII string shorter than 256 characters
     pub fn new<N: Into<Cow<'a, str>>>(name: N) -> Self {
         EntryKey(validate_and_convert_str(name))
     }
+
+    /// Same as `new`, but returns why the name is invalid instead of panicking.
+    pub fn try_new<N: Into<Cow<'a, str>>>(name: N) -> Result<Self, ValidationError> {
+        try_validate_str(name).map(EntryKey)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Lets maps keyed by `EntryKey` be queried with a plain `&str`.
+impl <'a> Borrow<str> for EntryKey<'a> {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub struct EntryValue<'a>(Cow<'a, str>);
 
 impl <'a> EntryValue<'a> {
+    /// # Panics
+    /// * if the value is not a printable ASCII string shorter than 256 characters
     pub fn new<N: Into<Cow<'a, str>>>(name: N) -> Self {
         EntryValue(validate_and_convert_str(name))
     }
+
+    /// Same as `new`, but returns why the value is invalid instead of panicking.
+    pub fn try_new<N: Into<Cow<'a, str>>>(name: N) -> Result<Self, ValidationError> {
+        try_validate_str(name).map(EntryValue)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
 }
 
-#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+/// Metadata of an `Entry`, ordered by how far its `EntryTtl` lets it propagate.
+#[derive(Clone, Eq, PartialEq, PartialOrd, Ord, Hash, Debug)]
 pub struct EntryMetadata(EntryTtl);
 
 impl EntryMetadata {
     pub fn new(ttl: EntryTtl) -> Self {
         EntryMetadata(ttl)
     }
+
+    pub fn ttl(&self) -> EntryTtl {
+        self.0
+    }
+}
+
+/// Metadata of an entry propagated without limit.
+impl Default for EntryMetadata {
+    fn default() -> Self {
+        EntryMetadata(EntryTtl::UnlimitedPropagation)
+    }
 }
 
 ///
@@ -49,13 +91,25 @@ impl EntryMetadata {
 ///
 /// There could be one or more proxy(ies) between sender and receiver. Proxies are treated as
 /// transparent entities and they are not counted as hops.
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+///
+/// TTLs are ordered by how far they let an entry propagate, `NoPropagation` being the smallest.
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash, Debug)]
 pub enum EntryTtl {
     NoPropagation,
     Propagation(usize),
     UnlimitedPropagation,
 }
 
+impl EntryTtl {
+    /// Returns `true` if an entry with this TTL may be sent to another process.
+    pub fn should_propagate(&self) -> bool {
+        match self {
+            EntryTtl::NoPropagation | EntryTtl::Propagation(0) => false,
+            EntryTtl::Propagation(_) | EntryTtl::UnlimitedPropagation => true,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,4 +128,27 @@ mod tests {
             EntryKey::new(s);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_try_new() {
+        assert_eq!(EntryKey::try_new("key"), Ok(EntryKey::new("key")));
+        assert_eq!(EntryValue::try_new("value"), Ok(EntryValue::new("value")));
+        assert_eq!(EntryKey::try_new("a".repeat(256)), Err(ValidationError::TooLong { len: 256 }));
+        assert_eq!(EntryValue::try_new("a\nb"), Err(ValidationError::NonAscii { index: 1, ch: '\n' }));
+    }
+
+    #[test]
+    fn test_metadata_ttl() {
+        assert_eq!(EntryMetadata::new(EntryTtl::Propagation(2)).ttl(), EntryTtl::Propagation(2));
+        assert_eq!(EntryMetadata::default().ttl(), EntryTtl::UnlimitedPropagation);
+    }
+
+    #[test]
+    fn test_should_propagate() {
+        assert!(!EntryTtl::NoPropagation.should_propagate());
+        assert!(!EntryTtl::Propagation(0).should_propagate());
+        assert!(EntryTtl::Propagation(1).should_propagate());
+        assert!(EntryTtl::UnlimitedPropagation.should_propagate());
+        assert!(EntryMetadata::default().ttl().should_propagate());
+    }
+}
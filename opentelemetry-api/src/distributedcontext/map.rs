@@ -0,0 +1,192 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::collections::hash_map::{Entry as MapEntry, Values};
+
+use super::{DistributedContext, Entry, EntryKey, EntryValue};
+
+/// Entry to keep when both `DistributedContextMap`s of a `merge` hold the same key.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MergePolicy {
+    /// Keep the entry of the map being merged into.
+    PreferSelf,
+    /// Keep the entry of the map being merged.
+    PreferOther,
+}
+
+/// A `DistributedContext` holding its entries in memory, at most one per `EntryKey`.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct DistributedContextMap<'a> {
+    entries: HashMap<EntryKey<'a>, Entry<'a>>,
+}
+
+impl <'a> DistributedContextMap<'a> {
+    pub fn new() -> Self {
+        DistributedContextMap::default()
+    }
+
+    /// Adds an entry, returning the one it displaced if the key was already present.
+    pub fn insert(&mut self, entry: Entry<'a>) -> Option<Entry<'a>> {
+        self.entries.insert(entry.key.clone(), entry)
+    }
+
+    /// Removes and returns the entry with the given key, if any.
+    pub fn remove(&mut self, key: &str) -> Option<Entry<'a>> {
+        self.entries.remove(key)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Entry<'a>> {
+        self.entries.get(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds the entries of `other`, e.g. the context extracted from a request, to this one.
+    ///
+    /// When both hold a key, `policy` picks the entry to keep. If both entries have the same
+    /// value, the one whose `EntryTtl` propagates further is kept instead, whatever the policy, so
+    /// that merging never shortens the reach of a value.
+    ///
+    /// Like `Resource::merge`, this combines the labels of two sources, but lets the caller choose
+    /// which one wins.
+    pub fn merge(&mut self, other: DistributedContextMap<'a>, policy: MergePolicy) {
+        for (key, entry) in other.entries {
+            match self.entries.entry(key) {
+                MapEntry::Vacant(e) => { e.insert(entry); },
+                MapEntry::Occupied(mut e) => {
+                    let replace = if e.get().value == entry.value {
+                        entry.metadata > e.get().metadata
+                    } else {
+                        policy == MergePolicy::PreferOther
+                    };
+                    if replace {
+                        e.insert(entry);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl <'a> DistributedContext<'a> for &'a DistributedContextMap<'a> {
+    type Iter = Values<'a, EntryKey<'a>, Entry<'a>>;
+
+    fn iter(&self) -> Self::Iter {
+        self.entries.values()
+    }
+
+    fn entry_value(&self, key: EntryKey<'_>) -> Option<&EntryValue<'_>> {
+        self.entries.get(key.as_str()).map(|entry| &entry.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distributedcontext::entry::{EntryMetadata, EntryTtl};
+
+    fn entry(key: &'static str, value: &'static str, ttl: EntryTtl) -> Entry<'static> {
+        Entry::new(EntryKey::new(key), EntryValue::new(value), EntryMetadata::new(ttl))
+    }
+
+    fn map(entries: Vec<Entry<'static>>) -> DistributedContextMap<'static> {
+        let mut map = DistributedContextMap::new();
+        for entry in entries {
+            map.insert(entry);
+        }
+        map
+    }
+
+    fn local() -> DistributedContextMap<'static> {
+        map(vec![
+            entry("user", "local", EntryTtl::UnlimitedPropagation),
+            entry("region", "eu", EntryTtl::UnlimitedPropagation),
+        ])
+    }
+
+    fn extracted() -> DistributedContextMap<'static> {
+        map(vec![
+            entry("user", "remote", EntryTtl::Propagation(1)),
+            entry("tenant", "acme", EntryTtl::NoPropagation),
+        ])
+    }
+
+    fn value<'a>(map: &'a DistributedContextMap<'a>, key: &str) -> Option<&'a EntryValue<'a>> {
+        map.get(key).map(|entry| &entry.value)
+    }
+
+    #[test]
+    fn test_merge_prefer_self() {
+        let mut merged = local();
+        merged.merge(extracted(), MergePolicy::PreferSelf);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged.get("user"), Some(&entry("user", "local", EntryTtl::UnlimitedPropagation)));
+        assert_eq!(value(&merged, "region"), Some(&EntryValue::new("eu")));
+        assert_eq!(value(&merged, "tenant"), Some(&EntryValue::new("acme")));
+    }
+
+    #[test]
+    fn test_merge_prefer_other() {
+        let mut merged = local();
+        merged.merge(extracted(), MergePolicy::PreferOther);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged.get("user"), Some(&entry("user", "remote", EntryTtl::Propagation(1))));
+        assert_eq!(value(&merged, "region"), Some(&EntryValue::new("eu")));
+    }
+
+    #[test]
+    fn test_merge_same_value_keeps_longest_ttl() {
+        let short = || map(vec![entry("user", "alice", EntryTtl::Propagation(1))]);
+        let long = || map(vec![entry("user", "alice", EntryTtl::Propagation(3))]);
+
+        for &policy in [MergePolicy::PreferSelf, MergePolicy::PreferOther].iter() {
+            let mut merged = short();
+            merged.merge(long(), policy);
+            assert_eq!(merged, long());
+
+            let mut merged = long();
+            merged.merge(short(), policy);
+            assert_eq!(merged, long());
+        }
+    }
+
+    #[test]
+    fn test_distributed_context() {
+        let map = local();
+        let context = &map;
+        assert_eq!(context.entry_value(EntryKey::new("region")), Some(&EntryValue::new("eu")));
+        assert_eq!(context.entry_value(EntryKey::new("tenant")), None);
+        assert_eq!(context.iter().count(), 2);
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut map = local();
+        let previous = map.insert(entry("user", "other", EntryTtl::NoPropagation));
+        assert_eq!(previous, Some(entry("user", "local", EntryTtl::UnlimitedPropagation)));
+        assert_eq!(map.remove("region"), Some(entry("region", "eu", EntryTtl::UnlimitedPropagation)));
+        assert_eq!(map.remove("region"), None);
+        assert_eq!(map.len(), 1);
+        assert!(!map.is_empty());
+    }
+}
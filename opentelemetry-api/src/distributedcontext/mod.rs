@@ -17,6 +17,9 @@
 pub mod entry;
 pub use entry::{Entry, EntryValue, EntryKey, EntryMetadata};
 
+pub mod map;
+pub use map::{DistributedContextMap, MergePolicy};
+
 /// A map from `EntryKey` to `EntryValue` and `EntryMetadata` that can be used to
 /// label anything that is associated with a specific operation.
 ///
@@ -0,0 +1,354 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Type erased `Meter` and metrics, so any `MeterProvider` can be installed globally.
+
+use std::any::Any;
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::metric::{
+    LabelArityError, LabelValue, Meter, MeterProvider, Metric, MetricBuildError, MetricBuilder,
+    TimeSeries,
+    counter::{Counter, CounterDouble, CounterLong, NoopCounterDouble, NoopCounterLong},
+    gauge::{Gauge, GaugeDouble, GaugeLong, NoopGaugeDouble, NoopGaugeLong},
+    measure::{Measure, MeasureBuilder, Measurement, MeasurementValue},
+};
+
+/// Object safe version of `TimeSeries`, implemented for every `TimeSeries`.
+pub trait DynTimeSeries<V> {
+    fn add_dyn(&self, delta: V);
+    fn set_dyn(&self, val: V);
+}
+
+impl <TS: TimeSeries> DynTimeSeries<TS::V> for TS {
+    fn add_dyn(&self, delta: TS::V) {
+        self.add(delta)
+    }
+
+    fn set_dyn(&self, val: TS::V) {
+        self.set(val)
+    }
+}
+
+/// `TimeSeries` of the boxed metrics, the default `BoxedTimeSeries` records nothing.
+pub struct BoxedTimeSeries<V>(Option<Box<dyn DynTimeSeries<V> + Send + Sync>>);
+
+impl <V> Default for BoxedTimeSeries<V> {
+    fn default() -> Self {
+        BoxedTimeSeries(None)
+    }
+}
+
+impl <V> TimeSeries for BoxedTimeSeries<V> {
+    type V = V;
+
+    fn add(&self, delta: V) {
+        if let Some(ts) = &self.0 {
+            ts.add_dyn(delta)
+        }
+    }
+
+    fn set(&self, val: V) {
+        if let Some(ts) = &self.0 {
+            ts.set_dyn(val)
+        }
+    }
+}
+
+/// Object safe version of `Metric`, implemented for every `Metric` whose `TimeSeries` can be
+/// boxed.
+pub trait DynMetric<V> {
+    fn timeseries_dyn(&self, label_values: Vec<LabelValue>) -> BoxedTimeSeries<V>;
+    fn try_timeseries_dyn(&self, label_values: Vec<LabelValue>) -> Result<BoxedTimeSeries<V>, LabelArityError>;
+    fn default_timeseries_dyn(&self) -> BoxedTimeSeries<V>;
+    fn remove_timeseries_dyn(&self, label_values: Vec<LabelValue>);
+    fn clear_dyn(&self);
+}
+
+impl <M> DynMetric<<M::TS as TimeSeries>::V> for M
+    where M: Metric,
+          M::TS: Send + Sync + 'static
+{
+    fn timeseries_dyn(&self, label_values: Vec<LabelValue>) -> BoxedTimeSeries<<M::TS as TimeSeries>::V> {
+        BoxedTimeSeries(Some(Box::new(self.timeseries(label_values))))
+    }
+
+    fn try_timeseries_dyn(&self, label_values: Vec<LabelValue>)
+        -> Result<BoxedTimeSeries<<M::TS as TimeSeries>::V>, LabelArityError>
+    {
+        let ts = self.try_timeseries(label_values)?;
+        Ok(BoxedTimeSeries(Some(Box::new(ts))))
+    }
+
+    fn default_timeseries_dyn(&self) -> BoxedTimeSeries<<M::TS as TimeSeries>::V> {
+        BoxedTimeSeries(Some(Box::new(self.default_timeseries())))
+    }
+
+    fn remove_timeseries_dyn(&self, label_values: Vec<LabelValue>) {
+        self.remove_timeseries(label_values)
+    }
+
+    fn clear_dyn(&self) {
+        self.clear()
+    }
+}
+
+/// Macro to generate the metrics handed out by `BoxedMeter`
+macro_rules! impl_boxed_metric {
+    ($name:ident, $val:ty, $noop:ty, $($kind:ident),+) => (
+        /// Metric built by a `BoxedMeter`, delegating to the metric of the global `Meter`.
+        ///
+        /// Building it from a `MetricBuilder` that does not come from a `BoxedMeter` results in a
+        /// metric that records nothing. Callbacks are not forwarded, they are ignored like for
+        /// the no-op metrics.
+        pub struct $name(Box<dyn DynMetric<$val> + Send + Sync>);
+
+        impl Metric for $name {
+            type Error = MetricBuildError;
+            type TS = BoxedTimeSeries<$val>;
+
+            fn timeseries(&self, label_values: Vec<LabelValue>) -> Self::TS {
+                self.0.timeseries_dyn(label_values)
+            }
+
+            fn try_timeseries(&self, label_values: Vec<LabelValue>) -> Result<Self::TS, LabelArityError> {
+                self.0.try_timeseries_dyn(label_values)
+            }
+
+            fn default_timeseries(&self) -> Self::TS {
+                self.0.default_timeseries_dyn()
+            }
+
+            fn remove_timeseries(&self, label_values: Vec<LabelValue>) {
+                self.0.remove_timeseries_dyn(label_values)
+            }
+
+            fn clear(&self) {
+                self.0.clear_dyn()
+            }
+
+            fn build(_mb: MetricBuilder<Self>) -> Result<Self, MetricBuildError> {
+                Ok($name(Box::new(<$noop>::default())))
+            }
+        }
+
+        $(impl $kind for $name {})+
+    );
+}
+
+impl_boxed_metric!(BoxedCounterLong, i64, NoopCounterLong, Counter, CounterLong);
+impl_boxed_metric!(BoxedCounterDouble, f64, NoopCounterDouble, Counter, CounterDouble);
+impl_boxed_metric!(BoxedGaugeLong, i64, NoopGaugeLong, Gauge, GaugeLong);
+impl_boxed_metric!(BoxedGaugeDouble, f64, NoopGaugeDouble, Gauge, GaugeDouble);
+
+/// `Measurement` of a `BoxedMeasure`, turned into a measurement of the global `Meter` when
+/// recorded.
+#[derive(Clone)]
+pub struct BoxedMeasurement {
+    measure: BoxedMeasure,
+    value: MeasurementValue,
+}
+
+impl Measurement for BoxedMeasurement {
+    fn value(&self) -> MeasurementValue {
+        self.value
+    }
+}
+
+/// Error returned when the `Meter` of the global provider fails to build a `Measure`, holding the
+/// debug representation of the error it returned.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MeasureBuildError(String);
+
+impl fmt::Display for MeasureBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "measure could not be built: {}", self.0)
+    }
+}
+
+impl Error for MeasureBuildError {}
+
+/// `Measure` built by a `BoxedMeter`, holding the measure of the global `Meter`.
+///
+/// Building it from a `MeasureBuilder` that does not come from a `BoxedMeter` results in a measure
+/// whose values are dropped.
+#[derive(Clone)]
+pub struct BoxedMeasure(Option<Arc<dyn Any + Send + Sync>>);
+
+impl BoxedMeasure {
+    fn downcast<M: Any>(&self) -> Option<&M> {
+        self.0.as_ref().and_then(|measure| measure.downcast_ref::<M>())
+    }
+}
+
+impl Measure for BoxedMeasure {
+    type Measurement = BoxedMeasurement;
+    type Error = MeasureBuildError;
+
+    fn double_measurement(&self, value: f64) -> BoxedMeasurement {
+        BoxedMeasurement { measure: self.clone(), value: MeasurementValue::Double(value) }
+    }
+
+    fn long_measurement(&self, value: i64) -> BoxedMeasurement {
+        BoxedMeasurement { measure: self.clone(), value: MeasurementValue::Long(value) }
+    }
+
+    fn build(_builder: MeasureBuilder<Self>) -> Result<Self, MeasureBuildError> {
+        Ok(BoxedMeasure(None))
+    }
+}
+
+/// Object safe version of `Meter`, implemented for every `Meter` whose metrics can be boxed.
+///
+/// The `Meter` is cloned for every call, so clones must share their registered metrics.
+pub trait DynMeter {
+    fn counter_long_dyn(&self, options: MetricBuilder<BoxedCounterLong>) -> Result<BoxedCounterLong, MetricBuildError>;
+    fn counter_double_dyn(&self, options: MetricBuilder<BoxedCounterDouble>) -> Result<BoxedCounterDouble, MetricBuildError>;
+    fn gauge_long_dyn(&self, options: MetricBuilder<BoxedGaugeLong>) -> Result<BoxedGaugeLong, MetricBuildError>;
+    fn gauge_double_dyn(&self, options: MetricBuilder<BoxedGaugeDouble>) -> Result<BoxedGaugeDouble, MetricBuildError>;
+    fn measure_dyn(&self, options: MeasureBuilder<BoxedMeasure>) -> Result<BoxedMeasure, MeasureBuildError>;
+    fn record_dyn(&self, measurements: Vec<BoxedMeasurement>);
+    fn record_value_dyn(&self, measure: &BoxedMeasure, value: f64, labels: &[LabelValue]);
+}
+
+impl <M> DynMeter for M
+    where M: Meter + Clone,
+          M::CL: Send + Sync + 'static,
+          <M::CL as Metric>::TS: TimeSeries<V=i64> + Send + Sync + 'static,
+          <M::CL as Metric>::Error: Into<MetricBuildError>,
+          M::CD: Send + Sync + 'static,
+          <M::CD as Metric>::TS: TimeSeries<V=f64> + Send + Sync + 'static,
+          <M::CD as Metric>::Error: Into<MetricBuildError>,
+          M::GL: Send + Sync + 'static,
+          <M::GL as Metric>::TS: TimeSeries<V=i64> + Send + Sync + 'static,
+          <M::GL as Metric>::Error: Into<MetricBuildError>,
+          M::GD: Send + Sync + 'static,
+          <M::GD as Metric>::TS: TimeSeries<V=f64> + Send + Sync + 'static,
+          <M::GD as Metric>::Error: Into<MetricBuildError>,
+          M::Measure: Send + Sync + 'static,
+          <M::Measure as Measure>::Error: fmt::Debug,
+{
+    fn counter_long_dyn(&self, options: MetricBuilder<BoxedCounterLong>) -> Result<BoxedCounterLong, MetricBuildError> {
+        let builder = self.clone().counter_long(options.name.clone());
+        let metric = options.apply_options(builder).build().map_err(Into::into)?;
+        Ok(BoxedCounterLong(Box::new(metric)))
+    }
+
+    fn counter_double_dyn(&self, options: MetricBuilder<BoxedCounterDouble>) -> Result<BoxedCounterDouble, MetricBuildError> {
+        let builder = self.clone().counter_double(options.name.clone());
+        let metric = options.apply_options(builder).build().map_err(Into::into)?;
+        Ok(BoxedCounterDouble(Box::new(metric)))
+    }
+
+    fn gauge_long_dyn(&self, options: MetricBuilder<BoxedGaugeLong>) -> Result<BoxedGaugeLong, MetricBuildError> {
+        let builder = self.clone().gauge_long(options.name.clone());
+        let metric = options.apply_options(builder).build().map_err(Into::into)?;
+        Ok(BoxedGaugeLong(Box::new(metric)))
+    }
+
+    fn gauge_double_dyn(&self, options: MetricBuilder<BoxedGaugeDouble>) -> Result<BoxedGaugeDouble, MetricBuildError> {
+        let builder = self.clone().gauge_double(options.name.clone());
+        let metric = options.apply_options(builder).build().map_err(Into::into)?;
+        Ok(BoxedGaugeDouble(Box::new(metric)))
+    }
+
+    fn measure_dyn(&self, options: MeasureBuilder<BoxedMeasure>) -> Result<BoxedMeasure, MeasureBuildError> {
+        let builder = self.clone().measure(options.name.clone());
+        let measure = options.apply_options(builder).build()
+            .map_err(|err| MeasureBuildError(format!("{:?}", err)))?;
+        Ok(BoxedMeasure(Some(Arc::new(measure))))
+    }
+
+    fn record_dyn(&self, measurements: Vec<BoxedMeasurement>) {
+        let measurements = measurements.iter().filter_map(|measurement| {
+            let measure = measurement.measure.downcast::<M::Measure>()?;
+            Some(match measurement.value {
+                MeasurementValue::Double(value) => measure.double_measurement(value),
+                MeasurementValue::Long(value) => measure.long_measurement(value),
+            })
+        });
+        self.clone().record(measurements)
+    }
+
+    fn record_value_dyn(&self, measure: &BoxedMeasure, value: f64, labels: &[LabelValue]) {
+        if let Some(measure) = measure.downcast::<M::Measure>() {
+            self.clone().record_value(measure, value, labels)
+        }
+    }
+}
+
+/// `Meter` returned by `global::meter`, delegating to the `Meter` of the global provider.
+#[derive(Clone)]
+pub struct BoxedMeter(Arc<dyn DynMeter + Send + Sync>);
+
+impl Meter for BoxedMeter {
+    type CL = BoxedCounterLong;
+    type CD = BoxedCounterDouble;
+    type GL = BoxedGaugeLong;
+    type GD = BoxedGaugeDouble;
+    type Measure = BoxedMeasure;
+
+    fn gauge_long<'a, N: Into<Cow<'a, str>>>(&mut self, name: N) -> MetricBuilder<'a, Self::GL> {
+        let meter = self.0.clone();
+        MetricBuilder::new(name).factory(move |options| meter.gauge_long_dyn(options))
+    }
+
+    fn gauge_double<'a, N: Into<Cow<'a, str>>>(&mut self, name: N) -> MetricBuilder<'a, Self::GD> {
+        let meter = self.0.clone();
+        MetricBuilder::new(name).factory(move |options| meter.gauge_double_dyn(options))
+    }
+
+    fn counter_double<'a, N: Into<Cow<'a, str>>>(&mut self, name: N) -> MetricBuilder<'a, Self::CD> {
+        let meter = self.0.clone();
+        MetricBuilder::new(name).factory(move |options| meter.counter_double_dyn(options))
+    }
+
+    fn counter_long<'a, N: Into<Cow<'a, str>>>(&mut self, name: N) -> MetricBuilder<'a, Self::CL> {
+        let meter = self.0.clone();
+        MetricBuilder::new(name).factory(move |options| meter.counter_long_dyn(options))
+    }
+
+    fn measure<'a, N: Into<Cow<'a, str>>>(&mut self, name: N) -> MeasureBuilder<'a, Self::Measure> {
+        let meter = self.0.clone();
+        MeasureBuilder::new(name).factory(move |options| meter.measure_dyn(options))
+    }
+
+    fn record<I>(&mut self, measurements: I) where I: IntoIterator<Item=BoxedMeasurement> {
+        self.0.record_dyn(measurements.into_iter().collect())
+    }
+
+    fn record_value(&mut self, measure: &BoxedMeasure, value: f64, labels: &[LabelValue]) {
+        self.0.record_value_dyn(measure, value, labels)
+    }
+}
+
+/// Object safe version of `MeterProvider`, implemented for every `MeterProvider` whose `Meter`s
+/// can be boxed.
+pub trait DynMeterProvider {
+    fn meter_dyn(&self, name: &str) -> BoxedMeter;
+}
+
+impl <P> DynMeterProvider for P
+    where P: MeterProvider,
+          P::Meter: DynMeter + Send + Sync + 'static
+{
+    fn meter_dyn(&self, name: &str) -> BoxedMeter {
+        BoxedMeter(Arc::new(self.meter(name)))
+    }
+}
@@ -0,0 +1,279 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Process wide `TracerProvider` and `MeterProvider`.
+//!
+//! Instrumented libraries obtain their `Tracer` and `Meter` from here, so the application decides
+//! which implementation is used by installing its providers at startup. Until then, the no-op
+//! providers are used.
+
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+
+use crate::metric::meter::NoopMeterProvider;
+use crate::trace::tracer::NoopTracerProvider;
+
+mod metric;
+pub use self::metric::{
+    BoxedCounterDouble, BoxedCounterLong, BoxedGaugeDouble, BoxedGaugeLong, BoxedMeasure,
+    BoxedMeasurement, BoxedMeter, BoxedTimeSeries, DynMeter, DynMeterProvider, DynMetric,
+    DynTimeSeries, MeasureBuildError,
+};
+
+mod trace;
+pub use self::trace::{BoxedSpan, BoxedTracer, DynSpan, DynTracer, DynTracerProvider};
+
+lazy_static! {
+    static ref GLOBAL_TRACER_PROVIDER: RwLock<Box<dyn DynTracerProvider + Send + Sync>> =
+        RwLock::new(Box::new(NoopTracerProvider));
+    static ref GLOBAL_METER_PROVIDER: RwLock<Box<dyn DynMeterProvider + Send + Sync>> =
+        RwLock::new(Box::new(NoopMeterProvider));
+}
+
+/// Installs the `TracerProvider` used by `tracer`.
+///
+/// Replacing the provider is allowed at any time, however `Tracer`s already obtained through
+/// `tracer` keep using the previous provider. Set the provider before any instrumented code runs.
+pub fn set_tracer_provider<P>(provider: P)
+    where P: DynTracerProvider + Send + Sync + 'static
+{
+    *GLOBAL_TRACER_PROVIDER.write().unwrap() = Box::new(provider);
+}
+
+/// Returns the `Tracer` of the global `TracerProvider` for the given instrumentation library, a
+/// no-op `Tracer` if no provider was installed.
+pub fn tracer(name: &str) -> BoxedTracer {
+    GLOBAL_TRACER_PROVIDER.read().unwrap().tracer_dyn(name)
+}
+
+/// Installs the `MeterProvider` used by `meter`.
+///
+/// Replacing the provider is allowed at any time, however `Meter`s and metrics already obtained
+/// through `meter` keep using the previous provider. Set the provider before any instrumented code
+/// runs.
+pub fn set_meter_provider<P>(provider: P)
+    where P: DynMeterProvider + Send + Sync + 'static
+{
+    *GLOBAL_METER_PROVIDER.write().unwrap() = Box::new(provider);
+}
+
+/// Returns the `Meter` of the global `MeterProvider` for the given instrumentation library, a
+/// no-op `Meter` if no provider was installed.
+pub fn meter(name: &str) -> BoxedMeter {
+    GLOBAL_METER_PROVIDER.read().unwrap().meter_dyn(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+    use std::sync::{Arc, Mutex, MutexGuard};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::metric::{LabelValue, Measure, MeasureBuilder, Meter, MeterProvider, Metric, TimeSeries};
+    use crate::metric::counter::{NoopCounterDouble, NoopCounterLong};
+    use crate::metric::gauge::{NoopGaugeDouble, NoopGaugeLong};
+    use crate::metric::measure::{NoopMeasure, NoopMeasurement};
+    use crate::metric::meter::DefaultMeter;
+    use crate::trace::attribute_value::AttributeValue;
+    use crate::trace::event::Event;
+    use crate::trace::link::Link;
+    use crate::trace::span::{DefaultSpan, Span};
+    use crate::trace::span_builder::SpanBuilder;
+    use crate::trace::span_context::SpanContext;
+    use crate::trace::span_data::SpanData;
+    use crate::trace::status::Status;
+    use crate::trace::tracer::{Tracer, TracerProvider};
+
+    lazy_static! {
+        static ref GLOBAL_LOCK: Mutex<()> = Mutex::new(());
+    }
+
+    /// Serializes the tests installing global providers, and restores the no-op providers when
+    /// dropped so they do not leak into other tests.
+    struct GlobalGuard {
+        _lock: MutexGuard<'static, ()>,
+    }
+
+    fn lock_global() -> GlobalGuard {
+        GlobalGuard { _lock: GLOBAL_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) }
+    }
+
+    impl Drop for GlobalGuard {
+        fn drop(&mut self) {
+            set_tracer_provider(NoopTracerProvider);
+            set_meter_provider(NoopMeterProvider);
+        }
+    }
+
+    struct TestSpan {
+        inner: DefaultSpan,
+        attributes: usize,
+    }
+
+    impl Span for TestSpan {
+        fn set_attribute<'a, K, V>(&mut self, _key: K, _value: V)
+            where K: Into<Cow<'a, str>>,
+                  V: Into<AttributeValue<'a>>
+        {
+            self.attributes += 1;
+        }
+
+        fn add_event<E: Event>(&mut self, _event: E) {}
+        fn add_link<L: Link>(&mut self, _link: L) {}
+        fn set_status(&mut self, _status: Status) {}
+        fn update_name<'a, N: Into<Cow<'a, str>>>(&mut self, _name: N) {}
+        fn end(&mut self) {}
+
+        fn context(&self) -> &SpanContext<'_> {
+            self.inner.context()
+        }
+
+        fn is_recording_events(&self) -> bool {
+            true
+        }
+
+        fn attribute_count(&self) -> usize {
+            self.attributes
+        }
+    }
+
+    impl Drop for TestSpan {
+        fn drop(&mut self) {}
+    }
+
+    struct TestTracer;
+
+    impl Tracer for TestTracer {
+        type Span = TestSpan;
+
+        fn start_span(&self, builder: SpanBuilder<Self>) -> TestSpan {
+            TestSpan { inner: DefaultSpan::invalid(), attributes: builder.attributes.len() }
+        }
+
+        fn record_span_data(&self, _span: SpanData<'static>) {}
+    }
+
+    struct TestTracerProvider;
+
+    impl TracerProvider for TestTracerProvider {
+        type Tracer = TestTracer;
+
+        fn tracer(&self, _name: &str) -> TestTracer {
+            TestTracer
+        }
+    }
+
+    struct TestMeterProvider(Arc<AtomicUsize>);
+
+    impl MeterProvider for TestMeterProvider {
+        type Meter = DefaultMeter;
+
+        fn meter(&self, _name: &str) -> DefaultMeter {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            DefaultMeter
+        }
+    }
+
+    #[derive(Clone)]
+    struct FailingMeasure;
+
+    impl Measure for FailingMeasure {
+        type Measurement = NoopMeasurement;
+        type Error = &'static str;
+
+        fn double_measurement(&self, value: f64) -> NoopMeasurement {
+            NoopMeasure.double_measurement(value)
+        }
+
+        fn long_measurement(&self, value: i64) -> NoopMeasurement {
+            NoopMeasure.long_measurement(value)
+        }
+
+        fn build(_builder: MeasureBuilder<Self>) -> Result<Self, &'static str> {
+            Err("unsupported")
+        }
+    }
+
+    #[derive(Clone)]
+    struct FailingMeter;
+
+    impl Meter for FailingMeter {
+        type CL = NoopCounterLong;
+        type CD = NoopCounterDouble;
+        type GL = NoopGaugeLong;
+        type GD = NoopGaugeDouble;
+        type Measure = FailingMeasure;
+
+        fn record<I>(&mut self, _measurements: I) where I: IntoIterator<Item=NoopMeasurement> {}
+
+        fn record_value(&mut self, _measure: &FailingMeasure, _value: f64, _labels: &[LabelValue]) {}
+    }
+
+    struct FailingMeterProvider;
+
+    impl MeterProvider for FailingMeterProvider {
+        type Meter = FailingMeter;
+
+        fn meter(&self, _name: &str) -> FailingMeter {
+            FailingMeter
+        }
+    }
+
+    #[test]
+    fn test_global_tracer() {
+        let _guard = lock_global();
+        let noop = tracer("test");
+        let span = noop.span_builder("before").attribute("key", "value").start_span();
+        assert!(!span.is_recording_events());
+        assert_eq!(span.attribute_count(), 0);
+
+        set_tracer_provider(TestTracerProvider);
+        let mut span = tracer("test").span_builder("after").attribute("key", "value").start_span();
+        assert!(span.is_recording_events());
+        span.set_attribute("other", 1);
+        assert_eq!(span.attribute_count(), 2);
+
+        // Tracers obtained before the provider was set are not updated
+        assert!(!noop.span_builder("stale").start_span().is_recording_events());
+    }
+
+    #[test]
+    fn test_global_meter() {
+        let _guard = lock_global();
+        let counter = meter("test").counter_long("before").build().unwrap();
+        counter.default_timeseries().add(1);
+        let measure = meter("test").measure("before").build().unwrap();
+        meter("test").record(vec![measure.double_measurement(1.0)]);
+
+        let meters = Arc::new(AtomicUsize::new(0));
+        set_meter_provider(TestMeterProvider(meters.clone()));
+        let mut configured = meter("test");
+        assert_eq!(meters.load(Ordering::SeqCst), 1);
+
+        let measure = configured.measure("after").build().unwrap();
+        configured.record_value(&measure, 1.0, &[]);
+    }
+
+    #[test]
+    fn test_global_measure_build_error() {
+        let _guard = lock_global();
+        set_meter_provider(FailingMeterProvider);
+
+        let err = meter("test").measure("latency").build().err().unwrap();
+        assert_eq!(err.to_string(), "measure could not be built: \"unsupported\"");
+    }
+}
@@ -0,0 +1,214 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Type erased `Tracer` and `Span`, so any `TracerProvider` can be installed globally.
+
+use std::borrow::Cow;
+
+use crate::trace::attribute_value::AttributeValue;
+use crate::trace::event::Event;
+use crate::trace::link::Link;
+use crate::trace::span::Span;
+use crate::trace::span_builder::SpanBuilder;
+use crate::trace::span_context::SpanContext;
+use crate::trace::span_data::{SpanData, SpanEvent, SpanLink};
+use crate::trace::status::Status;
+use crate::trace::tracer::{Tracer, TracerProvider};
+
+/// Object safe version of `Span`, implemented for every `Span`.
+pub trait DynSpan {
+    fn set_attribute_dyn(&mut self, key: Cow<str>, value: AttributeValue);
+    fn add_event_dyn(&mut self, event: &dyn Event);
+    fn add_link_dyn(&mut self, link: &dyn Link);
+    fn set_status_dyn(&mut self, status: Status);
+    fn update_name_dyn(&mut self, name: Cow<str>);
+    fn end_dyn(&mut self);
+    fn context_dyn(&self) -> &SpanContext<'_>;
+    fn is_recording_events_dyn(&self) -> bool;
+    fn attribute_count_dyn(&self) -> usize;
+    fn dropped_attribute_count_dyn(&self) -> usize;
+    fn link_count_dyn(&self) -> usize;
+    fn events_dyn(&self) -> &[SpanEvent];
+    fn links_dyn(&self) -> &[SpanLink];
+}
+
+impl <S: Span> DynSpan for S {
+    fn set_attribute_dyn(&mut self, key: Cow<str>, value: AttributeValue) {
+        self.set_attribute(key, value)
+    }
+
+    fn add_event_dyn(&mut self, event: &dyn Event) {
+        self.add_event(event)
+    }
+
+    fn add_link_dyn(&mut self, link: &dyn Link) {
+        self.add_link(link)
+    }
+
+    fn set_status_dyn(&mut self, status: Status) {
+        self.set_status(status)
+    }
+
+    fn update_name_dyn(&mut self, name: Cow<str>) {
+        self.update_name(name)
+    }
+
+    fn end_dyn(&mut self) {
+        self.end()
+    }
+
+    fn context_dyn(&self) -> &SpanContext<'_> {
+        self.context()
+    }
+
+    fn is_recording_events_dyn(&self) -> bool {
+        self.is_recording_events()
+    }
+
+    fn attribute_count_dyn(&self) -> usize {
+        self.attribute_count()
+    }
+
+    fn dropped_attribute_count_dyn(&self) -> usize {
+        self.dropped_attribute_count()
+    }
+
+    fn link_count_dyn(&self) -> usize {
+        self.link_count()
+    }
+
+    fn events_dyn(&self) -> &[SpanEvent] {
+        self.events()
+    }
+
+    fn links_dyn(&self) -> &[SpanLink] {
+        self.links()
+    }
+}
+
+/// `Span` started by a `BoxedTracer`.
+pub struct BoxedSpan(Box<dyn DynSpan + Send>);
+
+impl Span for BoxedSpan {
+    fn set_attribute<'a, K, V>(&mut self, key: K, value: V)
+        where K: Into<Cow<'a, str>>,
+              V: Into<AttributeValue<'a>>
+    {
+        self.0.set_attribute_dyn(key.into(), value.into())
+    }
+
+    fn add_event<E: Event>(&mut self, event: E) {
+        self.0.add_event_dyn(&event)
+    }
+
+    fn add_link<L: Link>(&mut self, link: L) {
+        self.0.add_link_dyn(&link)
+    }
+
+    fn set_status(&mut self, status: Status) {
+        self.0.set_status_dyn(status)
+    }
+
+    fn update_name<'a, N: Into<Cow<'a, str>>>(&mut self, name: N) {
+        self.0.update_name_dyn(name.into())
+    }
+
+    fn end(&mut self) {
+        self.0.end_dyn()
+    }
+
+    fn context(&self) -> &SpanContext<'_> {
+        self.0.context_dyn()
+    }
+
+    fn is_recording_events(&self) -> bool {
+        self.0.is_recording_events_dyn()
+    }
+
+    fn attribute_count(&self) -> usize {
+        self.0.attribute_count_dyn()
+    }
+
+    fn dropped_attribute_count(&self) -> usize {
+        self.0.dropped_attribute_count_dyn()
+    }
+
+    fn link_count(&self) -> usize {
+        self.0.link_count_dyn()
+    }
+
+    fn events(&self) -> &[SpanEvent] {
+        self.0.events_dyn()
+    }
+
+    fn links(&self) -> &[SpanLink] {
+        self.0.links_dyn()
+    }
+}
+
+impl Drop for BoxedSpan {
+    fn drop(&mut self) {}
+}
+
+/// Object safe version of `Tracer`, implemented for every `Tracer` whose `Span`s can be boxed.
+pub trait DynTracer {
+    fn start_span_dyn(&self, builder: SpanBuilder<BoxedTracer>) -> BoxedSpan;
+
+    fn record_span_data_dyn(&self, span: SpanData<'static>);
+}
+
+impl <T> DynTracer for T
+    where T: Tracer,
+          T::Span: Send + 'static
+{
+    fn start_span_dyn(&self, builder: SpanBuilder<BoxedTracer>) -> BoxedSpan {
+        BoxedSpan(Box::new(builder.with_tracer(self).start_span()))
+    }
+
+    fn record_span_data_dyn(&self, span: SpanData<'static>) {
+        self.record_span_data(span)
+    }
+}
+
+/// `Tracer` returned by `global::tracer`, delegating to the `Tracer` of the global provider.
+pub struct BoxedTracer(Box<dyn DynTracer + Send + Sync>);
+
+impl Tracer for BoxedTracer {
+    type Span = BoxedSpan;
+
+    fn start_span(&self, builder: SpanBuilder<Self>) -> BoxedSpan {
+        self.0.start_span_dyn(builder)
+    }
+
+    fn record_span_data(&self, span: SpanData<'static>) {
+        self.0.record_span_data_dyn(span)
+    }
+}
+
+/// Object safe version of `TracerProvider`, implemented for every `TracerProvider` whose
+/// `Tracer`s can be boxed.
+pub trait DynTracerProvider {
+    fn tracer_dyn(&self, name: &str) -> BoxedTracer;
+}
+
+impl <P> DynTracerProvider for P
+    where P: TracerProvider,
+          P::Tracer: DynTracer + Send + Sync + 'static
+{
+    fn tracer_dyn(&self, name: &str) -> BoxedTracer {
+        BoxedTracer(Box::new(self.tracer(name)))
+    }
+}
@@ -1,12 +1,57 @@
 use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+/// Maximum length in bytes of a validated string, i.e. less than 256 characters.
 const MAX_LEN: usize = 255;
 
-pub(crate) fn validate_and_convert_str<'a, N: Into<Cow<'a, str>>>(to_check: N) -> Cow<'a, str> {
+/// Returns `true` for the printable ASCII characters, codes 32 to 126 inclusive.
+fn is_printable(c: char) -> bool {
+    (32..=126).contains(&(c as u32))
+}
+
+/// The string is too long or contains characters that are not printable ASCII.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ValidationError {
+    /// The string is `len` bytes long, more than the 255 allowed.
+    TooLong { len: usize },
+    /// The character `ch` at position `index` is not printable ASCII.
+    NonAscii { index: usize, ch: char },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::TooLong { len } =>
+                write!(f, "should be an ASCII string not longer than {}, got {} bytes", MAX_LEN, len),
+            ValidationError::NonAscii { index, ch } =>
+                write!(f, "should be a printable ASCII string, contains {:?} at position {}", ch, index),
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+/// Returns `true` if the string would be accepted by `validate_and_convert_str`.
+pub(crate) fn is_valid_str(to_check: &str) -> bool {
+    try_validate_str(to_check).is_ok()
+}
+
+/// Checks that the string is printable ASCII and not longer than 255 characters.
+pub(crate) fn try_validate_str<'a, N: Into<Cow<'a, str>>>(to_check: N) -> Result<Cow<'a, str>, ValidationError> {
     let to_ret = to_check.into();
-    assert!(to_ret.len() < MAX_LEN, "Should be an ASCII string not longer than {}", MAX_LEN);
-    let is_allowed = to_ret.chars().all(|x| !x.is_ascii_control() && x.is_ascii());
-    assert!(is_allowed, "Should be an ASCII string, contains control or none ascii chars");
-    to_ret
+    if to_ret.len() > MAX_LEN {
+        return Err(ValidationError::TooLong { len: to_ret.len() });
+    }
+    if let Some((index, ch)) = to_ret.chars().enumerate().find(|&(_, c)| !is_printable(c)) {
+        return Err(ValidationError::NonAscii { index, ch });
+    }
+    Ok(to_ret)
+}
+
+/// Same as `try_validate_str`, but panics if the string is invalid.
+pub(crate) fn validate_and_convert_str<'a, N: Into<Cow<'a, str>>>(to_check: N) -> Cow<'a, str> {
+    try_validate_str(to_check).unwrap_or_else(|err| panic!("{}", err))
 }
 
 #[cfg(test)]
@@ -26,5 +71,49 @@ mod tests {
         fn test_internal_validate_str_len(s in "[[:ascii:]]{256, 3000}") {
             validate_and_convert_str(s)
         }
+
+        #[test]
+        fn test_internal_validate_str_printable_bounds(s in "[ ~]{0, 255}") {
+            validate_and_convert_str(s)
+        }
+
+        #[test]
+        #[should_panic(expected = "contains '\\u{1f}'")]
+        fn test_internal_validate_str_below_space(s in "[ -~]{0, 100}") {
+            validate_and_convert_str(format!("{}\u{1f}", s))
+        }
+
+        #[test]
+        #[should_panic(expected = "contains '\\u{7f}'")]
+        fn test_internal_validate_str_delete(s in "[ -~]{0, 100}") {
+            validate_and_convert_str(format!("{}\u{7f}", s))
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_internal_validate_str_max_len() {
+        let max = "a".repeat(MAX_LEN);
+        assert_eq!(validate_and_convert_str(max.as_str()), max);
+        assert!(is_valid_str(&max));
+        assert!(!is_valid_str(&"a".repeat(MAX_LEN + 1)));
+    }
+
+    #[test]
+    fn test_internal_try_validate_str() {
+        assert_eq!(try_validate_str("key"), Ok(Cow::Borrowed("key")));
+        assert_eq!(try_validate_str("a".repeat(300)), Err(ValidationError::TooLong { len: 300 }));
+        assert_eq!(try_validate_str("clé"), Err(ValidationError::NonAscii { index: 2, ch: 'é' }));
+    }
+
+    #[test]
+    #[should_panic(expected = "not longer than 255")]
+    fn test_internal_validate_str_over_max_len() {
+        validate_and_convert_str("a".repeat(MAX_LEN + 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "contains '\\t' at position 3")]
+    fn test_internal_validate_str_reports_position() {
+        validate_and_convert_str("key\tvalue");
+    }
+}
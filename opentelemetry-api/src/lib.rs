@@ -1,9 +1,12 @@
 #[macro_use]
 mod internal;
 pub mod resource;
+pub mod semconv;
 pub mod distributedcontext;
 pub mod metric;
 pub mod trace;
 pub mod context;
+pub mod global;
 
+pub use internal::ValidationError;
 pub use resource::{Resource};
\ No newline at end of file
@@ -1,4 +1,10 @@
-use super::{Metric, TimeSeries, MetricBuilder, LabelValue};
+use std::{
+    error::Error,
+    fmt,
+    sync::{Arc, atomic::{AtomicUsize, Ordering}},
+};
+
+use super::{Metric, MetricBuildError, TimeSeries, MetricBuilder, LabelValue};
 
 /// Counter metric, to report instantaneous measurement of a double value.
 /// Cumulative values can go up or stay the same, but can never go down.
@@ -39,13 +45,148 @@ pub trait Counter: Metric {}
 pub trait CounterLong: Counter {}
 pub trait CounterDouble: Counter {}
 
-impl_noop_metric!(NoopCounterLong, NoopTimeSeriesLong);
+/// Error returned when a negative delta is added to a `Counter`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct NegativeDeltaError;
+
+impl fmt::Display for NegativeDeltaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "counters cannot be decremented")
+    }
+}
+
+impl Error for NegativeDeltaError {}
+
+/// `TimeSeries` of a `Counter`, wrapping another `TimeSeries` so it can never go down.
+///
+/// Negative (and NaN) deltas are rejected: `try_add` reports them as an error, while
+/// `TimeSeries::add` ignores them and counts them in `rejected_count`. The same applies to values
+/// given to `try_set` and `TimeSeries::set`. Gauges use their
+/// `TimeSeries` directly and can go negative.
+#[derive(Clone, Default, Debug)]
+pub struct CounterTimeSeries<TS> {
+    inner: TS,
+    rejected: Arc<AtomicUsize>,
+}
+
+impl <TS> CounterTimeSeries<TS>
+    where TS: TimeSeries,
+          TS::V: PartialOrd + Default
+{
+    pub fn new(inner: TS) -> Self {
+        CounterTimeSeries {
+            inner,
+            rejected: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Returns the wrapped `TimeSeries`.
+    pub fn inner(&self) -> &TS {
+        &self.inner
+    }
+
+    /// Adds the given value to the current value, failing if it is negative.
+    pub fn try_add(&self, delta: TS::V) -> Result<(), NegativeDeltaError> {
+        if delta >= TS::V::default() {
+            self.inner.add(delta);
+            Ok(())
+        } else {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            Err(NegativeDeltaError)
+        }
+    }
+
+    /// Sets the current value, failing if it is negative.
+    pub fn try_set(&self, val: TS::V) -> Result<(), NegativeDeltaError> {
+        if val >= TS::V::default() {
+            self.inner.set(val);
+            Ok(())
+        } else {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            Err(NegativeDeltaError)
+        }
+    }
+
+    /// Returns how many deltas and values were rejected for being negative.
+    pub fn rejected_count(&self) -> usize {
+        self.rejected.load(Ordering::Relaxed)
+    }
+}
+
+impl <TS> TimeSeries for CounterTimeSeries<TS>
+    where TS: TimeSeries,
+          TS::V: PartialOrd + Default
+{
+    type V = TS::V;
+
+    /// Adds the given value to the current value, ignoring negative values.
+    fn add(&self, delta: Self::V) {
+        let _ = self.try_add(delta);
+    }
+
+    /// Sets the current value, ignoring negative values.
+    fn set(&self, val: Self::V) {
+        let _ = self.try_set(val);
+    }
+}
+
+impl_noop_metric!(NoopCounterLong, CounterTimeSeries<NoopTimeSeriesLong>);
 impl Counter for NoopCounterLong {}
 impl CounterLong for NoopCounterLong {}
 
-impl_noop_metric!(NoopCounterDouble, NoopTimeSeriesDouble);
+impl_noop_metric!(NoopCounterDouble, CounterTimeSeries<NoopTimeSeriesDouble>);
 impl Counter for NoopCounterDouble {}
 impl CounterDouble for NoopCounterDouble {}
 
 impl_noop_timeseries!(NoopTimeSeriesDouble, f64);
 impl_noop_timeseries!(NoopTimeSeriesLong, i64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[derive(Default)]
+    struct TestTimeSeries {
+        value: Cell<i64>,
+    }
+
+    impl TimeSeries for TestTimeSeries {
+        type V = i64;
+        fn add(&self, delta: i64) { self.value.set(self.value.get() + delta) }
+        fn set(&self, val: i64) { self.value.set(val) }
+    }
+
+    #[test]
+    fn test_rejects_negative_delta() {
+        let ts = CounterTimeSeries::new(TestTimeSeries::default());
+        assert_eq!(ts.try_add(2), Ok(()));
+        assert_eq!(ts.try_add(0), Ok(()));
+        assert_eq!(ts.try_add(-1), Err(NegativeDeltaError));
+        ts.add(-1);
+        assert_eq!(ts.inner().value.get(), 2);
+        assert_eq!(ts.rejected_count(), 2);
+    }
+
+    #[test]
+    fn test_set_rejects_negative_value() {
+        let ts = CounterTimeSeries::new(TestTimeSeries::default());
+        ts.set(5);
+        assert_eq!(ts.try_set(-1), Err(NegativeDeltaError));
+        ts.set(-2);
+        assert_eq!(ts.inner().value.get(), 5);
+        assert_eq!(ts.rejected_count(), 2);
+
+        let ts: CounterTimeSeries<NoopTimeSeriesDouble> = CounterTimeSeries::default();
+        assert_eq!(ts.try_set(f64::NAN), Err(NegativeDeltaError));
+        assert_eq!(ts.try_set(0.0), Ok(()));
+    }
+
+    #[test]
+    fn test_rejects_nan() {
+        let ts: CounterTimeSeries<NoopTimeSeriesDouble> = CounterTimeSeries::default();
+        assert_eq!(ts.try_add(f64::NAN), Err(NegativeDeltaError));
+        assert_eq!(ts.try_add(-0.5), Err(NegativeDeltaError));
+        assert_eq!(ts.try_add(0.5), Ok(()));
+    }
+}
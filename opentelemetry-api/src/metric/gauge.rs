@@ -13,7 +13,7 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
-use super::{Metric, TimeSeries, MetricBuilder, LabelValue};
+use super::{Metric, MetricBuildError, TimeSeries, MetricBuilder, LabelValue};
 
 /// Gauge metric, to report instantaneous measurement of a double value. Gauges can go both up and
 /// down. The gauges values can be negative.
@@ -1,9 +1,10 @@
 /// Macro to make it easy to generate Noop metrics
 macro_rules! impl_noop_metric {
-    ($name:ident, $ts:ident) => (
+    ($name:ident, $ts:ty) => (
+        #[derive(Copy, Clone, Default, Debug)]
         pub struct $name;
         impl Metric for $name {
-            type Error = ();
+            type Error = MetricBuildError;
             type TS = $ts;
 
             fn timeseries(&self, _label_values: Vec<LabelValue>) -> Self::TS {
@@ -14,15 +15,11 @@ macro_rules! impl_noop_metric {
                 Self::TS::default()
             }
 
-            fn remove_timeseries(&self, _label_values: Vec<LabelValue>) {
-                unimplemented!()
-            }
+            fn remove_timeseries(&self, _label_values: Vec<LabelValue>) {}
 
-            fn clear() {
-                unimplemented!()
-            }
+            fn clear(&self) {}
 
-            fn build(_mb: MetricBuilder<Self>) -> Result<Self, ()> {
+            fn build(_mb: MetricBuilder<Self>) -> Result<Self, MetricBuildError> {
                 Ok($name{})
             }
         }
@@ -1,11 +1,36 @@
 use std::borrow::Cow;
 use std::marker::PhantomData;
+use std::sync::Arc;
+
+use super::{LabelKey, LabelValue};
 
 /// Represents a single value recorded for the Measure.
 ///
 /// Measurement *MUST* be treated as immutable short lived object.
 /// Instrumentation logic *MUST NOT* hold on to the object and *MUST* only record it once.
-pub trait Measurement {}
+pub trait Measurement {
+    /// Returns the recorded value.
+    fn value(&self) -> MeasurementValue;
+}
+
+/// The value of a `Measurement`, depending on how it was created.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MeasurementValue {
+    /// Created by `Measure::long_measurement`.
+    Long(i64),
+    /// Created by `Measure::double_measurement`.
+    Double(f64),
+}
+
+impl MeasurementValue {
+    /// Returns the value as a `f64`, large `Long` values lose precision.
+    pub fn as_f64(self) -> f64 {
+        match self {
+            MeasurementValue::Long(value) => value as f64,
+            MeasurementValue::Double(value) => value,
+        }
+    }
+}
 
 /// Measure is a contract between the API exposing the raw measurement and an SDK aggregating these
 /// values into the Metric.
@@ -19,31 +44,59 @@ pub trait Measure {
     ///
     /// # Panics
     /// * if the type is not {@link Measure.Type#DOUBLE}.
-    fn double_measurement<'a>(value: f64) -> Self::Measurement;
+    fn double_measurement(&self, value: f64) -> Self::Measurement;
 
     /// Returns a new `Measurement` for this `Measure`.
     ///
     /// # Panics
     /// * if the type is not {@link Measure.Type#LONG}.
-    fn long_measurement<'a>(value: i64) -> Self::Measurement;
+    fn long_measurement(&self, value: i64) -> Self::Measurement;
+
+    /// Returns a new double `Measurement` for this `Measure`, with the given label values.
+    ///
+    /// Measures that do not aggregate by label ignore the label values, which is the default.
+    fn double_measurement_with_labels(&self, value: f64, _labels: &[LabelValue]) -> Self::Measurement {
+        self.double_measurement(value)
+    }
 
     fn build(builder: MeasureBuilder<Self>) -> Result<Self, Self::Error>
         where Self: Sized;
 }
 
+/// Function building a `Measure` from a `MeasureBuilder`, set with `MeasureBuilder::factory`.
+pub type MeasureFactory<'a, M> =
+    Arc<dyn Fn(MeasureBuilder<'a, M>) -> Result<M, <M as Measure>::Error> + Send + Sync + 'a>;
+
 pub struct MeasureBuilder<'a, M: Measure> {
     pub name: Cow<'a, str>,
     pub description: Cow<'a, str>,
     pub unit: Cow<'a, str>,
+    pub label_keys: Vec<LabelKey<'a>>,
+    factory: Option<MeasureFactory<'a, M>>,
     _factory: PhantomData<M>,
 }
 
+impl <'a, M: Measure> Clone for MeasureBuilder<'a, M> {
+    fn clone(&self) -> Self {
+        MeasureBuilder {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            unit: self.unit.clone(),
+            label_keys: self.label_keys.clone(),
+            factory: self.factory.clone(),
+            _factory: PhantomData,
+        }
+    }
+}
+
 impl <'a, M: Measure> MeasureBuilder<'a, M> {
     pub fn new<N: Into<Cow<'a, str>>>(name: N) -> Self {
         MeasureBuilder {
             name: name.into(),
             description: "".into(),
             unit: "1".into(),
+            label_keys: Vec::default(),
+            factory: None,
             _factory: PhantomData,
         }
     }
@@ -75,29 +128,70 @@ impl <'a, M: Measure> MeasureBuilder<'a, M> {
         self
     }
 
+    /// Sets the list of label keys, naming the label values the measurements are recorded with.
+    ///
+    /// Default value is `[]`
+    pub fn label_keys(mut self, label_keys: Vec<LabelKey<'a>>) -> Self {
+        self.label_keys = label_keys;
+        self
+    }
+
+    /// Sets the function building the `Measure` from this builder, in place of `Measure::build`.
+    ///
+    /// `Meter` implementations use this to register the measures created from their builders, to
+    /// return an equivalent one that is already registered, or to forward the options to another
+    /// `Meter`.
+    ///
+    /// Clones of this builder share the factory.
+    pub fn factory<F>(mut self, factory: F) -> Self
+        where F: Fn(MeasureBuilder<'a, M>) -> Result<M, M::Error> + Send + Sync + 'a
+    {
+        self.factory = Some(Arc::new(factory));
+        self
+    }
+
+    /// Copies the options of this builder, except the name, onto the builder of another `Measure`.
+    pub(crate) fn apply_options<N: Measure>(self, builder: MeasureBuilder<'a, N>) -> MeasureBuilder<'a, N> {
+        MeasureBuilder {
+            description: self.description,
+            unit: self.unit,
+            label_keys: self.label_keys,
+            ..builder
+        }
+    }
+
     pub fn build(mut self) -> Result<M, M::Error> {
-        M::build(self)
+        match self.factory.take() {
+            Some(factory) => factory(self),
+            None => M::build(self),
+        }
     }
 
 }
 
-pub struct NoopMeasurement;
-impl Measurement for NoopMeasurement {}
+#[derive(Copy, Clone, Debug)]
+pub struct NoopMeasurement(MeasurementValue);
+impl Measurement for NoopMeasurement {
+    fn value(&self) -> MeasurementValue {
+        self.0
+    }
+}
 
+#[derive(Copy, Clone, Default, Debug)]
 pub struct NoopMeasure;
 impl Measure for NoopMeasure {
     type Measurement = NoopMeasurement;
     type Error = ();
 
-    fn double_measurement<'a>(value: f64) -> Self::Measurement {
-        unimplemented!()
+    fn double_measurement(&self, value: f64) -> Self::Measurement {
+        NoopMeasurement(MeasurementValue::Double(value))
     }
 
-    fn long_measurement<'a>(value: i64) -> Self::Measurement {
-        unimplemented!()
+    fn long_measurement(&self, value: i64) -> Self::Measurement {
+        NoopMeasurement(MeasurementValue::Long(value))
     }
 
-    fn build(builder: MeasureBuilder<Self>) -> Result<Self, Self::Error> where Self: Sized {
-        unimplemented!()
+    fn build(_builder: MeasureBuilder<Self>) -> Result<Self, Self::Error> where Self: Sized {
+        Ok(NoopMeasure)
     }
 }
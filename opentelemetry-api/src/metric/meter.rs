@@ -1,6 +1,7 @@
 use std::borrow::Cow;
+use std::iter;
 use crate::metric::{
-    MetricBuilder,
+    LabelValue, MetricBuilder,
     CounterDouble, CounterLong, GaugeDouble, GaugeLong,
     counter, gauge, measure,
     measure::{Measure, MeasureBuilder},
@@ -112,7 +113,7 @@ pub trait Meter {
     ///
     /// # Panics
     /// * if different metric with the same name already registered.
-    fn gauge_long<'a, N: Into<Cow<'a, str>>>(name: N) -> MetricBuilder<'a, Self::GL> {
+    fn gauge_long<'a, N: Into<Cow<'a, str>>>(&mut self, name: N) -> MetricBuilder<'a, Self::GL> {
         MetricBuilder::new(name)
     }
 
@@ -120,7 +121,7 @@ pub trait Meter {
     ///
     /// # Panics
     /// * if different metric with the same name already registered.
-    fn gauge_double<'a, N: Into<Cow<'a, str>>>(name: N) -> MetricBuilder<'a, Self::GD> {
+    fn gauge_double<'a, N: Into<Cow<'a, str>>>(&mut self, name: N) -> MetricBuilder<'a, Self::GD> {
         MetricBuilder::new(name)
     }
 
@@ -150,6 +151,28 @@ pub trait Meter {
     fn record<I>(&mut self, measurements: I)
         where I: IntoIterator<Item=<<Self as Meter>::Measure as Measure>::Measurement>;
 
+    /// Records a single value for the given `Measure` with the given label values, with the
+    /// current `opentelemetry.distributedcontext.DistributedContextManager::current_context()`.
+    ///
+    /// Shorthand for `record` with the single `double_measurement_with_labels` of the value.
+    fn record_value(&mut self, measure: &Self::Measure, value: f64, labels: &[LabelValue]) {
+        self.record(iter::once(measure.double_measurement_with_labels(value, labels)));
+    }
+
+    /// Records a single double value for the given `Measure`, with no label values.
+    ///
+    /// Shorthand for `record` with the single `double_measurement` of the value.
+    fn record_double(&mut self, measure: &Self::Measure, value: f64) {
+        self.record(iter::once(measure.double_measurement(value)));
+    }
+
+    /// Records a single long value for the given `Measure`, with no label values.
+    ///
+    /// Shorthand for `record` with the single `long_measurement` of the value.
+    fn record_long(&mut self, measure: &Self::Measure, value: i64) {
+        self.record(iter::once(measure.long_measurement(value)));
+    }
+
     /*
 
     /// Records all given measurements, with an explicit `DistributedContext`.
@@ -165,6 +188,7 @@ pub trait Meter {
     */
 }
 
+#[derive(Copy, Clone, Default, Debug)]
 pub struct DefaultMeter;
 
 impl Meter for DefaultMeter {
@@ -174,8 +198,26 @@ impl Meter for DefaultMeter {
     type GD = gauge::NoopGaugeDouble;
     type Measure = measure::NoopMeasure;
 
-    fn record<I>(&mut self, measurements: I) where I: IntoIterator<Item=measure::NoopMeasurement> {
-        unimplemented!()
+    fn record<I>(&mut self, _measurements: I) where I: IntoIterator<Item=measure::NoopMeasurement> {}
+}
+
+/// Provides named `Meter`s, usually one per instrumented library.
+pub trait MeterProvider {
+    type Meter: Meter;
+
+    /// Returns the `Meter` for the instrumentation library with the given name.
+    fn meter(&self, name: &str) -> Self::Meter;
+}
+
+/// `MeterProvider` of `DefaultMeter`s, which record nothing.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct NoopMeterProvider;
+
+impl MeterProvider for NoopMeterProvider {
+    type Meter = DefaultMeter;
+
+    fn meter(&self, _name: &str) -> DefaultMeter {
+        DefaultMeter
     }
 }
 
@@ -186,6 +228,6 @@ mod tests {
     #[test]
     fn test_noops() {
         let mut m = DefaultMeter{};
-        let counter = m.counter_long("test").build().unwrap();
+        let _counter = m.counter_long("test").build().unwrap();
     }
 }
\ No newline at end of file
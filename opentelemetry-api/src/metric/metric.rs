@@ -1,14 +1,18 @@
 use std::{
     collections::HashMap,
     borrow::Cow,
+    error::Error,
+    fmt,
     marker::PhantomData,
+    sync::Arc,
 };
 
 use crate::Resource;
+use crate::internal::is_valid_str;
 use super::{LabelValue, LabelKey};
 
 pub trait Metric {
-    type Error;
+    type Error: From<MetricBuildError>;
     type TS: TimeSeries;
 
     /// Creates a `TimeSeries` and returns a `TimeSeries` if the specified `labelValues` is not
@@ -26,14 +30,28 @@ pub trait Metric {
     /// * if the number of `labelValues`s are not equal to the label keys.
     fn timeseries(&self, label_values: Vec<LabelValue>) -> Self::TS;
 
+    /// Same as `timeseries`, but returns an error instead of panicking if the number of
+    /// `labelValues`s are not equal to the label keys.
+    ///
+    /// The default implementation does not check the number of label values.
+    fn try_timeseries(&self, label_values: Vec<LabelValue>) -> Result<Self::TS, LabelArityError> {
+        Ok(self.timeseries(label_values))
+    }
+
     /// Returns a `TimeSeries` for a metric with all labels not set (default label value).
     fn default_timeseries(&self) -> Self::TS;
 
-    /// Sets a callback that gets executed every time before exporting this metric.
+    /// Sets a callback that gets executed every time before exporting this metric, to set the
+    /// values of its `TimeSeries`, replacing any previous callback.
     ///
     /// Evaluation is deferred until needed, if this `Metric` is not exported then it will never
-    /// be called.
-    //fn set_callback<F>(metric_updater: F) where F: FnOnce(&Metric) -> &Metric;
+    /// be called. The default implementation ignores the callback, as for metrics that are never
+    /// exported.
+    fn set_callback<F>(&self, _callback: F)
+        where F: Fn(&Self) + Send + Sync + 'static,
+              Self: Sized
+    {
+    }
 
     /// Removes the `TimeSeries` from the metric, if it is present.
     ///
@@ -43,13 +61,58 @@ pub trait Metric {
     /// Removes all `TimeSeries` from the metric
     ///
     /// i.e. references to all previous `TimeSeries` are invalid (not part of the metric).
-    fn clear();
+    fn clear(&self);
 
     /// Builds instances of metrics from a given MetricBuilder
     fn build(mb: MetricBuilder<Self>) -> Result<Self, Self::Error>
         where Self: Sized;
 }
 
+/// The number of label values given for a `TimeSeries` differs from the number of label keys of
+/// its `Metric`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct LabelArityError {
+    pub expected: usize,
+    pub actual: usize,
+}
+
+impl fmt::Display for LabelArityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected {} label values, got {}", self.expected, self.actual)
+    }
+}
+
+impl Error for LabelArityError {}
+
+/// The options of a `MetricBuilder` are invalid.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum MetricBuildError {
+    /// The name of the metric is empty.
+    EmptyName,
+    /// The name of the metric is not a printable ASCII string shorter than 256 characters.
+    InvalidName(String),
+    /// The unit of the metric is empty or not an ASCII string.
+    InvalidUnit(String),
+}
+
+impl fmt::Display for MetricBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MetricBuildError::EmptyName => write!(f, "metric name cannot be empty"),
+            MetricBuildError::InvalidName(name) =>
+                write!(f, "metric name {:?} should be a printable ASCII string shorter than 256 characters", name),
+            MetricBuildError::InvalidUnit(unit) =>
+                write!(f, "metric unit {:?} should be a non empty ASCII string", unit),
+        }
+    }
+}
+
+impl Error for MetricBuildError {}
+
+/// Function building a `Metric` from a `MetricBuilder`, set with `MetricBuilder::factory`.
+pub type MetricFactory<'a, M> =
+    Arc<dyn Fn(MetricBuilder<'a, M>) -> Result<M, <M as Metric>::Error> + Send + Sync + 'a>;
+
 pub struct MetricBuilder<'a, M: Metric> {
     pub name: Cow<'a, str>,
     pub description: Cow<'a, str>,
@@ -58,10 +121,29 @@ pub struct MetricBuilder<'a, M: Metric> {
     pub constant_labels: HashMap<LabelKey<'a>, LabelValue<'a>>,
     pub component: Option<Cow<'a, str>>,
     pub resource: Option<Resource<'a>>,
+    pub range: Option<(f64, f64)>,
+    factory: Option<MetricFactory<'a, M>>,
 
     _factory: PhantomData<M>,
 }
 
+impl <'a, M: Metric> Clone for MetricBuilder<'a, M> {
+    fn clone(&self) -> Self {
+        MetricBuilder {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            unit: self.unit.clone(),
+            label_keys: self.label_keys.clone(),
+            constant_labels: self.constant_labels.clone(),
+            component: self.component.clone(),
+            resource: self.resource.clone(),
+            range: self.range,
+            factory: self.factory.clone(),
+            _factory: PhantomData,
+        }
+    }
+}
+
 impl <'a, M: Metric> MetricBuilder<'a, M> {
 
     pub fn new<N: Into<Cow<'a, str>>>(name: N) -> Self {
@@ -73,6 +155,8 @@ impl <'a, M: Metric> MetricBuilder<'a, M> {
             constant_labels: HashMap::default(),
             component: None,
             resource: None,
+            range: None,
+            factory: None,
             _factory: PhantomData
         }
     }
@@ -131,9 +215,82 @@ impl <'a, M: Metric> MetricBuilder<'a, M> {
         self
     }
 
+    /// Sets the inclusive range of values this `Metric` can legitimately report.
+    ///
+    /// Only honoured by gauges, values outside of the range are clamped to it and counted as
+    /// out of range. Default value is `None`, i.e. unbounded.
+    ///
+    /// # Panics
+    /// * if `min` is greater than `max`.
+    pub fn range(mut self, min: f64, max: f64) -> Self {
+        assert!(min <= max, "Range minimum {} is greater than maximum {}", min, max);
+        self.range = Some((min, max));
+        self
+    }
+
+    /// Sets the function building the `Metric` from this builder, in place of `Metric::build`.
+    ///
+    /// `Meter` implementations use this to register the metrics created from their builders, to
+    /// return an equivalent one that is already registered, or to forward the options to another
+    /// `Meter`.
+    ///
+    /// Clones of this builder share the factory.
+    pub fn factory<F>(mut self, factory: F) -> Self
+        where F: Fn(MetricBuilder<'a, M>) -> Result<M, M::Error> + Send + Sync + 'a
+    {
+        self.factory = Some(Arc::new(factory));
+        self
+    }
+
+    /// Copies the options of this builder, except the name, onto the builder of another `Metric`.
+    pub(crate) fn apply_options<N: Metric>(self, builder: MetricBuilder<'a, N>) -> MetricBuilder<'a, N> {
+        MetricBuilder {
+            description: self.description,
+            unit: self.unit,
+            label_keys: self.label_keys,
+            constant_labels: self.constant_labels,
+            component: self.component,
+            resource: self.resource,
+            range: self.range,
+            ..builder
+        }
+    }
+
+    /// Returns the name the `Metric` is reported with, `component + "_" + name` if a non empty
+    /// component is set, `name` otherwise.
+    pub fn full_name(&self) -> Cow<'_, str> {
+        match &self.component {
+            Some(component) if !component.is_empty() => format!("{}_{}", component, self.name).into(),
+            _ => Cow::Borrowed(&self.name),
+        }
+    }
+
+    /// Checks that the name and unit are valid.
+    ///
+    /// The name must be a non empty, printable ASCII string shorter than 256 characters and the
+    /// unit a non empty ASCII string, e.g. `"1"`, `"ms"` or `"By/s"`.
+    pub fn validate(&self) -> Result<(), MetricBuildError> {
+        if self.name.is_empty() {
+            return Err(MetricBuildError::EmptyName);
+        }
+        if !is_valid_str(&self.name) {
+            return Err(MetricBuildError::InvalidName(self.name.to_string()));
+        }
+        if self.unit.is_empty() || !self.unit.is_ascii() {
+            return Err(MetricBuildError::InvalidUnit(self.unit.to_string()));
+        }
+        Ok(())
+    }
+
     /// Builds and returns a metric with the desired options.
-    pub fn build(self) -> Result<M, M::Error> {
-        M::build(self)
+    ///
+    /// Fails with a `MetricBuildError` if the options do not pass `validate`.
+    pub fn build(mut self) -> Result<M, M::Error> {
+        self.validate()?;
+        match self.factory.take() {
+            Some(factory) => factory(self),
+            None => M::build(self),
+        }
     }
 }
 
@@ -150,3 +307,81 @@ pub trait TimeSeries: Default {
     fn set(&self, val: Self::V);
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metric::counter::NoopCounterLong;
+
+    #[test]
+    fn test_build_valid() {
+        let built = MetricBuilder::<NoopCounterLong>::new("requests").unit("By/s").build();
+        assert!(built.is_ok());
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut constant_labels = HashMap::new();
+        constant_labels.insert(
+            LabelKey { key: "host".into(), description: "".into() },
+            LabelValue::new("localhost"),
+        );
+        let builder = MetricBuilder::<NoopCounterLong>::new("requests")
+            .label_keys(vec![LabelKey { key: "method".into(), description: "HTTP method".into() }])
+            .constant_labels(constant_labels)
+            .component("http");
+
+        let cloned = builder.clone();
+        assert_eq!(cloned.full_name(), "http_requests");
+        assert_eq!(cloned.label_keys, builder.label_keys);
+        assert_eq!(cloned.constant_labels, builder.constant_labels);
+        assert!(cloned.build().is_ok());
+    }
+
+    #[test]
+    fn test_builder_with_factory_is_send() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+        let builder = MetricBuilder::<NoopCounterLong>::new("requests")
+            .factory(|builder| Metric::build(builder));
+        assert_send_sync(&builder);
+        assert!(std::thread::spawn(move || builder.build().is_ok()).join().unwrap());
+    }
+
+    #[test]
+    fn test_full_name() {
+        let builder = MetricBuilder::<NoopCounterLong>::new("requests");
+        assert_eq!(builder.full_name(), "requests");
+
+        let builder = builder.component("http");
+        assert_eq!(builder.full_name(), "http_requests");
+
+        let builder = builder.component("");
+        assert_eq!(builder.full_name(), "requests");
+    }
+
+    #[test]
+    fn test_rejects_empty_name() {
+        let built = MetricBuilder::<NoopCounterLong>::new("").build();
+        assert_eq!(built.err(), Some(MetricBuildError::EmptyName));
+    }
+
+    #[test]
+    fn test_rejects_invalid_name() {
+        let built = MetricBuilder::<NoopCounterLong>::new("requêtes").build();
+        assert_eq!(built.err(), Some(MetricBuildError::InvalidName("requêtes".to_string())));
+
+        let long = "a".repeat(300);
+        let built = MetricBuilder::<NoopCounterLong>::new(long.as_str()).build();
+        assert_eq!(built.err(), Some(MetricBuildError::InvalidName(long)));
+    }
+
+    #[test]
+    fn test_rejects_invalid_unit() {
+        let built = MetricBuilder::<NoopCounterLong>::new("latency").unit("µs").build();
+        assert_eq!(built.err(), Some(MetricBuildError::InvalidUnit("µs".to_string())));
+
+        let built = MetricBuilder::<NoopCounterLong>::new("latency").unit("").build();
+        assert_eq!(built.err(), Some(MetricBuildError::InvalidUnit("".to_string())));
+    }
+}
@@ -4,22 +4,22 @@ use std::borrow::Cow;
 mod macros;
 
 pub mod metric;
-pub use metric::{Metric, MetricBuilder, TimeSeries};
+pub use metric::{LabelArityError, Metric, MetricBuildError, MetricBuilder, TimeSeries};
 
 pub mod meter;
-pub use meter::Meter;
+pub use meter::{Meter, MeterProvider};
 
 pub mod measure;
-pub use measure::{Measurement, Measure, MeasureBuilder};
+pub use measure::{Measurement, MeasurementValue, Measure, MeasureBuilder};
 
 pub mod gauge;
 pub use gauge::{Gauge, GaugeDouble, GaugeLong};
 
 pub mod counter;
-pub use counter::{Counter, CounterDouble, CounterLong};
+pub use counter::{Counter, CounterDouble, CounterLong, CounterTimeSeries, NegativeDeltaError};
 
 /// Defines a label key associated with a metric descriptor.
-#[derive(Hash, Eq, PartialEq)]
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
 pub struct LabelKey<'a> {
     /// The key for the label.
     pub key: Cow<'a, str>,
@@ -28,6 +28,7 @@ pub struct LabelKey<'a> {
     pub description: Cow<'a, str>,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct LabelValue<'a> {
     /// The value for the label.
     pub value: Cow<'a, str>,
@@ -37,6 +38,18 @@ pub struct LabelValue<'a> {
     pub has_value: bool,
 }
 
+impl <'a> LabelValue<'a> {
+    /// Creates a `LabelValue` set to the given value, which may be an empty string.
+    pub fn new<V: Into<Cow<'a, str>>>(value: V) -> Self {
+        LabelValue { value: value.into(), has_value: true }
+    }
+
+    /// Creates a `LabelValue` that is not set, the label takes its default value.
+    pub fn empty() -> Self {
+        LabelValue { value: "".into(), has_value: false }
+    }
+}
+
 #[cfg(test)]
 mod test {
 
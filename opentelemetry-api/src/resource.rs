@@ -29,27 +29,38 @@
 //! `k8s.io/namespace/name`.
 use std::{
     borrow::Cow,
-    collections::{HashMap, hash_map::Entry},
+    env,
+    collections::{HashMap, hash_map::{self, Entry}},
+    hash::{Hash, Hasher},
+    process,
 };
 
-use crate::internal::validate_and_convert_str;
+use crate::internal::{try_validate_str, validate_and_convert_str};
+use crate::semconv;
 
-#[derive(Default)]
+/// Resources are equal, and hash equally, when they hold the same labels, whatever the order in
+/// which they were added, and the same schema URL, an empty one being the same as none.
+#[derive(Clone, Default, Debug)]
 pub struct Resource<'a> {
-    pub labels: HashMap<Cow<'a, str>, Cow<'a, str>>
+    pub labels: HashMap<Cow<'a, str>, Cow<'a, str>>,
+    /// Set with `with_schema_url` and read with `schema_url`, which treats an empty URL as none.
+    schema_url: Option<Cow<'a, str>>,
 }
 
 impl <'a> Resource<'a> {
     fn new(labels: HashMap<Cow<'a, str>, Cow<'a, str>>) -> Self {
-        Resource { labels }
+        Resource { labels, schema_url: None }
     }
 
     /// Creates a new Resource out of the collection of labels
     ///
+    /// Keys are unique, labels added later with an existing key replace the earlier value (last
+    /// wins), see `Resource::insert`.
+    ///
     /// # Panics
     /// If the following hold
     ///
-    /// * The length of a key or value is _over_ 256 bytes
+    /// * The length of a key or value is 256 bytes or more
     /// * If a key or value contains none ascii chars
     pub fn create<K, V>(labels: HashMap<K, V>) -> Self
         where K: Into<Cow<'a, str>>, V: Into<Cow<'a, str>>
@@ -60,6 +71,42 @@ impl <'a> Resource<'a> {
         Resource::new(labels)
     }
 
+    /// Adds a label, returning the value it displaced if the key was already present.
+    ///
+    /// The last inserted value for a key wins.
+    ///
+    /// # Panics
+    /// If the key or value are invalid, see `Resource::create`.
+    pub fn insert<K, V>(&mut self, key: K, value: V) -> Option<Cow<'a, str>>
+        where K: Into<Cow<'a, str>>, V: Into<Cow<'a, str>>
+    {
+        self.labels.insert(validate_and_convert_str(key), validate_and_convert_str(value))
+    }
+
+    /// Creates a Resource describing a service, with the `service.name` and `service.version`
+    /// labels of the semantic conventions.
+    ///
+    /// # Panics
+    /// If the name or version are invalid, see `Resource::create`.
+    pub fn service<N, V>(name: N, version: V) -> Self
+        where N: Into<Cow<'a, str>>, V: Into<Cow<'a, str>>
+    {
+        Resource::empty()
+            .with_label(semconv::SERVICE_NAME, name)
+            .with_label(semconv::SERVICE_VERSION, version)
+    }
+
+    /// Adds a label and returns the `Resource`, for chaining. The last value given for a key wins.
+    ///
+    /// # Panics
+    /// If the key or value are invalid, see `Resource::create`.
+    pub fn with_label<K, V>(mut self, key: K, value: V) -> Self
+        where K: Into<Cow<'a, str>>, V: Into<Cow<'a, str>>
+    {
+        self.insert(key, value);
+        self
+    }
+
     /// Creates a new Resource that is a combination of labels of two Resources.
     ///
     /// For example, from two Resources - one representing the host and one representing a container,
@@ -68,7 +115,13 @@ impl <'a> Resource<'a> {
     /// Already set labels *WILL NOT* be overwritten unless they are empty string.
     /// Label key name-spacing SHOULD be used to prevent collisions across different resource
     /// detection steps.
+    ///
+    /// Likewise, the schema URL of `other` is only kept if this `Resource` has none, or an empty
+    /// one.
     pub fn merge(&mut self, other: Self) {
+        if self.schema_url().is_none() {
+            self.schema_url = other.schema_url;
+        }
         other.labels
             .into_iter()
             .for_each(|(key, value)| {
@@ -83,6 +136,15 @@ impl <'a> Resource<'a> {
             });
     }
 
+    /// Runs the detectors and merges the resources they detect, from left to right, so labels
+    /// detected first take precedence as with `merge`.
+    pub fn detect(detectors: &[&dyn ResourceDetector]) -> Resource<'static> {
+        detectors.iter().fold(Resource::empty(), |mut resource, detector| {
+            resource.merge(detector.detect());
+            resource
+        })
+    }
+
     pub fn labels(&self) -> HashMap<&str, &str> {
         self.labels
             .iter()
@@ -95,14 +157,250 @@ impl <'a> Resource<'a> {
         self.labels.get(label).map(|x| x.as_ref())
     }
 
+    /// Returns true if the resource has a label with the given key
+    pub fn contains_key(&self, label: &str) -> bool {
+        self.labels.contains_key(label)
+    }
+
+    /// Removes the label with the given key, returning its value if it was set
+    pub fn remove(&mut self, label: &str) -> Option<Cow<'a, str>> {
+        self.labels.remove(label)
+    }
+
+    /// Sets the URL of the schema of the semantic conventions the labels follow, e.g.
+    /// `https://opentelemetry.io/schemas/1.21.0`.
+    pub fn with_schema_url<U: Into<Cow<'a, str>>>(mut self, schema_url: U) -> Self {
+        self.schema_url = Some(schema_url.into());
+        self
+    }
+
+    /// Returns the schema URL, `None` if it is not set or empty.
+    pub fn schema_url(&self) -> Option<&str> {
+        self.schema_url.as_deref().filter(|url| !url.is_empty())
+    }
+
+    /// Iterates over the labels as `(key, value)` pairs, in no particular order
+    pub fn iter(&self) -> Iter<'_, 'a> {
+        Iter(self.labels.iter())
+    }
+
     pub fn empty() -> Self {
         Resource::default()
     }
+
+    /// Converts this `Resource` into one that owns its labels and schema URL.
+    pub fn into_owned(self) -> Resource<'static> {
+        Resource {
+            labels: self.labels.into_iter()
+                .map(|(key, value)| (Cow::Owned(key.into_owned()), Cow::Owned(value.into_owned())))
+                .collect(),
+            schema_url: self.schema_url.map(|url| Cow::Owned(url.into_owned())),
+        }
+    }
+}
+
+/// Iterator over the labels of a `Resource`, created by `Resource::iter`.
+#[derive(Clone, Debug)]
+pub struct Iter<'r, 'a>(hash_map::Iter<'r, Cow<'a, str>, Cow<'a, str>>);
+
+impl <'r, 'a> Iterator for Iter<'r, 'a> {
+    type Item = (&'r str, &'r str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, v)| (k.as_ref(), v.as_ref()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl <'r, 'a> ExactSizeIterator for Iter<'r, 'a> {}
+
+impl <'r, 'a> IntoIterator for &'r Resource<'a> {
+    type Item = (&'r str, &'r str);
+    type IntoIter = Iter<'r, 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl <'a> PartialEq for Resource<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.labels == other.labels && self.schema_url() == other.schema_url()
+    }
+}
+
+impl <'a> Eq for Resource<'a> {}
+
+impl <'a> Hash for Resource<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut labels: Vec<_> = self.labels.iter().collect();
+        labels.sort();
+        labels.hash(state);
+        self.schema_url().hash(state);
+    }
+}
+
+/// Serializes the labels as a map sorted by key, and the schema URL as `schema_url` when it is
+/// set and not empty.
+#[cfg(feature = "serde")]
+impl <'a> serde::Serialize for Resource<'a> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        use std::collections::BTreeMap;
+
+        let labels: BTreeMap<&str, &str> = self.iter().collect();
+        let schema_url = self.schema_url();
+        let mut state = serializer.serialize_struct("Resource", 1 + schema_url.iter().count())?;
+        state.serialize_field("labels", &labels)?;
+        if let Some(schema_url) = schema_url {
+            state.serialize_field("schema_url", schema_url)?;
+        }
+        state.end()
+    }
+}
+
+/// Detects a `Resource` describing the environment the process runs in.
+pub trait ResourceDetector {
+    /// Returns the detected labels, an empty `Resource` if nothing was detected.
+    fn detect(&self) -> Resource<'static>;
+}
+
+/// Name of the environment variable read by `EnvResourceDetector::new`.
+pub const RESOURCE_LABELS_ENV: &str = "OTEL_RESOURCE_LABELS";
+
+/// Detects the labels given in an environment variable, as comma separated `key=value` pairs,
+/// e.g. `OTEL_RESOURCE_LABELS=service.name=users,host.name=box-1`.
+///
+/// Keys and values are trimmed, pairs without a `=`, with an empty key, or with a key or value
+/// that is not a valid label are ignored.
+#[derive(Clone, Debug)]
+pub struct EnvResourceDetector {
+    var: Cow<'static, str>,
+}
+
+impl EnvResourceDetector {
+    /// Creates a detector reading `OTEL_RESOURCE_LABELS`.
+    pub fn new() -> Self {
+        EnvResourceDetector::from_var(RESOURCE_LABELS_ENV)
+    }
+
+    /// Creates a detector reading the given environment variable.
+    pub fn from_var<N: Into<Cow<'static, str>>>(var: N) -> Self {
+        EnvResourceDetector { var: var.into() }
+    }
+}
+
+impl Default for EnvResourceDetector {
+    fn default() -> Self {
+        EnvResourceDetector::new()
+    }
+}
+
+impl ResourceDetector for EnvResourceDetector {
+    fn detect(&self) -> Resource<'static> {
+        env::var(self.var.as_ref())
+            .map(|labels| parse_labels(&labels))
+            .unwrap_or_default()
+    }
+}
+
+fn parse_labels(labels: &str) -> Resource<'static> {
+    let labels = labels.split(',')
+        .filter_map(|pair| {
+            let mut parts = pair.splitn(2, '=');
+            let key = parts.next()?.trim();
+            let value = parts.next()?.trim();
+            if key.is_empty() {
+                return None;
+            }
+            let key = try_validate_str(key.to_string()).ok()?;
+            let value = try_validate_str(value.to_string()).ok()?;
+            Some((key, value))
+        })
+        .collect();
+    Resource::new(labels)
+}
+
+/// The version of the compiler that built this crate, if provided at compile time.
+const RUSTC_VERSION: Option<&str> = option_env!("OTEL_RUSTC_VERSION");
+
+/// Detects the `process.pid`, `process.executable.name`, `process.runtime.name` and
+/// `process.runtime.version` labels of the current process.
+///
+/// The runtime version is only known when the `OTEL_RUSTC_VERSION` environment variable is set
+/// while this crate is compiled, e.g. to `1.38.0`, the label is left out otherwise.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct ProcessResourceDetector;
+
+impl ResourceDetector for ProcessResourceDetector {
+    fn detect(&self) -> Resource<'static> {
+        let mut resource = Resource::empty()
+            .with_label(semconv::PROCESS_PID, process::id().to_string())
+            .with_label(semconv::PROCESS_RUNTIME_NAME, "rust");
+        let runtime_version = RUSTC_VERSION.filter(|version| !version.is_empty())
+            .and_then(|version| try_validate_str(version.to_string()).ok());
+        if let Some(version) = runtime_version {
+            resource.labels.insert(semconv::PROCESS_RUNTIME_VERSION.into(), version);
+        }
+        let executable_name = env::current_exe().ok()
+            .and_then(|path| path.file_name().map(|name| name.to_string_lossy().into_owned()))
+            .and_then(|name| try_validate_str(name).ok());
+        if let Some(name) = executable_name {
+            resource.labels.insert(semconv::PROCESS_EXECUTABLE_NAME.into(), name);
+        }
+        resource
+    }
+}
+
+/// Detects the `os.type` label, and the `os.description` label where supported: from
+/// `/etc/os-release` on Linux and `sw_vers` on macOS.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct OsResourceDetector;
+
+impl ResourceDetector for OsResourceDetector {
+    fn detect(&self) -> Resource<'static> {
+        let mut resource = Resource::empty().with_label(semconv::OS_TYPE, os_type());
+        if let Some(description) = os_description().and_then(|d| try_validate_str(d).ok()) {
+            resource.labels.insert(semconv::OS_DESCRIPTION.into(), description);
+        }
+        resource
+    }
+}
+
+/// The `os.type` of the semantic conventions, which calls macOS `darwin`.
+fn os_type() -> &'static str {
+    match env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn os_description() -> Option<String> {
+    let os_release = std::fs::read_to_string("/etc/os-release").ok()?;
+    os_release.lines()
+        .find_map(|line| line.strip_prefix("PRETTY_NAME="))
+        .map(|name| name.trim_matches('"').to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn os_description() -> Option<String> {
+    let output = process::Command::new("sw_vers").arg("-productVersion").output().ok()?;
+    let version = String::from_utf8(output.stdout).ok()?;
+    Some(format!("macOS {}", version.trim()))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn os_description() -> Option<String> {
+    None
 }
 
 /// Utility to make resource creation slightly simpler
 ///
-/// Can be used in place of `Resource::new`
+/// Can be used in place of `Resource::new`, if a key is repeated the last value wins.
 ///
 /// ## Example
 ///
@@ -175,6 +473,173 @@ mod tests {
         assert_eq!(r.labels.get("test2"), Some(&Cow::Borrowed("label2")));
     }
 
+    #[test]
+    fn test_insert_last_wins() {
+        let mut r = Resource::empty();
+        assert_eq!(r.insert("key", "first"), None);
+        assert_eq!(r.insert("key", "second"), Some(Cow::Borrowed("first")));
+        assert_eq!(r.get("key"), Some("second"));
+        assert_eq!(r.labels.len(), 1);
+
+        let r = resource! {
+            "key" => "first",
+            "key" => "second",
+        };
+        assert_eq!(r.get("key"), Some("second"));
+    }
+
+    #[test]
+    fn test_iterate() {
+        let r = resource! {
+            "host.name" => "box-1",
+            "service.name" => "users",
+        };
+        let mut labels = Vec::new();
+        for (k, v) in &r {
+            labels.push((k, v));
+        }
+        labels.sort();
+        assert_eq!(labels, vec![("host.name", "box-1"), ("service.name", "users")]);
+
+        assert_eq!(r.iter().len(), 2);
+        let labels: HashMap<&str, &str> = r.iter().collect();
+        assert_eq!(labels, r.labels());
+    }
+
+    struct TestDetector(Resource<'static>);
+
+    impl ResourceDetector for TestDetector {
+        fn detect(&self) -> Resource<'static> {
+            self.0.clone()
+        }
+    }
+
+    #[test]
+    fn test_detect_precedence() {
+        let first = TestDetector(resource! {
+            "service.name" => "users",
+            "host.name" => "",
+        });
+        let second = TestDetector(resource! {
+            "service.name" => "ignored",
+            "host.name" => "box-1",
+            "host.id" => "42",
+        });
+
+        let r = Resource::detect(&[&first, &second]);
+        assert_eq!(r, resource! {
+            "service.name" => "users",
+            "host.name" => "box-1",
+            "host.id" => "42",
+        });
+        assert_eq!(Resource::detect(&[&second, &first]).get("service.name"), Some("ignored"));
+        assert_eq!(Resource::detect(&[]), Resource::empty());
+    }
+
+    #[test]
+    fn test_env_detector() {
+        let var = "OTEL_RESOURCE_LABELS_TEST_ENV_DETECTOR";
+        assert_eq!(EnvResourceDetector::from_var(var).detect(), Resource::empty());
+
+        env::set_var(var, " service.name = users ,host.name=box-1,invalid,=empty,k=v=w,non.ascii=\u{e9}");
+        let r = EnvResourceDetector::from_var(var).detect();
+        env::remove_var(var);
+        assert_eq!(r, resource! {
+            "service.name" => "users",
+            "host.name" => "box-1",
+            "k" => "v=w",
+        });
+    }
+
+    #[test]
+    fn test_process_detector() {
+        let r = ProcessResourceDetector.detect();
+        assert_eq!(r.get(semconv::PROCESS_PID), Some(process::id().to_string().as_str()));
+        assert!(!r.get(semconv::PROCESS_EXECUTABLE_NAME).unwrap().is_empty());
+        assert_eq!(r.get(semconv::PROCESS_RUNTIME_NAME), Some("rust"));
+        assert_eq!(r.get(semconv::PROCESS_RUNTIME_VERSION), RUSTC_VERSION.filter(|v| !v.is_empty()));
+    }
+
+    #[test]
+    fn test_os_detector() {
+        let r = OsResourceDetector.detect();
+        assert_eq!(r.get(semconv::OS_TYPE), Some(os_type()));
+        if cfg!(target_os = "linux") {
+            assert_eq!(r.get(semconv::OS_TYPE), Some("linux"));
+        }
+        if let Some(description) = r.get(semconv::OS_DESCRIPTION) {
+            assert!(!description.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_into_owned() {
+        let key = String::from("service.name");
+        let resource = Resource::empty().with_label(key.as_str(), "checkout");
+        let owned: Resource<'static> = resource.clone().into_owned();
+        assert_eq!(owned, resource);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut r = resource! {
+            "host.name" => "box-1",
+            "service.name" => "users",
+        };
+        assert!(r.contains_key("host.name"));
+        assert_eq!(r.remove("host.name"), Some(Cow::Borrowed("box-1")));
+        assert!(!r.contains_key("host.name"));
+        assert_eq!(r.get("host.name"), None);
+        assert_eq!(r.labels.len(), 1);
+    }
+
+    #[test]
+    fn test_remove_missing() {
+        let mut r = resource! {
+            "service.name" => "users",
+        };
+        assert!(!r.contains_key("host.name"));
+        assert_eq!(r.remove("host.name"), None);
+        assert!(r.contains_key("service.name"));
+    }
+
+    #[test]
+    fn test_with_label() {
+        let host = "web-1".to_string();
+        let r = Resource::empty()
+            .with_label("service.name", "api")
+            .with_label("host", host)
+            .with_label("service.name", "checkout");
+
+        assert_eq!(r.get("service.name"), Some("checkout"));
+        assert_eq!(r.get("host"), Some("web-1"));
+        assert_eq!(r.labels.len(), 2);
+        assert_eq!(r, resource! { "host" => "web-1", "service.name" => "checkout" });
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_with_label_invalid_key() {
+        Resource::empty().with_label("service.name", "api").with_label("h\u{f6}st", "x");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_with_label_invalid_value() {
+        Resource::empty().with_label("service.name", "a".repeat(256));
+    }
+
+    #[test]
+    fn test_service() {
+        let r = Resource::service("api", "1.2");
+        assert_eq!(r.labels.len(), 2);
+        assert_eq!(r.get("service.name"), Some("api"));
+        assert_eq!(r.get("service.version"), Some("1.2"));
+
+        let r = r.with_label(semconv::HOST_NAME, "web-1");
+        assert_eq!(r.get("host.name"), Some("web-1"));
+    }
+
     #[test]
     fn test_merge_resources() {
         let mut r1 = resource! {
@@ -196,6 +661,47 @@ mod tests {
         assert_eq!(r1.get("test_4").unwrap(), "val_4");
     }
 
+    #[test]
+    fn test_merge_schema_url() {
+        let mut r1 = Resource::empty().with_schema_url("https://opentelemetry.io/schemas/1.21.0");
+        r1.merge(Resource::empty().with_schema_url("https://opentelemetry.io/schemas/1.4.0"));
+        assert_eq!(r1.schema_url(), Some("https://opentelemetry.io/schemas/1.21.0"));
+
+        let mut r2 = Resource::empty().with_schema_url("");
+        r2.merge(Resource::empty().with_schema_url("https://opentelemetry.io/schemas/1.4.0"));
+        assert_eq!(r2.schema_url(), Some("https://opentelemetry.io/schemas/1.4.0"));
+
+        let mut r3 = Resource::empty();
+        r3.merge(Resource::empty());
+        assert_eq!(r3.schema_url(), None);
+        assert_eq!(r3.schema_url, None);
+    }
+
+    #[test]
+    fn test_schema_url_eq_and_owned() {
+        let resource = Resource::service("checkout", "1.0.0")
+            .with_schema_url("https://opentelemetry.io/schemas/1.21.0");
+        assert_ne!(resource, Resource::service("checkout", "1.0.0"));
+        assert_eq!(resource.clone().into_owned(), resource);
+
+        let empty_url = Resource::service("checkout", "1.0.0").with_schema_url("");
+        assert_eq!(empty_url, Resource::service("checkout", "1.0.0"));
+        assert_eq!(hash(&empty_url), hash(&Resource::service("checkout", "1.0.0")));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize() {
+        let resource = Resource::service("checkout", "1.0.0");
+        assert_eq!(serde_json::to_value(&resource).unwrap(), serde_json::json!({
+            "labels": {"service.name": "checkout", "service.version": "1.0.0"},
+        }));
+
+        let resource = resource.with_schema_url("https://opentelemetry.io/schemas/1.21.0");
+        let json = serde_json::to_value(&resource).unwrap();
+        assert_eq!(json["schema_url"], "https://opentelemetry.io/schemas/1.21.0");
+    }
+
     #[test]
     fn test_labels() {
         let r1 = resource! {
@@ -210,6 +716,41 @@ mod tests {
         assert_eq!(*labels.get("test_3").unwrap(), "val_3");
     }
 
+    fn hash(resource: &Resource) -> u64 {
+        use std::collections::hash_map::DefaultHasher;
+        let mut hasher = DefaultHasher::new();
+        resource.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_eq_and_hash_ignore_order() {
+        let r1 = resource! {
+            "service.name" => "checkout",
+            "host.name" => "web-1",
+            "k8s.pod.name" => "checkout-5d8f",
+        };
+
+        let mut r2 = Resource::empty();
+        r2.insert("k8s.pod.name", "checkout-5d8f".to_string());
+        r2.insert("host.name", "web-1");
+        r2.insert("service.name", "checkout");
+
+        assert_eq!(r1, r2);
+        assert_eq!(hash(&r1), hash(&r2));
+
+        r2.insert("host.name", "web-2");
+        assert_ne!(r1, r2);
+        assert_ne!(r1, Resource::empty());
+
+        let mut resources = HashMap::new();
+        resources.insert(r1.clone(), 1);
+        resources.insert(r2, 2);
+        resources.insert(r1.clone(), 3);
+        assert_eq!(resources.len(), 2);
+        assert_eq!(resources[&r1], 3);
+    }
+
     proptest! {
         #[test]
         #[should_panic]
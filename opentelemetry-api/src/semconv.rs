@@ -0,0 +1,55 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//!
+//! Keys of the resource labels defined by the OpenTelemetry semantic conventions.
+//!
+//! See <https://github.com/open-telemetry/opentelemetry-specification/tree/master/specification/resource/semantic_conventions>
+
+/// Logical name of the service, e.g. `shoppingcart`.
+pub const SERVICE_NAME: &str = "service.name";
+/// Namespace of `SERVICE_NAME`, e.g. `Shop`.
+pub const SERVICE_NAMESPACE: &str = "service.namespace";
+/// Unique id of the service instance, e.g. `627cc493-f310-47de-96bd-71410b7dec09`.
+pub const SERVICE_INSTANCE_ID: &str = "service.instance.id";
+/// Version of the service, e.g. `2.0.0`.
+pub const SERVICE_VERSION: &str = "service.version";
+
+/// Name of the telemetry SDK, e.g. `opentelemetry`.
+pub const TELEMETRY_SDK_NAME: &str = "telemetry.sdk.name";
+/// Language of the telemetry SDK, e.g. `rust`.
+pub const TELEMETRY_SDK_LANGUAGE: &str = "telemetry.sdk.language";
+/// Version of the telemetry SDK, e.g. `0.1.0`.
+pub const TELEMETRY_SDK_VERSION: &str = "telemetry.sdk.version";
+
+/// Hostname of the host, as returned by the `hostname` command.
+pub const HOST_NAME: &str = "host.name";
+/// Unique id of the host, e.g. the instance id of a cloud provider.
+pub const HOST_ID: &str = "host.id";
+
+/// Process identifier, e.g. `1234`.
+pub const PROCESS_PID: &str = "process.pid";
+/// Name of the process executable, e.g. `otelcol`.
+pub const PROCESS_EXECUTABLE_NAME: &str = "process.executable.name";
+/// Name of the runtime of the process, `rust` for Rust programs.
+pub const PROCESS_RUNTIME_NAME: &str = "process.runtime.name";
+/// Version of the runtime of the process, the version of the compiler for Rust, e.g. `1.38.0`.
+pub const PROCESS_RUNTIME_VERSION: &str = "process.runtime.version";
+
+/// Type of the operating system, e.g. `linux`, `darwin` or `windows`.
+pub const OS_TYPE: &str = "os.type";
+/// Human readable description of the operating system, e.g. `Debian GNU/Linux 10 (buster)`.
+pub const OS_DESCRIPTION: &str = "os.description";
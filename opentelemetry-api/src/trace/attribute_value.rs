@@ -15,17 +15,112 @@
  */
 
 use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::time::Duration;
 
 /// Represents all the possible values for an attribute.
-#[derive(Clone, PartialEq, PartialOrd, Debug)]
+///
+/// Values are `Eq` and `Hash`, so they can key maps and sets: doubles are compared numerically,
+/// except that all NaNs are equal to each other, and `0.0` equals `-0.0`. Ordering agrees with
+/// equality: values order by variant first, two NaNs are equal, and a NaN is unordered against
+/// any other double.
+#[derive(Clone)]
 pub enum AttributeValue<'a> {
     String(Cow<'a, str>),
     Boolean(bool),
     Long(i64),
-    Double(f64)
+    Double(f64),
+    StringArray(Vec<Cow<'a, str>>),
+    BoolArray(Vec<bool>),
+    LongArray(Vec<i64>),
+    DoubleArray(Vec<f64>),
+}
+
+impl <'a> AttributeValue<'a> {
+    /// Returns the name of this value's type, e.g. `"long"`, or `"long[]"` for arrays.
+    pub fn value_type(&self) -> &'static str {
+        match self {
+            AttributeValue::String(_) => "string",
+            AttributeValue::Boolean(_) => "bool",
+            AttributeValue::Long(_) => "long",
+            AttributeValue::Double(_) => "double",
+            AttributeValue::StringArray(_) => "string[]",
+            AttributeValue::BoolArray(_) => "bool[]",
+            AttributeValue::LongArray(_) => "long[]",
+            AttributeValue::DoubleArray(_) => "double[]",
+        }
+    }
+
+    /// Returns the value of a `String`, `None` for other variants.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            AttributeValue::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `Long`, `None` for other variants.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            AttributeValue::Long(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `Double`, `None` for other variants, including `Long`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            AttributeValue::Double(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `Boolean`, `None` for other variants.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            AttributeValue::Boolean(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Converts this value into one that owns all of its data.
+    pub fn into_owned(self) -> AttributeValue<'static> {
+        match self {
+            AttributeValue::String(v) => AttributeValue::String(Cow::Owned(v.into_owned())),
+            AttributeValue::Boolean(v) => AttributeValue::Boolean(v),
+            AttributeValue::Long(v) => AttributeValue::Long(v),
+            AttributeValue::Double(v) => AttributeValue::Double(v),
+            AttributeValue::StringArray(v) => AttributeValue::StringArray(
+                v.into_iter().map(|s| Cow::Owned(s.into_owned())).collect()
+            ),
+            AttributeValue::BoolArray(v) => AttributeValue::BoolArray(v),
+            AttributeValue::LongArray(v) => AttributeValue::LongArray(v),
+            AttributeValue::DoubleArray(v) => AttributeValue::DoubleArray(v),
+        }
+    }
 }
 
 macro_rules! impl_from {
+    ([$what: ty], $variant: expr) => (
+        impl <'a> From<Vec<$what>> for AttributeValue<'a> {
+            fn from(val: Vec<$what>) -> Self {
+                $variant(val.into_iter().map(Cow::from).collect())
+            }
+        }
+    );
+
+    ([$what: ty], $conv: ty, $variant: expr) => (
+        impl <'a> From<Vec<$what>> for AttributeValue<'a> {
+            fn from(val: Vec<$what>) -> Self {
+                $variant(val.into_iter().map(|v| v as $conv).collect())
+            }
+        }
+    );
+
     ($what: ty, $variant: expr) => (
         impl <'a> From<$what> for AttributeValue<'a> {
             fn from(val: $what) -> Self {
@@ -50,7 +145,6 @@ impl_from!(u16, i64, AttributeValue::Long);
 impl_from!(i32, i64, AttributeValue::Long);
 impl_from!(u32, i64, AttributeValue::Long);
 impl_from!(i64, i64, AttributeValue::Long);
-impl_from!(u64, i64, AttributeValue::Long);
 
 impl_from!(f32, f64, AttributeValue::Double);
 impl_from!(f64, f64, AttributeValue::Double);
@@ -61,6 +155,316 @@ impl_from!(&'a String, AttributeValue::String);
 
 impl_from!(bool, bool, AttributeValue::Boolean);
 
+impl_from!([i8], i64, AttributeValue::LongArray);
+impl_from!([u8], i64, AttributeValue::LongArray);
+impl_from!([i16], i64, AttributeValue::LongArray);
+impl_from!([u16], i64, AttributeValue::LongArray);
+impl_from!([i32], i64, AttributeValue::LongArray);
+impl_from!([u32], i64, AttributeValue::LongArray);
+impl_from!([i64], i64, AttributeValue::LongArray);
+
+impl_from!([f32], f64, AttributeValue::DoubleArray);
+impl_from!([f64], f64, AttributeValue::DoubleArray);
+
+impl_from!([String], AttributeValue::StringArray);
+impl_from!([&'a str], AttributeValue::StringArray);
+impl_from!([&'a String], AttributeValue::StringArray);
+
+impl_from!([bool], bool, AttributeValue::BoolArray);
+
+/// A `u64` is stored as a `Long` when it fits in an `i64`, values above `i64::MAX` are stored
+/// as their decimal `String` representation rather than wrapping to a negative `Long`.
+impl <'a> From<u64> for AttributeValue<'a> {
+    fn from(val: u64) -> Self {
+        if val <= i64::MAX as u64 {
+            AttributeValue::Long(val as i64)
+        } else {
+            AttributeValue::String(Cow::from(val.to_string()))
+        }
+    }
+}
+
+/// A `Vec<u64>` is stored as a `LongArray` when every element fits in an `i64`, otherwise the
+/// whole array is stored as a `StringArray` of decimal representations.
+impl <'a> From<Vec<u64>> for AttributeValue<'a> {
+    fn from(val: Vec<u64>) -> Self {
+        if val.iter().all(|&v| v <= i64::MAX as u64) {
+            AttributeValue::LongArray(val.into_iter().map(|v| v as i64).collect())
+        } else {
+            AttributeValue::StringArray(val.into_iter().map(|v| Cow::from(v.to_string())).collect())
+        }
+    }
+}
+
+impl <'a> PartialEq for AttributeValue<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (AttributeValue::String(a), AttributeValue::String(b)) => a == b,
+            (AttributeValue::Boolean(a), AttributeValue::Boolean(b)) => a == b,
+            (AttributeValue::Long(a), AttributeValue::Long(b)) => a == b,
+            (AttributeValue::Double(a), AttributeValue::Double(b)) => double_bits(*a) == double_bits(*b),
+            (AttributeValue::StringArray(a), AttributeValue::StringArray(b)) => a == b,
+            (AttributeValue::BoolArray(a), AttributeValue::BoolArray(b)) => a == b,
+            (AttributeValue::LongArray(a), AttributeValue::LongArray(b)) => a == b,
+            (AttributeValue::DoubleArray(a), AttributeValue::DoubleArray(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| double_bits(*a) == double_bits(*b))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl <'a> Eq for AttributeValue<'a> {}
+
+impl <'a> Hash for AttributeValue<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            AttributeValue::String(v) => v.hash(state),
+            AttributeValue::Boolean(v) => v.hash(state),
+            AttributeValue::Long(v) => v.hash(state),
+            AttributeValue::Double(v) => double_bits(*v).hash(state),
+            AttributeValue::StringArray(v) => v.hash(state),
+            AttributeValue::BoolArray(v) => v.hash(state),
+            AttributeValue::LongArray(v) => v.hash(state),
+            AttributeValue::DoubleArray(v) => {
+                v.len().hash(state);
+                v.iter().for_each(|v| double_bits(*v).hash(state));
+            }
+        }
+    }
+}
+
+impl <'a> PartialOrd for AttributeValue<'a> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (AttributeValue::String(a), AttributeValue::String(b)) => a.partial_cmp(b),
+            (AttributeValue::Boolean(a), AttributeValue::Boolean(b)) => a.partial_cmp(b),
+            (AttributeValue::Long(a), AttributeValue::Long(b)) => a.partial_cmp(b),
+            (AttributeValue::Double(a), AttributeValue::Double(b)) => double_cmp(*a, *b),
+            (AttributeValue::StringArray(a), AttributeValue::StringArray(b)) => a.partial_cmp(b),
+            (AttributeValue::BoolArray(a), AttributeValue::BoolArray(b)) => a.partial_cmp(b),
+            (AttributeValue::LongArray(a), AttributeValue::LongArray(b)) => a.partial_cmp(b),
+            (AttributeValue::DoubleArray(a), AttributeValue::DoubleArray(b)) => {
+                for (a, b) in a.iter().zip(b) {
+                    match double_cmp(*a, *b) {
+                        Some(Ordering::Equal) => continue,
+                        ordering => return ordering,
+                    }
+                }
+                a.len().partial_cmp(&b.len())
+            }
+            _ => variant_index(self).partial_cmp(&variant_index(other)),
+        }
+    }
+}
+
+/// The position of the variant in the declaration, which orders values of different types.
+fn variant_index(value: &AttributeValue) -> u8 {
+    match value {
+        AttributeValue::String(_) => 0,
+        AttributeValue::Boolean(_) => 1,
+        AttributeValue::Long(_) => 2,
+        AttributeValue::Double(_) => 3,
+        AttributeValue::StringArray(_) => 4,
+        AttributeValue::BoolArray(_) => 5,
+        AttributeValue::LongArray(_) => 6,
+        AttributeValue::DoubleArray(_) => 7,
+    }
+}
+
+/// Compares two doubles consistently with `double_bits`, so that NaNs are equal to each other.
+fn double_cmp(a: f64, b: f64) -> Option<Ordering> {
+    if double_bits(a) == double_bits(b) {
+        Some(Ordering::Equal)
+    } else {
+        a.partial_cmp(&b)
+    }
+}
+
+/// The bit pattern of a double, the same for every NaN and for both zeros.
+fn double_bits(v: f64) -> u64 {
+    if v.is_nan() {
+        f64::NAN.to_bits()
+    } else if v == 0.0 {
+        0.0f64.to_bits()
+    } else {
+        v.to_bits()
+    }
+}
+
+/// A `Duration` is stored as a `Long` of nanoseconds, the unit of the span timestamps. Durations
+/// longer than `i64::MAX` nanoseconds, about 292 years, saturate to `i64::MAX`.
+impl <'a> From<Duration> for AttributeValue<'a> {
+    fn from(val: Duration) -> Self {
+        AttributeValue::Long(i64::try_from(val.as_nanos()).unwrap_or(i64::MAX))
+    }
+}
+
+/// Renders the bare value rather than the variant name, so attribute maps stay readable in span
+/// dumps. Strings are quoted and doubles always carry a fractional part, keeping the type
+/// recoverable from the output. Arrays are prefixed with their element type, so that empty arrays
+/// remain distinguishable, e.g. `long[1, 2]`.
+impl <'a> fmt::Debug for AttributeValue<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AttributeValue::String(v) => write!(f, "{:?}", v),
+            AttributeValue::Boolean(v) => write!(f, "{:?}", v),
+            AttributeValue::Long(v) => write!(f, "{:?}", v),
+            AttributeValue::Double(v) => write!(f, "{:?}", v),
+            AttributeValue::StringArray(v) => write!(f, "string{:?}", v),
+            AttributeValue::BoolArray(v) => write!(f, "bool{:?}", v),
+            AttributeValue::LongArray(v) => write!(f, "long{:?}", v),
+            AttributeValue::DoubleArray(v) => write!(f, "double{:?}", v),
+        }
+    }
+}
+
+/// Renders the plain value, strings are not quoted and arrays are rendered as `[a, b]`.
+impl <'a> fmt::Display for AttributeValue<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AttributeValue::String(v) => write!(f, "{}", v),
+            AttributeValue::Boolean(v) => write!(f, "{}", v),
+            AttributeValue::Long(v) => write!(f, "{}", v),
+            AttributeValue::Double(v) => write!(f, "{}", v),
+            AttributeValue::StringArray(v) => write_array(f, v),
+            AttributeValue::BoolArray(v) => write_array(f, v),
+            AttributeValue::LongArray(v) => write_array(f, v),
+            AttributeValue::DoubleArray(v) => write_array(f, v),
+        }
+    }
+}
+
+fn write_array<T: fmt::Display>(f: &mut fmt::Formatter, values: &[T]) -> fmt::Result {
+    write!(f, "[")?;
+    for (i, v) in values.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", v)?;
+    }
+    write!(f, "]")
+}
+
+/// Serializes the OTLP `AnyValue` of the value, e.g. `AttributeValue::Long(1)` as
+/// `{"intValue": "1"}`, the 64 bit integer being a string as in OTLP/JSON. Arrays are written as
+/// `{"arrayValue": {"values": [..]}}`, holding the `AnyValue` of every element.
+#[cfg(feature = "serde")]
+impl <'a> serde::Serialize for AttributeValue<'a> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let values = match self {
+            AttributeValue::String(v) => return serializer.serialize_newtype_variant("AnyValue", 0, "stringValue", v),
+            AttributeValue::Boolean(v) => return serializer.serialize_newtype_variant("AnyValue", 1, "boolValue", v),
+            AttributeValue::Long(v) =>
+                return serializer.serialize_newtype_variant("AnyValue", 2, "intValue", &v.to_string()),
+            AttributeValue::Double(v) => return serializer.serialize_newtype_variant("AnyValue", 3, "doubleValue", v),
+            AttributeValue::StringArray(v) => v.iter().cloned().map(AttributeValue::String).collect(),
+            AttributeValue::BoolArray(v) => v.iter().cloned().map(AttributeValue::Boolean).collect(),
+            AttributeValue::LongArray(v) => v.iter().cloned().map(AttributeValue::Long).collect(),
+            AttributeValue::DoubleArray(v) => v.iter().cloned().map(AttributeValue::Double).collect(),
+        };
+        serializer.serialize_newtype_variant("AnyValue", 4, "arrayValue", &ArrayValue { values })
+    }
+}
+
+/// Deserializes an OTLP `AnyValue`, accepting `intValue`s both as strings and as numbers.
+///
+/// The values of an `arrayValue` must all be of the same, non array, type. An empty `arrayValue`
+/// is read as an empty `StringArray`.
+#[cfg(feature = "serde")]
+impl <'de, 'a> serde::Deserialize<'de> for AttributeValue<'a> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let values = match AnyValue::deserialize(deserializer)? {
+            AnyValue::String(v) => return Ok(AttributeValue::String(v.into())),
+            AnyValue::Boolean(v) => return Ok(AttributeValue::Boolean(v)),
+            AnyValue::Long(v) => return Ok(AttributeValue::Long(v)),
+            AnyValue::Double(v) => return Ok(AttributeValue::Double(v)),
+            AnyValue::Array(array) => array.values,
+        };
+
+        let value_type = values.first().map_or("string", AttributeValue::value_type);
+        if let Some(other) = values.iter().find(|v| v.value_type() != value_type) {
+            return Err(D::Error::custom(format!(
+                "array values must be of the same type, found {} and {}", value_type, other.value_type())));
+        }
+
+        let values = values.into_iter();
+        Ok(match value_type {
+            "string" => AttributeValue::StringArray(values.filter_map(|v| match v {
+                AttributeValue::String(v) => Some(Cow::Owned(v.into_owned())),
+                _ => None,
+            }).collect()),
+            "bool" => AttributeValue::BoolArray(values.filter_map(|v| match v {
+                AttributeValue::Boolean(v) => Some(v),
+                _ => None,
+            }).collect()),
+            "long" => AttributeValue::LongArray(values.filter_map(|v| match v {
+                AttributeValue::Long(v) => Some(v),
+                _ => None,
+            }).collect()),
+            "double" => AttributeValue::DoubleArray(values.filter_map(|v| match v {
+                AttributeValue::Double(v) => Some(v),
+                _ => None,
+            }).collect()),
+            _ => return Err(D::Error::custom(format!("array values cannot be arrays, found {}", value_type))),
+        })
+    }
+}
+
+/// Shape of an OTLP `AnyValue`, a single field object named after the type of the value.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+enum AnyValue {
+    #[serde(rename = "stringValue")]
+    String(String),
+    #[serde(rename = "boolValue")]
+    Boolean(bool),
+    #[serde(rename = "intValue", deserialize_with = "deserialize_int_value")]
+    Long(i64),
+    #[serde(rename = "doubleValue")]
+    Double(f64),
+    #[serde(rename = "arrayValue")]
+    Array(ArrayValue<'static>),
+}
+
+/// An OTLP `ArrayValue`, `{"values": [AnyValue, ..]}`.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ArrayValue<'a> {
+    values: Vec<AttributeValue<'a>>,
+}
+
+/// Reads an `intValue`, written as a string in OTLP/JSON though some producers use numbers.
+#[cfg(feature = "serde")]
+fn deserialize_int_value<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    struct IntValueVisitor;
+
+    impl <'de> serde::de::Visitor<'de> for IntValueVisitor {
+        type Value = i64;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "a 64 bit integer, or a string holding one")
+        }
+
+        fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<i64, E> {
+            Ok(v)
+        }
+
+        fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<i64, E> {
+            std::convert::TryFrom::try_from(v).map_err(|_| E::invalid_value(serde::de::Unexpected::Unsigned(v), &self))
+        }
+
+        fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<i64, E> {
+            v.parse().map_err(|_| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+        }
+    }
+
+    deserializer.deserialize_any(IntValueVisitor)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -69,5 +473,181 @@ mod tests {
         assert_eq!(AttributeValue::String("test".into()), "test".into());
         assert_eq!(AttributeValue::String("test".into()), "test".to_string().into())
     }
-}
 
+    #[test]
+    pub fn test_debug() {
+        assert_eq!(format!("{:?}", AttributeValue::String("test".into())), "\"test\"");
+        assert_eq!(format!("{:?}", AttributeValue::Boolean(true)), "true");
+        assert_eq!(format!("{:?}", AttributeValue::Long(5)), "5");
+        assert_eq!(format!("{:?}", AttributeValue::Double(5.0)), "5.0");
+        assert_eq!(format!("{:?}", AttributeValue::Double(-1.5)), "-1.5");
+        assert_eq!(format!("{:?}", AttributeValue::from(vec!["a", "b"])), "string[\"a\", \"b\"]");
+        assert_eq!(format!("{:?}", AttributeValue::from(vec![true])), "bool[true]");
+        assert_eq!(format!("{:?}", AttributeValue::from(vec![1i64, 2])), "long[1, 2]");
+        assert_eq!(format!("{:?}", AttributeValue::from(Vec::<f64>::new())), "double[]");
+    }
+
+    #[test]
+    pub fn test_display() {
+        assert_eq!(AttributeValue::Long(5).to_string(), "5");
+        assert_eq!(AttributeValue::Double(1.5).to_string(), "1.5");
+        assert_eq!(AttributeValue::Boolean(false).to_string(), "false");
+        assert_eq!(AttributeValue::String("test".into()).to_string(), "test");
+        assert_eq!(AttributeValue::from(vec!["a", "b"]).to_string(), "[a, b]");
+        assert_eq!(AttributeValue::from(Vec::<i64>::new()).to_string(), "[]");
+    }
+
+    #[test]
+    pub fn test_value_type() {
+        assert_eq!(AttributeValue::String("test".into()).value_type(), "string");
+        assert_eq!(AttributeValue::Boolean(true).value_type(), "bool");
+        assert_eq!(AttributeValue::Long(5).value_type(), "long");
+        assert_eq!(AttributeValue::Double(1.5).value_type(), "double");
+        assert_eq!(AttributeValue::from(vec![1i64]).value_type(), "long[]");
+    }
+
+    #[test]
+    pub fn test_typed_getters() {
+        let long = AttributeValue::Long(5);
+        assert_eq!(long.as_i64(), Some(5));
+        assert_eq!(long.as_str(), None);
+        assert_eq!(long.as_f64(), None);
+        assert_eq!(long.as_bool(), None);
+
+        assert_eq!(AttributeValue::from("GET").as_str(), Some("GET"));
+        assert_eq!(AttributeValue::Double(0.5).as_f64(), Some(0.5));
+        assert_eq!(AttributeValue::Boolean(true).as_bool(), Some(true));
+        assert_eq!(AttributeValue::from(vec!["a"]).as_str(), None);
+    }
+
+    #[test]
+    pub fn test_ordering_agrees_with_eq() {
+        let pairs = vec![
+            (AttributeValue::Double(f64::NAN), AttributeValue::Double(-f64::NAN)),
+            (AttributeValue::Double(0.0), AttributeValue::Double(-0.0)),
+            (AttributeValue::from(vec![f64::NAN, 0.0]), AttributeValue::from(vec![-f64::NAN, -0.0])),
+        ];
+        for (a, b) in pairs {
+            assert_eq!(a, b);
+            assert_eq!(a.partial_cmp(&b), Some(Ordering::Equal));
+        }
+
+        let nan = AttributeValue::Double(f64::NAN);
+        let one = AttributeValue::Double(1.0);
+        assert_ne!(nan, one);
+        assert_eq!(nan.partial_cmp(&one), None);
+
+        assert!(AttributeValue::Double(1.0) < AttributeValue::Double(2.0));
+        assert!(AttributeValue::from(vec![1.0]) < AttributeValue::from(vec![1.0, 0.0]));
+        assert!(AttributeValue::from("b") < AttributeValue::Boolean(false));
+        assert!(AttributeValue::Long(i64::MAX) < AttributeValue::Double(f64::MIN));
+    }
+
+    #[test]
+    pub fn test_hash_set() {
+        use std::collections::HashSet;
+
+        let values = vec![
+            AttributeValue::from("GET"),
+            AttributeValue::Boolean(true),
+            AttributeValue::Long(5),
+            AttributeValue::Double(1.5),
+            AttributeValue::Double(f64::NAN),
+            AttributeValue::Double(0.0),
+            AttributeValue::from(vec!["a", "b"]),
+            AttributeValue::from(vec![true]),
+            AttributeValue::from(vec![1i64, 2]),
+            AttributeValue::from(vec![0.5, f64::NAN]),
+        ];
+        let mut set: HashSet<_> = values.iter().cloned().collect();
+        assert_eq!(set.len(), values.len());
+
+        for value in values.clone() {
+            assert!(!set.insert(value));
+        }
+        assert!(!set.insert(AttributeValue::String("GET".to_string().into())));
+        assert!(!set.insert(AttributeValue::Double(-f64::NAN)));
+        assert!(!set.insert(AttributeValue::Double(-0.0)));
+        assert!(!set.insert(AttributeValue::DoubleArray(vec![0.5, -f64::NAN])));
+        assert_eq!(set.len(), values.len());
+
+        // Same payload, different variants
+        assert!(set.insert(AttributeValue::LongArray(vec![5])));
+        assert!(set.insert(AttributeValue::from(vec![1.5])));
+        assert!(set.insert(AttributeValue::from(Vec::<f64>::new())));
+        assert!(set.insert(AttributeValue::from(Vec::<i64>::new())));
+    }
+
+    #[test]
+    pub fn test_array_from() {
+        assert_eq!(AttributeValue::LongArray(vec![1, 2, 3]), vec![1i64, 2, 3].into());
+        assert_eq!(AttributeValue::LongArray(vec![1, 2]), vec![1u8, 2].into());
+        assert_eq!(AttributeValue::DoubleArray(vec![0.5]), vec![0.5f32].into());
+        assert_eq!(AttributeValue::BoolArray(vec![true, false]), vec![true, false].into());
+        assert_eq!(AttributeValue::StringArray(vec!["a".into(), "b".into()]), vec!["a", "b"].into());
+        assert_eq!(AttributeValue::StringArray(vec!["a".into()]), vec!["a".to_string()].into());
+    }
+
+    #[test]
+    pub fn test_u64_from() {
+        assert_eq!(AttributeValue::Long(42), 42u64.into());
+        assert_eq!(AttributeValue::Long(i64::MAX), (i64::MAX as u64).into());
+        assert_eq!(AttributeValue::String("18446744073709551615".into()), u64::MAX.into());
+        assert_ne!(AttributeValue::Long(-1), u64::MAX.into());
+
+        assert_eq!(AttributeValue::LongArray(vec![1, 2]), vec![1u64, 2].into());
+        assert_eq!(
+            AttributeValue::StringArray(vec!["1".into(), "18446744073709551615".into()]),
+            vec![1u64, u64::MAX].into()
+        );
+    }
+
+    #[test]
+    pub fn test_duration_from() {
+        assert_eq!(AttributeValue::Long(1_500_000_000), Duration::from_millis(1500).into());
+        assert_eq!(AttributeValue::Long(0), Duration::default().into());
+        assert_eq!(AttributeValue::Long(i64::MAX), Duration::from_nanos(i64::MAX as u64).into());
+        assert_eq!(AttributeValue::Long(i64::MAX), Duration::from_secs(u64::MAX).into());
+    }
+
+    #[cfg(feature = "serde")]
+    fn round_trip(value: AttributeValue<'_>, json: serde_json::Value) {
+        assert_eq!(serde_json::to_value(&value).unwrap(), json);
+        assert_eq!(serde_json::from_value::<AttributeValue<'_>>(json).unwrap(), value);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        use serde_json::json;
+        round_trip(AttributeValue::from("GET"), json!({"stringValue": "GET"}));
+        round_trip(AttributeValue::Boolean(true), json!({"boolValue": true}));
+        round_trip(AttributeValue::Long(i64::MIN), json!({"intValue": "-9223372036854775808"}));
+        round_trip(AttributeValue::Double(0.5), json!({"doubleValue": 0.5}));
+        round_trip(AttributeValue::from(vec!["a", "b"]),
+                   json!({"arrayValue": {"values": [{"stringValue": "a"}, {"stringValue": "b"}]}}));
+        round_trip(AttributeValue::from(vec![true, false]),
+                   json!({"arrayValue": {"values": [{"boolValue": true}, {"boolValue": false}]}}));
+        round_trip(AttributeValue::from(vec![1i64, -2]),
+                   json!({"arrayValue": {"values": [{"intValue": "1"}, {"intValue": "-2"}]}}));
+        round_trip(AttributeValue::from(vec![1.5f64]), json!({"arrayValue": {"values": [{"doubleValue": 1.5}]}}));
+        round_trip(AttributeValue::StringArray(Vec::new()), json!({"arrayValue": {"values": []}}));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize() {
+        use serde_json::json;
+        let from_json = |json| serde_json::from_value::<AttributeValue<'_>>(json);
+        assert_eq!(from_json(json!({"intValue": 42})).unwrap(), AttributeValue::Long(42));
+        assert_eq!(from_json(json!({"doubleValue": 1})).unwrap(), AttributeValue::Double(1.0));
+
+        assert!(from_json(json!({"intValue": "1.5"})).is_err());
+        assert!(from_json(json!({"intValue": u64::MAX})).is_err());
+        assert!(from_json(json!({"bytesValue": "AA=="})).is_err());
+        assert!(from_json(json!({"stringValue": "a", "boolValue": true})).is_err());
+        assert!(from_json(json!("GET")).is_err());
+        assert!(from_json(json!({"arrayValue": {"values": [{"stringValue": "a"}, {"boolValue": true}]}})).is_err());
+        assert!(from_json(json!({"arrayValue": {"values": [{"arrayValue": {"values": []}}]}})).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::iter::FromIterator;
+use std::ops::Index;
+
+use crate::trace::attribute_value::AttributeValue;
+
+/// Attributes of a span, kept in the order their keys were first inserted.
+///
+/// Updating the value of an existing key leaves it in place, so exporters emit attributes in a
+/// deterministic order. Lookups scan the attributes, which are expected to be few.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Attributes<'a> {
+    entries: Vec<(String, AttributeValue<'a>)>,
+}
+
+impl <'a> Attributes<'a> {
+    pub fn new() -> Self {
+        Attributes::default()
+    }
+
+    /// Sets the value of `key`, returning the value it replaced if the key was already present.
+    ///
+    /// A new key is appended, an existing one keeps its position.
+    pub fn insert(&mut self, key: String, value: AttributeValue<'a>) -> Option<AttributeValue<'a>> {
+        match self.entries.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, existing)) => Some(std::mem::replace(existing, value)),
+            None => {
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&AttributeValue<'a>> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over the attributes, in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &AttributeValue<'a>)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /// Converts these attributes into ones that own all of their values.
+    pub fn into_owned(self) -> Attributes<'static> {
+        self.into_iter().map(|(k, v)| (k, v.into_owned())).collect()
+    }
+}
+
+/// # Panics
+/// * if the key is not present
+impl <'a, 'k> Index<&'k str> for Attributes<'a> {
+    type Output = AttributeValue<'a>;
+
+    fn index(&self, key: &'k str) -> &AttributeValue<'a> {
+        self.get(key).unwrap_or_else(|| panic!("no attribute {:?}", key))
+    }
+}
+
+/// Collects attributes in order, a repeated key updating the value of its first occurrence.
+impl <'a> FromIterator<(String, AttributeValue<'a>)> for Attributes<'a> {
+    fn from_iter<I: IntoIterator<Item = (String, AttributeValue<'a>)>>(iter: I) -> Self {
+        let mut attributes = Attributes::new();
+        for (key, value) in iter {
+            attributes.insert(key, value);
+        }
+        attributes
+    }
+}
+
+impl <'a> IntoIterator for Attributes<'a> {
+    type Item = (String, AttributeValue<'a>);
+    type IntoIter = std::vec::IntoIter<(String, AttributeValue<'a>)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+/// Serializes a map from key to value, in insertion order.
+#[cfg(feature = "serde")]
+impl <'a> serde::Serialize for Attributes<'a> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys<'s>(attributes: &'s Attributes<'_>) -> Vec<&'s str> {
+        attributes.iter().map(|(k, _)| k).collect()
+    }
+
+    #[test]
+    fn test_insertion_order() {
+        let mut attributes = Attributes::new();
+        assert_eq!(attributes.insert("c".to_string(), AttributeValue::Long(1)), None);
+        assert_eq!(attributes.insert("a".to_string(), AttributeValue::Long(2)), None);
+        assert_eq!(attributes.insert("b".to_string(), AttributeValue::Long(3)), None);
+        assert_eq!(attributes.insert("a".to_string(), AttributeValue::Long(4)), Some(AttributeValue::Long(2)));
+
+        assert_eq!(keys(&attributes), vec!["c", "a", "b"]);
+        assert_eq!(attributes["a"], AttributeValue::Long(4));
+        assert_eq!(attributes.len(), 3);
+        assert!(attributes.contains_key("b") && !attributes.contains_key("d"));
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let attributes: Attributes = vec![
+            ("b".to_string(), AttributeValue::from("x")),
+            ("a".to_string(), AttributeValue::from("y")),
+            ("b".to_string(), AttributeValue::from("z")),
+        ].into_iter().collect();
+        assert_eq!(keys(&attributes), vec!["b", "a"]);
+        assert_eq!(attributes.get("b"), Some(&AttributeValue::from("z")));
+        assert_eq!(attributes.clone().into_owned(), attributes);
+    }
+
+    #[test]
+    #[should_panic(expected = "no attribute \"missing\"")]
+    fn test_index_missing() {
+        let _ = &Attributes::new()["missing"];
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize_in_order() {
+        let attributes: Attributes = vec![
+            ("z".to_string(), AttributeValue::Long(1)),
+            ("a".to_string(), AttributeValue::Boolean(true)),
+        ].into_iter().collect();
+        assert_eq!(serde_json::to_string(&attributes).unwrap(),
+                   r#"{"z":{"intValue":"1"},"a":{"boolValue":true}}"#);
+    }
+}
@@ -26,3 +26,13 @@ pub trait Event {
     /// Return the attributes of the `Event`.
     fn attributes(&self) -> HashMap<&str, &AttributeValue>;
 }
+
+impl <E: Event + ?Sized> Event for &E {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn attributes(&self) -> HashMap<&str, &AttributeValue<'_>> {
+        (**self).attributes()
+    }
+}
@@ -14,6 +14,7 @@
  * limitations under the License.
  */
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 use crate::trace::attribute_value::AttributeValue;
 use crate::trace::span_context::SpanContext;
@@ -29,3 +30,82 @@ pub trait Link {
     /// Returns the set of attributes.
     fn attributes(&self) -> HashMap<&str, &AttributeValue>;
 }
+
+impl <L: Link + ?Sized> Link for &L {
+    fn context(&self) -> SpanContext<'_> {
+        (**self).context()
+    }
+
+    fn attributes(&self) -> HashMap<&str, &AttributeValue<'_>> {
+        (**self).attributes()
+    }
+}
+
+/// A `Link` holding its `SpanContext` and attributes.
+///
+/// ```
+/// use opentelemetry_api::trace::link::SimpleLink;
+/// use opentelemetry_api::trace::span_context::SpanContext;
+///
+/// let link = SimpleLink::new(SpanContext::invalid())
+///     .attribute("batch.index", 3);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct SimpleLink<'a> {
+    pub context: SpanContext<'a>,
+    pub attributes: HashMap<Cow<'a, str>, AttributeValue<'a>>,
+}
+
+impl <'a> SimpleLink<'a> {
+    /// Creates a link to the given `SpanContext`, without attributes.
+    pub fn new(context: SpanContext<'a>) -> Self {
+        SimpleLink { context, attributes: HashMap::new() }
+    }
+
+    /// Adds an attribute, replacing any previous value for the same key.
+    pub fn attribute<K, V>(mut self, key: K, value: V) -> Self
+        where K: Into<Cow<'a, str>>,
+              V: Into<AttributeValue<'a>>
+    {
+        self.attributes.insert(key.into(), value.into());
+        self
+    }
+}
+
+impl <'a> Link for SimpleLink<'a> {
+    fn context(&self) -> SpanContext<'_> {
+        self.context.clone()
+    }
+
+    fn attributes(&self) -> HashMap<&str, &AttributeValue<'_>> {
+        self.attributes.iter().map(|(k, v)| (k.as_ref(), v)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::span_id::SpanId;
+    use crate::trace::trace_id::TraceId;
+    use crate::trace::trace_options::TraceOptions;
+    use crate::trace::trace_state::TraceState;
+
+    fn context() -> SpanContext<'static> {
+        SpanContext::new(TraceId::from_bytes([1; 16]), SpanId::new(1), TraceOptions::IS_SAMPLED,
+                         TraceState::default())
+    }
+
+    #[test]
+    fn test_bare_link() {
+        let link = SimpleLink::new(context());
+        assert_eq!(Link::context(&link), context());
+        assert!(link.attributes().is_empty());
+    }
+
+    #[test]
+    fn test_link_attributes() {
+        let link = SimpleLink::new(context()).attribute("index", 1).attribute("index", 2);
+        assert_eq!(link.attributes().len(), 1);
+        assert_eq!(link.attributes()["index"], &AttributeValue::Long(2));
+    }
+}
@@ -1,4 +1,5 @@
 pub mod attribute_value;
+pub mod attributes;
 pub mod event;
 pub mod link;
 pub mod span_context;
@@ -10,3 +11,8 @@ pub mod trace_state;
 pub mod tracer;
 pub mod sampler;
 pub mod span;
+pub mod span_builder;
+pub mod span_data;
+#[cfg(feature = "serde")]
+pub mod otlp_json;
+pub mod propagation;
@@ -0,0 +1,259 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//!
+//! OTLP/JSON representation of `SpanData`, as accepted by the OpenTelemetry Collector over
+//! OTLP/HTTP.
+//!
+//! This is the JSON mapping of the OTLP `Span` message: camelCase field names, base16 trace and
+//! span ids, enums as integers and 64 bit integers, such as the nanosecond timestamps, as strings.
+//!
+//! See <https://opentelemetry.io/docs/specs/otlp/#json-protobuf-encoding>
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use crate::trace::attribute_value::AttributeValue;
+use crate::trace::attributes::Attributes;
+use crate::trace::span::SpanKind;
+use crate::trace::span_data::{SpanData, SpanEvent, SpanLink};
+use crate::trace::status::{CanonicalCode, Status};
+
+/// OTLP `STATUS_CODE_UNSET`, which OTLP readers treat as a successful span.
+const STATUS_CODE_UNSET: i32 = 0;
+/// OTLP `STATUS_CODE_ERROR`.
+const STATUS_CODE_ERROR: i32 = 2;
+
+/// Serializes the wrapped `SpanData` as an OTLP/JSON `Span`, e.g. with
+/// `serde_json::to_string(&OtlpSpan(&span_data))`.
+#[derive(Copy, Clone, Debug)]
+pub struct OtlpSpan<'s, 'a>(pub &'s SpanData<'a>);
+
+impl <'s, 'a> Serialize for OtlpSpan<'s, 'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let span = self.0;
+        let trace_state = span.context.state.to_header();
+
+        let mut state = serializer.serialize_struct("Span", 14)?;
+        state.serialize_field("traceId", &span.context.trace_id.as_hex())?;
+        state.serialize_field("spanId", &span.context.span_id.as_hex())?;
+        if !trace_state.is_empty() {
+            state.serialize_field("traceState", &trace_state)?;
+        }
+        state.serialize_field("name", &span.name)?;
+        state.serialize_field("kind", &kind(span.kind))?;
+        state.serialize_field("startTimeUnixNano", &unix_nanos(span.start_time))?;
+        state.serialize_field("endTimeUnixNano", &unix_nanos(span.end_time))?;
+        state.serialize_field("attributes", &OrderedAttributes(&span.attributes))?;
+        if span.dropped_attribute_count > 0 {
+            state.serialize_field("droppedAttributesCount", &span.dropped_attribute_count)?;
+        }
+        state.serialize_field("events", &span.events.iter().map(Event).collect::<Vec<_>>())?;
+        if span.dropped_event_count > 0 {
+            state.serialize_field("droppedEventsCount", &span.dropped_event_count)?;
+        }
+        state.serialize_field("links", &span.links.iter().map(Link).collect::<Vec<_>>())?;
+        if span.dropped_link_count > 0 {
+            state.serialize_field("droppedLinksCount", &span.dropped_link_count)?;
+        }
+        state.serialize_field("status", &OtlpStatus(&span.status))?;
+        state.end()
+    }
+}
+
+/// OTLP `SpanKind`, where `0` is `SPAN_KIND_UNSPECIFIED`.
+fn kind(kind: SpanKind) -> i32 {
+    match kind {
+        SpanKind::Internal => 1,
+        SpanKind::Server => 2,
+        SpanKind::Client => 3,
+        SpanKind::Producer => 4,
+        SpanKind::Consumer => 5,
+    }
+}
+
+/// Nanoseconds since the epoch as a string, as the JSON mapping requires for `fixed64`.
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos().to_string()
+}
+
+/// Span attributes as a list of OTLP `KeyValue`s, in insertion order.
+struct OrderedAttributes<'s, 'a>(&'s Attributes<'a>);
+
+impl <'s, 'a> Serialize for OrderedAttributes<'s, 'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(|(key, value)| KeyValue(key, value)))
+    }
+}
+
+/// Event or link attributes as a list of OTLP `KeyValue`s, sorted by key for a stable output.
+struct SortedAttributes<'s, 'a>(&'s HashMap<String, AttributeValue<'a>>);
+
+impl <'s, 'a> Serialize for SortedAttributes<'s, 'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut attributes: Vec<_> = self.0.iter().collect();
+        attributes.sort_by_key(|(key, _)| *key);
+        serializer.collect_seq(attributes.into_iter().map(|(key, value)| KeyValue(key, value)))
+    }
+}
+
+/// An OTLP `KeyValue`, the value being serialized as an `AnyValue`.
+struct KeyValue<'s, 'a>(&'s str, &'s AttributeValue<'a>);
+
+impl <'s, 'a> Serialize for KeyValue<'s, 'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("KeyValue", 2)?;
+        state.serialize_field("key", self.0)?;
+        state.serialize_field("value", self.1)?;
+        state.end()
+    }
+}
+
+struct Event<'s>(&'s SpanEvent);
+
+impl <'s> Serialize for Event<'s> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Event", 3)?;
+        state.serialize_field("timeUnixNano", &unix_nanos(self.0.timestamp))?;
+        state.serialize_field("name", &self.0.name)?;
+        state.serialize_field("attributes", &SortedAttributes(&self.0.attributes))?;
+        state.end()
+    }
+}
+
+struct Link<'s>(&'s SpanLink);
+
+impl <'s> Serialize for Link<'s> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let context = &self.0.context;
+        let trace_state = context.state.to_header();
+
+        let mut state = serializer.serialize_struct("Link", 4)?;
+        state.serialize_field("traceId", &context.trace_id.as_hex())?;
+        state.serialize_field("spanId", &context.span_id.as_hex())?;
+        if !trace_state.is_empty() {
+            state.serialize_field("traceState", &trace_state)?;
+        }
+        state.serialize_field("attributes", &SortedAttributes(&self.0.attributes))?;
+        state.end()
+    }
+}
+
+/// OTLP `Status`, `OK` being written as unset, and every other `CanonicalCode` as an error whose
+/// message is the description, or the name of the code if there is no description.
+struct OtlpStatus<'s, 'a>(&'s Status<'a>);
+
+impl <'s, 'a> Serialize for OtlpStatus<'s, 'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let status = self.0;
+        let mut state = serializer.serialize_struct("Status", 2)?;
+        if status.status_code == CanonicalCode::Ok {
+            state.serialize_field("code", &STATUS_CODE_UNSET)?;
+        } else {
+            let message = if status.description.is_empty() {
+                status.status_code.as_str()
+            } else {
+                &status.description
+            };
+            state.serialize_field("message", message)?;
+            state.serialize_field("code", &STATUS_CODE_ERROR)?;
+        }
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::*;
+    use crate::trace::span_context::SpanContext;
+    use crate::trace::span_id::SpanId;
+    use crate::trace::trace_id::TraceId;
+    use crate::trace::trace_options::TraceOptions;
+    use crate::trace::trace_state::TraceState;
+
+    const GOLDEN_SPAN: &str = include_str!("testdata/otlp_span.json");
+
+    fn context(span_id: u64, state: TraceState<'static>) -> SpanContext<'static> {
+        SpanContext::new(TraceId::from_hex("5b8efff798038103d269b633813fc60c").unwrap(),
+                         SpanId::new(span_id), TraceOptions::IS_SAMPLED, state)
+    }
+
+    fn span_data() -> SpanData<'static> {
+        let start_time = UNIX_EPOCH + Duration::from_nanos(1_544_712_660_000_000_000);
+        let mut attributes = Attributes::new();
+        attributes.insert("http.method".to_string(), AttributeValue::from("GET"));
+        attributes.insert("http.status_code".to_string(), AttributeValue::Long(404));
+        attributes.insert("retry".to_string(), AttributeValue::Boolean(true));
+        attributes.insert("sample.rate".to_string(), AttributeValue::Double(0.5));
+        attributes.insert("tags".to_string(), AttributeValue::StringArray(vec!["a".into(), "b".into()]));
+
+        let mut event_attributes = HashMap::new();
+        event_attributes.insert("attempt".to_string(), AttributeValue::LongArray(vec![1, 2]));
+
+        SpanData {
+            context: context(0xeee19b7ec3c1b174, TraceState::from_header("rojo=00f067aa0ba902b7").unwrap()),
+            name: "GET /users".into(),
+            kind: SpanKind::Server,
+            start_time,
+            end_time: start_time + Duration::from_millis(1),
+            attributes,
+            dropped_attribute_count: 2,
+            events: vec![SpanEvent {
+                name: "retry".to_string(),
+                attributes: event_attributes,
+                timestamp: start_time + Duration::from_micros(500),
+            }],
+            dropped_event_count: 0,
+            links: vec![SpanLink { context: context(0xeee19b7ec3c1b173, TraceState::default()),
+                                   attributes: HashMap::new() }],
+            dropped_link_count: 3,
+            status: Status::not_found().with_description("no such user"),
+        }
+    }
+
+    #[test]
+    fn test_golden_span() {
+        let json = serde_json::to_value(OtlpSpan(&span_data())).unwrap();
+        let golden: serde_json::Value = serde_json::from_str(GOLDEN_SPAN).unwrap();
+        assert_eq!(json, golden);
+    }
+
+    #[test]
+    fn test_defaults_omitted() {
+        let mut data = span_data();
+        data.context.state = TraceState::default();
+        data.dropped_attribute_count = 0;
+        data.dropped_link_count = 0;
+        data.status = Status::ok();
+
+        let json = serde_json::to_value(OtlpSpan(&data)).unwrap();
+        assert!(json.get("traceState").is_none());
+        assert!(json.get("droppedAttributesCount").is_none());
+        assert!(json.get("droppedEventsCount").is_none());
+        assert!(json.get("droppedLinksCount").is_none());
+        assert_eq!(json["status"], serde_json::json!({"code": STATUS_CODE_UNSET}));
+    }
+
+    #[test]
+    fn test_status_message_defaults_to_code() {
+        let mut data = span_data();
+        data.status = Status::unavailable();
+        let json = serde_json::to_value(OtlpSpan(&data)).unwrap();
+        assert_eq!(json["status"], serde_json::json!({"code": STATUS_CODE_ERROR, "message": "UNAVAILABLE"}));
+    }
+}
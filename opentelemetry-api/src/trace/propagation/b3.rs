@@ -0,0 +1,262 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//!
+//! Implementation of the Zipkin B3 formats, either the `b3` single header or the `X-B3-*` headers.
+//!
+//! See <https://github.com/openzipkin/b3-propagation>
+use crate::context::propagation::HttpTextFormat;
+use crate::trace::span_context::SpanContext;
+use crate::trace::span_id::SpanId;
+use crate::trace::trace_id::TraceId;
+use crate::trace::trace_options::TraceOptions;
+use crate::trace::trace_state::TraceState;
+
+pub const B3_SINGLE_HEADER: &str = "b3";
+pub const B3_TRACE_ID_HEADER: &str = "X-B3-TraceId";
+pub const B3_SPAN_ID_HEADER: &str = "X-B3-SpanId";
+pub const B3_SAMPLED_HEADER: &str = "X-B3-Sampled";
+pub const B3_FLAGS_HEADER: &str = "X-B3-Flags";
+
+/// Headers written by `inject`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum B3Encoding {
+    /// The `b3` header, `{trace-id}-{span-id}-{sampled}`.
+    SingleHeader,
+    /// The `X-B3-TraceId`, `X-B3-SpanId` and `X-B3-Sampled` headers.
+    MultipleHeader,
+}
+
+/// Propagates `SpanContext`s using the B3 headers, injecting those of its `B3Encoding`.
+///
+/// Extraction accepts both encodings, see `extract`.
+#[derive(Copy, Clone, Debug)]
+pub struct B3Propagator {
+    encoding: B3Encoding,
+}
+
+impl B3Propagator {
+    pub fn new(encoding: B3Encoding) -> Self {
+        B3Propagator { encoding }
+    }
+}
+
+impl HttpTextFormat for B3Propagator {
+    fn fields(&self) -> &'static [&'static str] {
+        &[B3_SINGLE_HEADER, B3_TRACE_ID_HEADER, B3_SPAN_ID_HEADER, B3_SAMPLED_HEADER, B3_FLAGS_HEADER]
+    }
+
+    fn inject<C, S>(&self, context: &SpanContext, carrier: &mut C, setter: S)
+        where S: Fn(&mut C, &str, &str)
+    {
+        inject(context, self.encoding, carrier, setter)
+    }
+
+    fn extract<C, G>(&self, carrier: &C, getter: G) -> Option<SpanContext<'static>>
+        where G: Fn(&C, &str) -> Option<String>
+    {
+        extract(carrier, getter)
+    }
+}
+
+/// Injects the given `SpanContext` into the carrier, using the headers of `encoding`.
+pub fn inject<C, S>(context: &SpanContext, encoding: B3Encoding, carrier: &mut C, setter: S)
+    where S: Fn(&mut C, &str, &str)
+{
+    let sampled = if context.options.is_sampled() { "1" } else { "0" };
+    match encoding {
+        B3Encoding::SingleHeader => {
+            let header = format!("{}-{}-{}", context.trace_id.as_hex(), context.span_id.as_hex(), sampled);
+            setter(carrier, B3_SINGLE_HEADER, &header);
+        },
+        B3Encoding::MultipleHeader => {
+            setter(carrier, B3_TRACE_ID_HEADER, &context.trace_id.as_hex());
+            setter(carrier, B3_SPAN_ID_HEADER, &context.span_id.as_hex());
+            setter(carrier, B3_SAMPLED_HEADER, sampled);
+        },
+    }
+}
+
+/// Extracts a remote `SpanContext` with an empty `TraceState` from the carrier.
+///
+/// The `b3` single header is preferred over the `X-B3-*` headers. Debug traces are sampled, and
+/// traces without a sampling decision are not, leaving the decision to the local `Sampler`.
+///
+/// Returns `None` if the carrier holds no context, or an invalid one.
+pub fn extract<C, G>(carrier: &C, getter: G) -> Option<SpanContext<'static>>
+    where G: Fn(&C, &str) -> Option<String>
+{
+    if let Some(header) = getter(carrier, B3_SINGLE_HEADER) {
+        return decode_single_header(&header);
+    }
+
+    let trace_id = parse_trace_id(&getter(carrier, B3_TRACE_ID_HEADER)?)?;
+    let span_id = parse_span_id(&getter(carrier, B3_SPAN_ID_HEADER)?)?;
+    let debug = getter(carrier, B3_FLAGS_HEADER).as_deref().map(str::trim) == Some("1");
+    let sampled = match getter(carrier, B3_SAMPLED_HEADER) {
+        Some(sampled) => parse_sampled(&sampled)?,
+        None => false,
+    };
+    let options = TraceOptions::default().with_sampled(debug || sampled);
+    Some(SpanContext::new_remote(trace_id, span_id, options, TraceState::default()))
+}
+
+/// Decodes a `b3` header value, `{trace-id}-{span-id}[-{sampled}[-{parent-span-id}]]`.
+///
+/// A header only holding a sampling decision carries no context, so it decodes to `None`.
+pub fn decode_single_header(header: &str) -> Option<SpanContext<'static>> {
+    let fields: Vec<&str> = header.trim().split('-').collect();
+    if fields.len() < 2 || fields.len() > 4 {
+        return None;
+    }
+
+    let trace_id = parse_trace_id(fields[0])?;
+    let span_id = parse_span_id(fields[1])?;
+    let sampled = match fields.get(2) {
+        Some(sampled) => parse_sampled(sampled)?,
+        None => false,
+    };
+    if let Some(parent_span_id) = fields.get(3) {
+        parse_span_id(parent_span_id)?;
+    }
+
+    let options = TraceOptions::default().with_sampled(sampled);
+    Some(SpanContext::new_remote(trace_id, span_id, options, TraceState::default()))
+}
+
+/// Parses a 64 or 128 bit trace id, 16 or 32 base16 characters.
+fn parse_trace_id(hex: &str) -> Option<TraceId> {
+    let hex = hex.trim();
+    let trace_id = match hex.len() {
+        16 => TraceId::from_hex(&format!("{:0>32}", hex)),
+        _ => TraceId::from_hex(hex),
+    };
+    trace_id.filter(TraceId::is_valid)
+}
+
+fn parse_span_id(hex: &str) -> Option<SpanId> {
+    SpanId::from_hex(hex.trim()).filter(SpanId::is_valid)
+}
+
+/// Parses a sampling decision, `d` meaning debug which implies sampled.
+fn parse_sampled(sampled: &str) -> Option<bool> {
+    match sampled.trim() {
+        "1" | "d" | "true" => Some(true),
+        "0" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    const TRACE_ID: &str = "80f198ee56343ba864fe8b2a57d3eff7";
+    const SPAN_ID: &str = "e457b5a2e4d86bd1";
+
+    fn setter(carrier: &mut HashMap<String, String>, key: &str, value: &str) {
+        carrier.insert(key.to_string(), value.to_string());
+    }
+
+    fn getter(carrier: &HashMap<String, String>, key: &str) -> Option<String> {
+        carrier.get(key).cloned()
+    }
+
+    fn carrier(headers: &[(&str, &str)]) -> HashMap<String, String> {
+        headers.iter().map(|&(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn context(options: TraceOptions) -> SpanContext<'static> {
+        SpanContext::new(TraceId::from_hex(TRACE_ID).unwrap(), SpanId::from_hex(SPAN_ID).unwrap(),
+                         options, TraceState::default())
+    }
+
+    #[test]
+    fn test_single_header_round_trip() {
+        let mut headers = HashMap::new();
+        inject(&context(TraceOptions::IS_SAMPLED), B3Encoding::SingleHeader, &mut headers, setter);
+        assert_eq!(headers[B3_SINGLE_HEADER], format!("{}-{}-1", TRACE_ID, SPAN_ID));
+        assert_eq!(headers.len(), 1);
+
+        let extracted = extract(&headers, getter).unwrap();
+        assert!(extracted.is_remote());
+        assert_eq!(extracted, SpanContext { is_remote: true, ..context(TraceOptions::IS_SAMPLED) });
+    }
+
+    #[test]
+    fn test_multiple_header_round_trip() {
+        let mut headers = HashMap::new();
+        inject(&context(TraceOptions::default()), B3Encoding::MultipleHeader, &mut headers, setter);
+        assert_eq!(headers[B3_TRACE_ID_HEADER], TRACE_ID);
+        assert_eq!(headers[B3_SPAN_ID_HEADER], SPAN_ID);
+        assert_eq!(headers[B3_SAMPLED_HEADER], "0");
+
+        let extracted = extract(&headers, getter).unwrap();
+        assert_eq!(extracted, SpanContext { is_remote: true, ..context(TraceOptions::default()) });
+    }
+
+    #[test]
+    fn test_single_header_forms() {
+        let parent = "05e3ac9a4f6e3b90";
+        let sampled = |header: String| extract(&carrier(&[(B3_SINGLE_HEADER, &header)]), getter)
+            .map(|context| context.options.is_sampled());
+
+        assert_eq!(sampled(format!("{}-{}", TRACE_ID, SPAN_ID)), Some(false));
+        assert_eq!(sampled(format!("{}-{}-d", TRACE_ID, SPAN_ID)), Some(true));
+        assert_eq!(sampled(format!("{}-{}-1-{}", TRACE_ID, SPAN_ID, parent)), Some(true));
+        assert_eq!(sampled(format!("{}-{}-0", &TRACE_ID[16..], SPAN_ID)), Some(false));
+        assert_eq!(sampled("0".to_string()), None);
+        assert_eq!(sampled(format!("{}-{}-x", TRACE_ID, SPAN_ID)), None);
+        assert_eq!(sampled(format!("{}-{}-1-{}-extra", TRACE_ID, SPAN_ID, parent)), None);
+    }
+
+    #[test]
+    fn test_64_bit_trace_id() {
+        let headers = carrier(&[(B3_TRACE_ID_HEADER, &TRACE_ID[16..]), (B3_SPAN_ID_HEADER, SPAN_ID)]);
+        let extracted = extract(&headers, getter).unwrap();
+        assert_eq!(extracted.trace_id.as_hex(), format!("0000000000000000{}", &TRACE_ID[16..]));
+    }
+
+    #[test]
+    fn test_missing_sampled_header() {
+        let headers = carrier(&[(B3_TRACE_ID_HEADER, TRACE_ID), (B3_SPAN_ID_HEADER, SPAN_ID)]);
+        let extracted = extract(&headers, getter).unwrap();
+        assert!(!extracted.options.is_sampled());
+
+        // The debug flag implies sampled
+        let headers = carrier(&[(B3_TRACE_ID_HEADER, TRACE_ID), (B3_SPAN_ID_HEADER, SPAN_ID),
+                                (B3_FLAGS_HEADER, "1")]);
+        assert!(extract(&headers, getter).unwrap().options.is_sampled());
+    }
+
+    #[test]
+    fn test_extract_invalid() {
+        assert_eq!(extract(&HashMap::new(), getter), None);
+        assert_eq!(extract(&carrier(&[(B3_TRACE_ID_HEADER, TRACE_ID)]), getter), None);
+
+        let invalid = [
+            carrier(&[(B3_TRACE_ID_HEADER, &"0".repeat(32)), (B3_SPAN_ID_HEADER, SPAN_ID)]),
+            carrier(&[(B3_TRACE_ID_HEADER, &TRACE_ID[1..]), (B3_SPAN_ID_HEADER, SPAN_ID)]),
+            carrier(&[(B3_TRACE_ID_HEADER, TRACE_ID), (B3_SPAN_ID_HEADER, &"0".repeat(16))]),
+            carrier(&[(B3_TRACE_ID_HEADER, TRACE_ID), (B3_SPAN_ID_HEADER, SPAN_ID),
+                      (B3_SAMPLED_HEADER, "yes")]),
+        ];
+        for headers in invalid.iter() {
+            assert_eq!(extract(headers, getter), None, "{:?}", headers);
+        }
+    }
+}
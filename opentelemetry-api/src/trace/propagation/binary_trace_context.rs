@@ -0,0 +1,185 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//!
+//! Implementation of the W3C Trace Context binary format.
+//!
+//! See <https://w3c.github.io/trace-context-binary/>
+use std::convert::TryFrom;
+
+use crate::context::propagation::{BinaryFormat, PropagationError};
+use crate::trace::span_context::SpanContext;
+use crate::trace::span_id::SpanId;
+use crate::trace::trace_id::TraceId;
+use crate::trace::trace_options::TraceOptions;
+use crate::trace::trace_state::TraceState;
+
+const VERSION: u8 = 0;
+const TRACE_ID_FIELD: u8 = 0;
+const SPAN_ID_FIELD: u8 = 1;
+const TRACE_OPTIONS_FIELD: u8 = 2;
+
+const TRACE_ID_LEN: usize = 16;
+const SPAN_ID_LEN: usize = 8;
+
+/// Propagates `SpanContext`s using the W3C binary format.
+///
+/// The `TraceState` is not part of the binary format and is never propagated.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct BinaryTraceContext;
+
+impl BinaryFormat for BinaryTraceContext {
+    fn to_bytes(&self, context: &SpanContext) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(2 + TRACE_ID_LEN + 1 + SPAN_ID_LEN + 2);
+        bytes.push(VERSION);
+        bytes.push(TRACE_ID_FIELD);
+        bytes.extend_from_slice(&context.trace_id.to_bytes());
+        bytes.push(SPAN_ID_FIELD);
+        bytes.extend_from_slice(&context.span_id.to_bytes());
+        bytes.push(TRACE_OPTIONS_FIELD);
+        bytes.push(context.options.to_byte());
+        bytes
+    }
+
+    /// Deserializes a remote `SpanContext`.
+    ///
+    /// Truncated input or unexpected field ids are `PropagationError::Malformed`. When both ids are
+    /// all zeros, `PropagationError::InvalidTraceId` is returned.
+    fn from_bytes(&self, bytes: &[u8]) -> Result<SpanContext<'static>, PropagationError> {
+        let (&version, bytes) = bytes.split_first().ok_or(PropagationError::Malformed)?;
+        if version != VERSION {
+            return Err(PropagationError::UnsupportedVersion(version));
+        }
+
+        let bytes = expect_field(bytes, TRACE_ID_FIELD, TRACE_ID_LEN)
+            .ok_or(PropagationError::Malformed)?;
+        let trace_id = TraceId::from_bytes(<[u8; TRACE_ID_LEN]>::try_from(&bytes[..TRACE_ID_LEN]).unwrap());
+        let bytes = &bytes[TRACE_ID_LEN..];
+
+        let bytes = expect_field(bytes, SPAN_ID_FIELD, SPAN_ID_LEN)
+            .ok_or(PropagationError::Malformed)?;
+        let span_id = SpanId::from_bytes(<[u8; SPAN_ID_LEN]>::try_from(&bytes[..SPAN_ID_LEN]).unwrap());
+        let bytes = &bytes[SPAN_ID_LEN..];
+
+        let options = match expect_field(bytes, TRACE_OPTIONS_FIELD, 1) {
+            Some(bytes) => TraceOptions::from_byte(bytes[0]),
+            None if bytes.is_empty() => TraceOptions::default(),
+            None => return Err(PropagationError::Malformed),
+        };
+
+        if !trace_id.is_valid() {
+            return Err(PropagationError::InvalidTraceId);
+        }
+        if !span_id.is_valid() {
+            return Err(PropagationError::InvalidSpanId);
+        }
+
+        Ok(SpanContext::new_remote(trace_id, span_id, options, TraceState::default()))
+    }
+}
+
+/// Checks that `bytes` starts with the given field id followed by at least `len` bytes, returning
+/// the bytes after the field id.
+fn expect_field(bytes: &[u8], field: u8, len: usize) -> Option<&[u8]> {
+    match bytes.split_first() {
+        Some((&id, rest)) if id == field && rest.len() >= len => Some(rest),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> SpanContext<'static> {
+        SpanContext::new(
+            TraceId::from_bytes([1; 16]),
+            SpanId::new(2),
+            TraceOptions::IS_SAMPLED,
+            TraceState::default(),
+        )
+    }
+
+    fn bytes(trace_id: [u8; 16], span_id: [u8; 8]) -> Vec<u8> {
+        let mut bytes = vec![VERSION, TRACE_ID_FIELD];
+        bytes.extend_from_slice(&trace_id);
+        bytes.push(SPAN_ID_FIELD);
+        bytes.extend_from_slice(&span_id);
+        bytes.extend_from_slice(&[TRACE_OPTIONS_FIELD, 1]);
+        bytes
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let bytes = BinaryTraceContext.to_bytes(&context());
+        assert_eq!(bytes.len(), 29);
+        let remote = SpanContext { is_remote: true, ..context() };
+        assert_eq!(BinaryTraceContext.from_bytes(&bytes), Ok(remote));
+    }
+
+    #[test]
+    fn test_options_are_optional() {
+        let mut bytes = BinaryTraceContext.to_bytes(&context());
+        bytes.truncate(27);
+        let extracted = BinaryTraceContext.from_bytes(&bytes).unwrap();
+        assert_eq!(extracted.options, TraceOptions::default());
+    }
+
+    #[test]
+    fn test_zero_trace_id() {
+        assert_eq!(BinaryTraceContext.from_bytes(&bytes([0; 16], [2; 8])),
+                   Err(PropagationError::InvalidTraceId));
+    }
+
+    #[test]
+    fn test_zero_span_id() {
+        assert_eq!(BinaryTraceContext.from_bytes(&bytes([1; 16], [0; 8])),
+                   Err(PropagationError::InvalidSpanId));
+    }
+
+    #[test]
+    fn test_zero_trace_and_span_id() {
+        assert_eq!(BinaryTraceContext.from_bytes(&bytes([0; 16], [0; 8])),
+                   Err(PropagationError::InvalidTraceId));
+    }
+
+    #[test]
+    fn test_malformed() {
+        let valid = BinaryTraceContext.to_bytes(&context());
+        assert_eq!(BinaryTraceContext.from_bytes(&[]), Err(PropagationError::Malformed));
+        assert_eq!(BinaryTraceContext.from_bytes(&[1]), Err(PropagationError::UnsupportedVersion(1)));
+        assert_eq!(BinaryTraceContext.from_bytes(&valid[..10]), Err(PropagationError::Malformed));
+        assert_eq!(BinaryTraceContext.from_bytes(&valid[..20]), Err(PropagationError::Malformed));
+        assert_eq!(BinaryTraceContext.from_bytes(&valid[..28]), Err(PropagationError::Malformed));
+    }
+
+    #[test]
+    fn test_truncated_at_every_field_boundary() {
+        let valid = BinaryTraceContext.to_bytes(&context());
+        // Truncating right after the span id only drops the optional trace options
+        for len in (0..valid.len()).filter(|&len| len != 27) {
+            assert_eq!(BinaryTraceContext.from_bytes(&valid[..len]), Err(PropagationError::Malformed),
+                       "truncated to {} bytes", len);
+        }
+    }
+
+    #[test]
+    fn test_unexpected_field_id() {
+        let mut bytes = BinaryTraceContext.to_bytes(&context());
+        bytes[18] = TRACE_OPTIONS_FIELD;
+        assert_eq!(BinaryTraceContext.from_bytes(&bytes), Err(PropagationError::Malformed));
+    }
+}
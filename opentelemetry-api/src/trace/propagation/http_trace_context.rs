@@ -0,0 +1,303 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//!
+//! Implementation of the W3C Trace Context HTTP format.
+//!
+//! See <https://w3c.github.io/trace-context/>
+use crate::context::propagation::HttpTextFormat;
+use crate::trace::span_context::SpanContext;
+use crate::trace::span_id::SpanId;
+use crate::trace::trace_id::TraceId;
+use crate::trace::trace_options::TraceOptions;
+use crate::trace::trace_state::{TraceState, MAX_KEY_VALUE_PAIRS};
+
+pub const TRACEPARENT: &str = "traceparent";
+pub const TRACESTATE: &str = "tracestate";
+
+const VERSION: &str = "00";
+const INVALID_VERSION: &str = "ff";
+
+/// Propagates `SpanContext`s using the W3C `traceparent` and `tracestate` headers.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct HttpTraceContext;
+
+impl HttpTextFormat for HttpTraceContext {
+    fn fields(&self) -> &'static [&'static str] {
+        &[TRACEPARENT, TRACESTATE]
+    }
+
+    fn inject<C, S>(&self, context: &SpanContext, carrier: &mut C, setter: S)
+        where S: Fn(&mut C, &str, &str)
+    {
+        setter(carrier, TRACEPARENT, &encode_traceparent(context));
+        if !context.state.is_empty() {
+            setter(carrier, TRACESTATE, &context.state.to_header());
+        }
+    }
+
+    fn extract<C, G>(&self, carrier: &C, getter: G) -> Option<SpanContext<'static>>
+        where G: Fn(&C, &str) -> Option<String>
+    {
+        let mut context = decode_traceparent(&getter(carrier, TRACEPARENT)?)?;
+        if let Some(state) = getter(carrier, TRACESTATE).and_then(|h| TraceState::from_header(&h)) {
+            context.state = state;
+        }
+        Some(context)
+    }
+}
+
+/// Encodes the `traceparent` header value for the given `SpanContext`.
+pub fn encode_traceparent(context: &SpanContext) -> String {
+    format!("{}-{}-{}-{:02x}",
+            VERSION, context.trace_id.as_hex(), context.span_id.as_hex(), context.options.to_byte())
+}
+
+/// The fields of a `traceparent` header, before they are assembled into a `SpanContext`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Traceparent {
+    version: u8,
+    trace_id: TraceId,
+    span_id: SpanId,
+    options: TraceOptions,
+}
+
+impl Traceparent {
+    /// Returns the version of the header format.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Returns the `TraceId` of the parent.
+    pub fn trace_id(&self) -> TraceId {
+        self.trace_id
+    }
+
+    /// Returns the `SpanId` of the parent.
+    pub fn span_id(&self) -> SpanId {
+        self.span_id
+    }
+
+    /// Returns the trace flags of the parent.
+    pub fn options(&self) -> TraceOptions {
+        self.options
+    }
+
+    /// Assembles a remote `SpanContext` with an empty `TraceState` out of these fields.
+    pub fn to_span_context(&self) -> SpanContext<'static> {
+        SpanContext::new_remote(self.trace_id, self.span_id, self.options, TraceState::default())
+    }
+}
+
+/// Parses the fields of a `traceparent` header value.
+///
+/// Parsing is lenient towards future versions, which are read by their first four fields only.
+/// Returns `None` for malformed headers and for all-zero trace or span ids.
+pub fn parse_traceparent_fields(header: &str) -> Option<Traceparent> {
+    let fields: Vec<&str> = header.trim().split('-').collect();
+    if fields.len() < 4 {
+        return None;
+    }
+
+    let is_hex = fields[0].chars().all(|c| c.is_ascii_hexdigit());
+    if fields[0].len() != 2 || !is_hex || fields[0] == INVALID_VERSION {
+        return None;
+    }
+    let version = u8::from_str_radix(fields[0], 16).ok()?;
+    if fields[0] == VERSION && fields.len() != 4 {
+        return None;
+    }
+
+    let trace_id = TraceId::from_hex(fields[1]).filter(TraceId::is_valid)?;
+    let span_id = SpanId::from_hex(fields[2]).filter(SpanId::is_valid)?;
+    if fields[3].len() != 2 {
+        return None;
+    }
+    let options = u8::from_str_radix(fields[3], 16).ok().map(TraceOptions::from_byte)?;
+
+    Some(Traceparent { version, trace_id, span_id, options })
+}
+
+/// Decodes a `traceparent` header value into a remote `SpanContext` with an empty `TraceState`.
+///
+/// See `parse_traceparent_fields` for the accepted formats.
+pub fn decode_traceparent(header: &str) -> Option<SpanContext<'static>> {
+    parse_traceparent_fields(header).map(|parent| parent.to_span_context())
+}
+
+/// Injects the `SpanContext` with `vendor_key=vendor_value` moved to the front of its tracestate.
+///
+/// Vendors are expected to stamp their own entry on every outgoing request, and W3C requires
+/// the most recently written entry to come first. When the tracestate is already full, its last
+/// entry is evicted to make room for a new vendor entry.
+///
+/// # Panics
+/// * if the key or value are not valid `TraceState` entries
+pub fn inject_with_vendor<C, S>(context: &SpanContext, vendor_key: &str, vendor_value: &str,
+                                carrier: &mut C, setter: S)
+    where S: Fn(&mut C, &str, &str)
+{
+    let mut builder = context.state.as_builder();
+    if context.state.get(vendor_key).is_none() && context.state.len() >= MAX_KEY_VALUE_PAIRS {
+        if let Some(last) = context.state.entries().last() {
+            builder = builder.remove(last.key.as_ref());
+        }
+    }
+    let state = builder.set(vendor_key, vendor_value).build();
+    let stamped = SpanContext::new(context.trace_id, context.span_id, context.options, state);
+    HttpTraceContext.inject(&stamped, carrier, setter);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+    const SPAN_ID: &str = "00f067aa0ba902b7";
+
+    fn setter(carrier: &mut HashMap<String, String>, key: &str, value: &str) {
+        carrier.insert(key.to_string(), value.to_string());
+    }
+
+    fn getter(carrier: &HashMap<String, String>, key: &str) -> Option<String> {
+        carrier.get(key).cloned()
+    }
+
+    fn context() -> SpanContext<'static> {
+        SpanContext::new(
+            TraceId::from_hex(TRACE_ID).unwrap(),
+            SpanId::from_hex(SPAN_ID).unwrap(),
+            TraceOptions::IS_SAMPLED,
+            TraceState::from_header("rojo=00f067aa0ba902b7").unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_inject_extract_round_trip() {
+        let mut carrier = HashMap::new();
+        HttpTraceContext.inject(&context(), &mut carrier, setter);
+        assert_eq!(carrier[TRACEPARENT], format!("00-{}-{}-01", TRACE_ID, SPAN_ID));
+        assert_eq!(carrier[TRACESTATE], "rojo=00f067aa0ba902b7");
+
+        let extracted = HttpTraceContext.extract(&carrier, getter).unwrap();
+        assert!(extracted.is_remote());
+        assert_eq!(extracted, SpanContext { is_remote: true, ..context() });
+    }
+
+    #[test]
+    fn test_extract_missing_header() {
+        assert_eq!(HttpTraceContext.extract(&HashMap::new(), getter), None);
+    }
+
+    #[test]
+    fn test_decode_traceparent() {
+        let ctx = decode_traceparent(&format!("00-{}-{}-00", TRACE_ID, SPAN_ID)).unwrap();
+        assert_eq!(ctx.trace_id.as_hex(), TRACE_ID);
+        assert_eq!(ctx.span_id.as_hex(), SPAN_ID);
+        assert_eq!(ctx.options, TraceOptions::DEFAULT_OPTIONS);
+        assert!(ctx.is_remote());
+
+        // Future versions may append fields
+        assert!(decode_traceparent(&format!("cc-{}-{}-01-what-the-future", TRACE_ID, SPAN_ID)).is_some());
+    }
+
+    #[test]
+    fn test_decode_traceparent_unknown_flags() {
+        let ctx = decode_traceparent(&format!("00-{}-{}-81", TRACE_ID, SPAN_ID)).unwrap();
+        assert_eq!(ctx.options, TraceOptions::IS_SAMPLED);
+        assert_eq!(encode_traceparent(&ctx), format!("00-{}-{}-01", TRACE_ID, SPAN_ID));
+    }
+
+    #[test]
+    fn test_decode_traceparent_invalid() {
+        let invalid = [
+            "".to_string(),
+            format!("ff-{}-{}-01", TRACE_ID, SPAN_ID),
+            format!("00-{}-{}-01-extra", TRACE_ID, SPAN_ID),
+            format!("00-{}-{}-01", "0".repeat(32), SPAN_ID),
+            format!("00-{}-{}-01", TRACE_ID, "0".repeat(16)),
+            format!("00-{}-{}-1", TRACE_ID, SPAN_ID),
+            format!("+1-{}-{}-01", TRACE_ID, SPAN_ID),
+            format!("00-{}-{}-01", &TRACE_ID[1..], SPAN_ID),
+        ];
+        for header in invalid.iter() {
+            assert_eq!(decode_traceparent(header), None, "{}", header);
+        }
+    }
+
+    #[test]
+    fn test_parse_traceparent_fields() {
+        let parent = parse_traceparent_fields(&format!("00-{}-{}-01", TRACE_ID, SPAN_ID)).unwrap();
+        assert_eq!(parent.version(), 0);
+        assert_eq!(parent.trace_id().as_hex(), TRACE_ID);
+        assert_eq!(parent.span_id().as_hex(), SPAN_ID);
+        assert_eq!(parent.options(), TraceOptions::IS_SAMPLED);
+
+        let parent = parse_traceparent_fields(&format!("01-{}-{}-00", TRACE_ID, SPAN_ID)).unwrap();
+        assert_eq!(parent.version(), 1);
+        assert_eq!(parent.options(), TraceOptions::DEFAULT_OPTIONS);
+
+        let parent = parse_traceparent_fields(&format!("01-{}-{}-00-future", TRACE_ID, SPAN_ID)).unwrap();
+        assert_eq!(parent.version(), 1);
+        assert_eq!(parent.to_span_context().trace_id.as_hex(), TRACE_ID);
+    }
+
+    #[test]
+    fn test_inject_with_vendor() {
+        let mut carrier = HashMap::new();
+        inject_with_vendor(&context(), "congo", "t61rcWkgMzE", &mut carrier, setter);
+        assert_eq!(carrier[TRACESTATE], "congo=t61rcWkgMzE,rojo=00f067aa0ba902b7");
+
+        // An existing vendor entry moves to the front
+        let mut carrier = HashMap::new();
+        inject_with_vendor(&context(), "rojo", "updated", &mut carrier, setter);
+        assert_eq!(carrier[TRACESTATE], "rojo=updated");
+        assert_eq!(carrier[TRACEPARENT], format!("00-{}-{}-01", TRACE_ID, SPAN_ID));
+    }
+
+    #[test]
+    fn test_inject_with_vendor_moves_existing_entry() {
+        let state = TraceState::from_header("rojo=1,congo=2,tango=3").unwrap();
+        let context = SpanContext { state, ..context() };
+        let mut carrier = HashMap::new();
+        inject_with_vendor(&context, "congo", "updated", &mut carrier, setter);
+        assert_eq!(carrier[TRACESTATE], "congo=updated,rojo=1,tango=3");
+    }
+
+    #[test]
+    fn test_inject_with_vendor_full_state() {
+        let header = (0..MAX_KEY_VALUE_PAIRS).map(|i| format!("k{}=v{}", i, i)).collect::<Vec<_>>();
+        let state = TraceState::from_header(&header.join(",")).unwrap();
+        let context = SpanContext { state, ..context() };
+
+        let mut carrier = HashMap::new();
+        inject_with_vendor(&context, "congo", "t61rc", &mut carrier, setter);
+        let stamped = TraceState::from_header(&carrier[TRACESTATE]).unwrap();
+        assert_eq!(stamped.len(), MAX_KEY_VALUE_PAIRS);
+        assert_eq!(stamped.entries()[0].key, "congo");
+        assert_eq!(stamped.entries()[1].key, "k0");
+        assert!(stamped.get("k31").is_none());
+
+        // Updating an existing entry evicts nothing
+        let mut carrier = HashMap::new();
+        inject_with_vendor(&context, "k31", "updated", &mut carrier, setter);
+        let stamped = TraceState::from_header(&carrier[TRACESTATE]).unwrap();
+        assert_eq!(stamped.len(), MAX_KEY_VALUE_PAIRS);
+        assert_eq!(stamped.entries()[0].value, "updated");
+        assert_eq!(stamped.entries()[31].key, "k30");
+    }
+}
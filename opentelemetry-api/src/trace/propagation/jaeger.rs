@@ -0,0 +1,183 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//!
+//! Implementation of the Jaeger `uber-trace-id` header format,
+//! `{trace-id}:{span-id}:{parent-span-id}:{flags}`.
+//!
+//! See <https://www.jaegertracing.io/docs/client-libraries/#propagation-format>
+use std::error::Error;
+use std::fmt;
+
+use crate::trace::span_context::SpanContext;
+use crate::trace::span_id::SpanId;
+use crate::trace::trace_id::TraceId;
+use crate::trace::trace_options::TraceOptions;
+use crate::trace::trace_state::TraceState;
+
+pub const UBER_TRACE_ID: &str = "uber-trace-id";
+
+/// Flag bit of a sampled trace.
+const FLAG_SAMPLED: u8 = 0x01;
+/// Flag bit of a debug trace, which Jaeger always samples.
+const FLAG_DEBUG: u8 = 0x02;
+
+/// Error returned when an `uber-trace-id` header value cannot be decoded.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum JaegerError {
+    /// The value does not have four colon delimited fields.
+    Malformed,
+    /// The trace id is not 1 to 32 base16 characters, or is all zeros.
+    InvalidTraceId,
+    /// The span id is not 1 to 16 base16 characters, or is all zeros.
+    InvalidSpanId,
+    /// The deprecated parent span id is not 1 to 16 base16 characters.
+    InvalidParentSpanId,
+    /// The flags are not 1 or 2 base16 characters.
+    InvalidFlags,
+}
+
+impl fmt::Display for JaegerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JaegerError::Malformed => write!(f, "expected {{trace-id}}:{{span-id}}:{{parent-span-id}}:{{flags}}"),
+            JaegerError::InvalidTraceId => write!(f, "invalid trace id"),
+            JaegerError::InvalidSpanId => write!(f, "invalid span id"),
+            JaegerError::InvalidParentSpanId => write!(f, "invalid parent span id"),
+            JaegerError::InvalidFlags => write!(f, "invalid flags"),
+        }
+    }
+}
+
+impl Error for JaegerError {}
+
+/// Encodes the `uber-trace-id` header value for the given `SpanContext`.
+///
+/// Trace ids whose upper 64 bits are zero are written with 16 characters, as Jaeger clients
+/// generating 64 bit ids do. The parent span id is deprecated and always written as `0`.
+pub fn encode(context: &SpanContext) -> String {
+    let trace_id = context.trace_id.as_hex();
+    let trace_id = match trace_id.strip_prefix("0000000000000000") {
+        Some(lower) => lower,
+        None => &trace_id,
+    };
+    let flags = if context.options.is_sampled() { FLAG_SAMPLED } else { 0 };
+    format!("{}:{}:0:{:x}", trace_id, context.span_id.as_hex(), flags)
+}
+
+/// Decodes an `uber-trace-id` header value into a remote `SpanContext` with an empty
+/// `TraceState`.
+///
+/// Ids may omit their leading zeros, and the value may be URL encoded, i.e. use `%3A` in place of
+/// the colons. Both the sampled and the debug flags mark the context as sampled, as `TraceOptions`
+/// cannot represent the debug flag.
+pub fn decode(header: &str) -> Result<SpanContext<'static>, JaegerError> {
+    let header = header.trim().replace("%3A", ":").replace("%3a", ":");
+    let fields: Vec<&str> = header.split(':').collect();
+    if fields.len() != 4 {
+        return Err(JaegerError::Malformed);
+    }
+
+    let trace_id = parse_hex(fields[0], 2 * TraceId::get_size())
+        .and_then(|hex| TraceId::from_hex(&hex))
+        .filter(TraceId::is_valid)
+        .ok_or(JaegerError::InvalidTraceId)?;
+    let span_id = parse_hex(fields[1], 2 * SpanId::get_size())
+        .and_then(|hex| SpanId::from_hex(&hex))
+        .filter(SpanId::is_valid)
+        .ok_or(JaegerError::InvalidSpanId)?;
+    parse_hex(fields[2], 2 * SpanId::get_size()).ok_or(JaegerError::InvalidParentSpanId)?;
+    let flags = parse_hex(fields[3], 2)
+        .and_then(|hex| u8::from_str_radix(&hex, 16).ok())
+        .ok_or(JaegerError::InvalidFlags)?;
+
+    let options = TraceOptions::default().with_sampled(flags & (FLAG_SAMPLED | FLAG_DEBUG) != 0);
+    Ok(SpanContext::new_remote(trace_id, span_id, options, TraceState::default()))
+}
+
+/// Left pads a field of 1 to `len` base16 characters with zeros to `len` characters.
+fn parse_hex(field: &str, len: usize) -> Option<String> {
+    if field.is_empty() || field.len() > len || !field.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(format!("{:0>width$}", field, width = len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_128_bit() {
+        let header = "6309ab92c95468edf3cf4a9b7a2cb7e5:47dbd44cb3a08e37:0:1";
+        let context = decode(header).unwrap();
+        assert_eq!(context.trace_id.as_hex(), "6309ab92c95468edf3cf4a9b7a2cb7e5");
+        assert_eq!(context.span_id.as_hex(), "47dbd44cb3a08e37");
+        assert!(context.options.is_sampled());
+        assert!(context.is_remote());
+        assert_eq!(encode(&context), header);
+    }
+
+    #[test]
+    fn test_round_trip_64_bit() {
+        let header = "3ebc2e7d24f6de47:3ebc2e7d24f6de47:0:0";
+        let context = decode(header).unwrap();
+        assert_eq!(context.trace_id.as_hex(), "00000000000000003ebc2e7d24f6de47");
+        assert!(!context.options.is_sampled());
+        assert_eq!(encode(&context), header);
+    }
+
+    #[test]
+    fn test_decode_short_ids() {
+        let context = decode("abc:def:0:1").unwrap();
+        assert_eq!(context.trace_id.as_hex(), "00000000000000000000000000000abc");
+        assert_eq!(context.span_id.as_hex(), "0000000000000def");
+        assert_eq!(encode(&context), "0000000000000abc:0000000000000def:0:1");
+    }
+
+    #[test]
+    fn test_decode_flags() {
+        let decode_sampled = |flags| decode(&format!("abc:def:0:{}", flags)).unwrap().options.is_sampled();
+        assert!(!decode_sampled("0"));
+        assert!(decode_sampled("1"));
+        assert!(decode_sampled("2"));
+        assert!(decode_sampled("3"));
+        assert!(!decode_sampled("8"));
+        assert!(decode_sampled("0b"));
+    }
+
+    #[test]
+    fn test_decode_url_encoded() {
+        let context = decode("3ebc2e7d24f6de47%3A3ebc2e7d24f6de47%3a0%3A1").unwrap();
+        assert_eq!(context.span_id.as_hex(), "3ebc2e7d24f6de47");
+        assert!(context.options.is_sampled());
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert_eq!(decode(""), Err(JaegerError::Malformed));
+        assert_eq!(decode("abc:def:0"), Err(JaegerError::Malformed));
+        assert_eq!(decode("abc:def:0:1:2"), Err(JaegerError::Malformed));
+        assert_eq!(decode(":def:0:1"), Err(JaegerError::InvalidTraceId));
+        assert_eq!(decode("0:def:0:1"), Err(JaegerError::InvalidTraceId));
+        assert_eq!(decode(&format!("{}:def:0:1", "a".repeat(33))), Err(JaegerError::InvalidTraceId));
+        assert_eq!(decode("abc:xyz:0:1"), Err(JaegerError::InvalidSpanId));
+        assert_eq!(decode("abc:0:0:1"), Err(JaegerError::InvalidSpanId));
+        assert_eq!(decode("abc:def::1"), Err(JaegerError::InvalidParentSpanId));
+        assert_eq!(decode("abc:def:0:100"), Err(JaegerError::InvalidFlags));
+        assert_eq!(decode("abc:def:0:-1"), Err(JaegerError::InvalidFlags));
+    }
+}
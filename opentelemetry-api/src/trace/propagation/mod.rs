@@ -0,0 +1,10 @@
+pub mod binary_trace_context;
+pub use binary_trace_context::BinaryTraceContext;
+
+pub mod http_trace_context;
+pub use http_trace_context::HttpTraceContext;
+
+pub mod b3;
+pub use b3::B3Propagator;
+
+pub mod jaeger;
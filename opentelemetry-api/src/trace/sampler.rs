@@ -16,13 +16,17 @@
 
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
 
 use crate::trace::span_context::SpanContext;
 use crate::trace::span_id::SpanId;
 use crate::trace::trace_id::TraceId;
-use crate::trace::span::Span;
+use crate::trace::trace_state::TraceState;
 use crate::trace::attribute_value::AttributeValue;
+use crate::trace::link::Link;
 
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum ParentContext<'a> {
     /// The span has a remote parent
     RemoteParent(SpanContext<'a>),
@@ -32,6 +36,41 @@ pub enum ParentContext<'a> {
     RootSpan,
 }
 
+impl <'a> ParentContext<'a> {
+    /// Returns a `ParentContext::Parent` for a `SpanContext` created in this process.
+    pub fn from_local(context: SpanContext<'a>) -> Self {
+        ParentContext::Parent(context)
+    }
+
+    /// Returns a `ParentContext::RemoteParent` for a `SpanContext` propagated from another process.
+    pub fn from_remote(context: SpanContext<'a>) -> Self {
+        ParentContext::RemoteParent(context)
+    }
+
+    /// Returns a `ParentContext::RootSpan`.
+    pub fn root() -> Self {
+        ParentContext::RootSpan
+    }
+}
+
+/// Picks `RemoteParent` or `Parent` depending on `SpanContext::is_remote`.
+impl <'a> From<SpanContext<'a>> for ParentContext<'a> {
+    fn from(context: SpanContext<'a>) -> Self {
+        if context.is_remote() {
+            ParentContext::RemoteParent(context)
+        } else {
+            ParentContext::Parent(context)
+        }
+    }
+}
+
+/// Picks `RemoteParent` or `Parent` depending on `SpanContext::is_remote`.
+impl <'a> From<&SpanContext<'a>> for ParentContext<'a> {
+    fn from(context: &SpanContext<'a>) -> Self {
+        ParentContext::from(context.clone())
+    }
+}
+
 /// Sampling decision returned by `Sampler::should_sample`
 pub trait Decision {
     /// Return sampling decision whether span should be sampled or not.
@@ -39,6 +78,14 @@ pub trait Decision {
 
     /// Return tags which will be attached to the span.
     fn attributes(&self) -> HashMap<&str, &AttributeValue>;
+
+    /// Return the `TraceState` of the new span, if the sampler modified the parent one, e.g. to
+    /// record the sampling rate.
+    ///
+    /// `None` keeps the `TraceState` of the parent.
+    fn trace_state(&self) -> Option<&TraceState<'_>> {
+        None
+    }
 }
 
 /// Sampler is used to make decisions on {@link Span} sampling.
@@ -53,11 +100,11 @@ pub trait Sampler {
     ///   This will be identical to that in the parentContext, unless this is a root span.
     /// * spanId the `SpanId` for the new `Span.
     /// * name the name of the new `Span`.
-    /// * parentLinks the parentLinks associated with the new `Span.
-    fn should_sample<'a, N, S>(&self, parent_ctx: ParentContext, trace_id: TraceId, span_id: SpanId,
-                               name: N, parent_links: Vec<S>) -> Self::Decision
+    /// * parentLinks the links the new `Span` is created with.
+    fn should_sample<'a, N, L>(&self, parent_ctx: ParentContext, trace_id: TraceId, span_id: SpanId,
+                               name: N, parent_links: &[L]) -> Self::Decision
         where N: Into<Cow<'a, str>>,
-              S: Span;
+              L: Link;
 
     /// Returns the description of this `Sampler`.
     ///
@@ -67,3 +114,509 @@ pub trait Sampler {
     fn description(&self) -> &str;
 
 }
+
+/// `Decision` with a fixed sampling result and no attributes.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SimpleDecision {
+    sampled: bool,
+}
+
+impl SimpleDecision {
+    pub fn new(sampled: bool) -> Self {
+        SimpleDecision { sampled }
+    }
+}
+
+impl Decision for SimpleDecision {
+    fn is_sampled(&self) -> bool {
+        self.sampled
+    }
+
+    fn attributes(&self) -> HashMap<&str, &AttributeValue<'_>> {
+        HashMap::new()
+    }
+}
+
+/// `Decision` holding a sampling result, the attributes to add to the `Span` and optionally a new
+/// `TraceState`, for `Sampler`s which do not need their own `Decision` type.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct SamplingResult<'a> {
+    pub sampled: bool,
+    pub attributes: HashMap<String, AttributeValue<'a>>,
+    pub trace_state: Option<TraceState<'a>>,
+}
+
+impl <'a> SamplingResult<'a> {
+    /// Returns a `SamplingResult` which does not sample the `Span`.
+    pub fn drop() -> Self {
+        SamplingResult::default()
+    }
+
+    /// Returns a `SamplingResult` which records and samples the `Span`.
+    pub fn record_and_sample() -> Self {
+        SamplingResult { sampled: true, ..SamplingResult::default() }
+    }
+
+    /// Adds an attribute to attach to the `Span`.
+    pub fn with_attribute<K, V>(mut self, key: K, value: V) -> Self
+        where K: Into<String>,
+              V: Into<AttributeValue<'a>>
+    {
+        self.attributes.insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets the `TraceState` of the new `Span`.
+    pub fn with_trace_state(mut self, trace_state: TraceState<'a>) -> Self {
+        self.trace_state = Some(trace_state);
+        self
+    }
+}
+
+impl <'a> Decision for SamplingResult<'a> {
+    fn is_sampled(&self) -> bool {
+        self.sampled
+    }
+
+    fn attributes(&self) -> HashMap<&str, &AttributeValue<'_>> {
+        self.attributes.iter().map(|(key, value)| (key.as_str(), value)).collect()
+    }
+
+    fn trace_state(&self) -> Option<&TraceState<'_>> {
+        self.trace_state.as_ref()
+    }
+}
+
+/// `Sampler` that samples every `Span`.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct AlwaysOnSampler;
+
+impl Sampler for AlwaysOnSampler {
+    type Decision = SimpleDecision;
+
+    fn should_sample<'a, N, L>(&self, _parent_ctx: ParentContext, _trace_id: TraceId, _span_id: SpanId,
+                               _name: N, _parent_links: &[L]) -> SimpleDecision
+        where N: Into<Cow<'a, str>>,
+              L: Link
+    {
+        SimpleDecision::new(true)
+    }
+
+    fn description(&self) -> &str {
+        "AlwaysOnSampler"
+    }
+}
+
+/// `Sampler` that never samples any `Span`.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct AlwaysOffSampler;
+
+impl Sampler for AlwaysOffSampler {
+    type Decision = SimpleDecision;
+
+    fn should_sample<'a, N, L>(&self, _parent_ctx: ParentContext, _trace_id: TraceId, _span_id: SpanId,
+                               _name: N, _parent_links: &[L]) -> SimpleDecision
+        where N: Into<Cow<'a, str>>,
+              L: Link
+    {
+        SimpleDecision::new(false)
+    }
+
+    fn description(&self) -> &str {
+        "AlwaysOffSampler"
+    }
+}
+
+/// `Sampler` that samples a given fraction of traces.
+///
+/// The decision only depends on the lower 64 bits of the `TraceId`, so all the `Span`s of a trace
+/// are either sampled or not.
+#[derive(Clone, Debug)]
+pub struct ProbabilitySampler {
+    probability: f64,
+    id_upper_bound: u64,
+    description: String,
+}
+
+impl ProbabilitySampler {
+    /// Creates a `ProbabilitySampler`, the probability is clamped to `[0, 1]` and `NaN` samples
+    /// nothing.
+    // `f64::clamp` needs Rust 1.50
+    #[allow(clippy::manual_clamp)]
+    pub fn new(probability: f64) -> Self {
+        let probability = if probability.is_nan() { 0.0 } else { probability.max(0.0).min(1.0) };
+        let id_upper_bound = if probability >= 1.0 {
+            u64::MAX
+        } else {
+            (probability * u64::MAX as f64) as u64
+        };
+        ProbabilitySampler {
+            probability,
+            id_upper_bound,
+            description: format!("ProbabilitySampler{{{:.6}}}", probability),
+        }
+    }
+
+    /// Returns the probability of sampling a trace.
+    pub fn probability(&self) -> f64 {
+        self.probability
+    }
+}
+
+impl Sampler for ProbabilitySampler {
+    type Decision = SimpleDecision;
+
+    fn should_sample<'a, N, L>(&self, _parent_ctx: ParentContext, trace_id: TraceId, _span_id: SpanId,
+                               _name: N, _parent_links: &[L]) -> SimpleDecision
+        where N: Into<Cow<'a, str>>,
+              L: Link
+    {
+        if self.id_upper_bound == u64::MAX {
+            return SimpleDecision::new(true);
+        }
+        let mut lower = [0; 8];
+        lower.copy_from_slice(&trace_id.to_bytes()[8..]);
+        SimpleDecision::new(u64::from_be_bytes(lower) < self.id_upper_bound)
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// `Sampler` that samples at most a given number of `Span`s per second.
+///
+/// This is a token bucket holding up to `max_per_second` tokens, refilled continuously at
+/// `max_per_second` tokens per second, and starting full. A `Span` is sampled only if a token is
+/// available, so bursts of up to `max_per_second` `Span`s are sampled.
+#[derive(Debug)]
+pub struct RateLimitingSampler {
+    max_per_second: u32,
+    bucket: Mutex<TokenBucket>,
+    description: String,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimitingSampler {
+    pub fn new(max_per_second: u32) -> Self {
+        RateLimitingSampler::starting_at(max_per_second, Instant::now())
+    }
+
+    fn starting_at(max_per_second: u32, now: Instant) -> Self {
+        RateLimitingSampler {
+            max_per_second,
+            bucket: Mutex::new(TokenBucket {
+                tokens: f64::from(max_per_second),
+                last_refill: now,
+            }),
+            description: format!("RateLimitingSampler{{{}}}", max_per_second),
+        }
+    }
+
+    /// Returns the maximum number of `Span`s sampled per second.
+    pub fn max_per_second(&self) -> u32 {
+        self.max_per_second
+    }
+
+    /// Refills the bucket up to `now`, and takes a token if one is available.
+    fn try_acquire(&self, now: Instant) -> bool {
+        let max = f64::from(self.max_per_second);
+        let mut bucket = self.bucket.lock().unwrap_or_else(|err| err.into_inner());
+        if now > bucket.last_refill {
+            let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * max).min(max);
+            bucket.last_refill = now;
+        }
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Sampler for RateLimitingSampler {
+    type Decision = SimpleDecision;
+
+    fn should_sample<'a, N, L>(&self, _parent_ctx: ParentContext, _trace_id: TraceId, _span_id: SpanId,
+                               _name: N, _parent_links: &[L]) -> SimpleDecision
+        where N: Into<Cow<'a, str>>,
+              L: Link
+    {
+        SimpleDecision::new(self.try_acquire(Instant::now()))
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// `Sampler` that follows the sampling decision of the parent `Span`, and delegates to a root
+/// `Sampler` for `Span`s without parent.
+#[derive(Clone, Debug)]
+pub struct ParentBasedSampler<S> {
+    root: S,
+    description: String,
+}
+
+impl <S: Sampler> ParentBasedSampler<S> {
+    pub fn new(root: S) -> Self {
+        let description = format!("ParentBasedSampler{{root={}}}", root.description());
+        ParentBasedSampler { root, description }
+    }
+}
+
+/// `Decision` of a `ParentBasedSampler`.
+#[derive(Clone, Debug)]
+pub enum ParentBasedDecision<D> {
+    /// The sampled flag of the parent `SpanContext`.
+    Parent(SimpleDecision),
+    /// The decision of the root `Sampler`.
+    Root(D),
+}
+
+impl <D: Decision> Decision for ParentBasedDecision<D> {
+    fn is_sampled(&self) -> bool {
+        match self {
+            ParentBasedDecision::Parent(decision) => decision.is_sampled(),
+            ParentBasedDecision::Root(decision) => decision.is_sampled(),
+        }
+    }
+
+    fn attributes(&self) -> HashMap<&str, &AttributeValue<'_>> {
+        match self {
+            ParentBasedDecision::Parent(decision) => decision.attributes(),
+            ParentBasedDecision::Root(decision) => decision.attributes(),
+        }
+    }
+
+    fn trace_state(&self) -> Option<&TraceState<'_>> {
+        match self {
+            ParentBasedDecision::Parent(decision) => decision.trace_state(),
+            ParentBasedDecision::Root(decision) => decision.trace_state(),
+        }
+    }
+}
+
+impl <R: Sampler> Sampler for ParentBasedSampler<R> {
+    type Decision = ParentBasedDecision<R::Decision>;
+
+    fn should_sample<'a, N, L>(&self, parent_ctx: ParentContext, trace_id: TraceId, span_id: SpanId,
+                               name: N, parent_links: &[L]) -> Self::Decision
+        where N: Into<Cow<'a, str>>,
+              L: Link
+    {
+        match parent_ctx {
+            ParentContext::RemoteParent(ctx) | ParentContext::Parent(ctx) => {
+                ParentBasedDecision::Parent(SimpleDecision::new(ctx.options.is_sampled()))
+            },
+            ParentContext::RootSpan => ParentBasedDecision::Root(
+                self.root.should_sample(ParentContext::RootSpan, trace_id, span_id, name, parent_links)
+            ),
+        }
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+    use super::*;
+    use crate::trace::link::SimpleLink;
+    use crate::trace::trace_options::TraceOptions;
+    use crate::trace::trace_state::TraceState;
+
+    fn span_context(options: TraceOptions) -> SpanContext<'static> {
+        SpanContext::new(
+            TraceId::from_bytes([1; 16]),
+            SpanId::new(1),
+            options,
+            TraceState::default(),
+        )
+    }
+
+    fn parents() -> Vec<ParentContext<'static>> {
+        vec![
+            ParentContext::RootSpan,
+            ParentContext::Parent(span_context(TraceOptions::default())),
+            ParentContext::RemoteParent(span_context(TraceOptions::IS_SAMPLED)),
+        ]
+    }
+
+    fn sample<S: Sampler>(sampler: &S, parent: ParentContext, trace_id: TraceId) -> bool {
+        sampler.should_sample(parent, trace_id, SpanId::new(2), "span", &[] as &[SimpleLink])
+            .is_sampled()
+    }
+
+    #[test]
+    fn test_parent_context_from_span_context() {
+        let local = span_context(TraceOptions::default());
+        let remote = SpanContext::new_remote(local.trace_id, local.span_id, local.options,
+                                             TraceState::default());
+
+        assert_eq!(ParentContext::from(&local), ParentContext::Parent(local.clone()));
+        assert_eq!(ParentContext::from(&remote), ParentContext::RemoteParent(remote.clone()));
+        assert_eq!(ParentContext::from(remote.clone()), ParentContext::from_remote(remote));
+        assert_eq!(ParentContext::from(local.clone()), ParentContext::from_local(local));
+        assert_eq!(ParentContext::root(), ParentContext::RootSpan);
+    }
+
+    #[test]
+    fn test_sampling_result() {
+        assert!(!SamplingResult::drop().is_sampled());
+        assert!(SamplingResult::record_and_sample().is_sampled());
+        assert!(SamplingResult::drop().attributes().is_empty());
+        assert!(SamplingResult::record_and_sample().trace_state().is_none());
+
+        let state = TraceState::from_header("rojo=00f067aa0ba902b7").unwrap();
+        let result = SamplingResult::record_and_sample()
+            .with_attribute("sampler.rate", 0.5)
+            .with_trace_state(state.clone());
+        assert_eq!(result.attributes().get("sampler.rate"), Some(&&AttributeValue::Double(0.5)));
+        assert_eq!(result.trace_state(), Some(&state));
+    }
+
+    #[test]
+    fn test_always_on() {
+        for parent in parents() {
+            assert!(sample(&AlwaysOnSampler, parent, TraceId::from_bytes([2; 16])));
+        }
+        assert_eq!(AlwaysOnSampler.description(), "AlwaysOnSampler");
+    }
+
+    #[test]
+    fn test_always_off() {
+        for parent in parents() {
+            assert!(!sample(&AlwaysOffSampler, parent, TraceId::from_bytes([2; 16])));
+        }
+        assert_eq!(AlwaysOffSampler.description(), "AlwaysOffSampler");
+    }
+
+    fn trace_id(lower: u64) -> TraceId {
+        let mut bytes = [0xff; 16];
+        bytes[8..].copy_from_slice(&lower.to_be_bytes());
+        TraceId::from_bytes(bytes)
+    }
+
+    #[test]
+    fn test_probability_bounds() {
+        let always = ProbabilitySampler::new(1.0);
+        let never = ProbabilitySampler::new(0.0);
+        for &lower in &[0, 1, u64::MAX / 2, u64::MAX - 1, u64::MAX] {
+            assert!(sample(&always, ParentContext::RootSpan, trace_id(lower)));
+            assert!(!sample(&never, ParentContext::RootSpan, trace_id(lower)));
+        }
+    }
+
+    #[test]
+    fn test_probability_threshold() {
+        let sampler = ProbabilitySampler::new(0.5);
+        assert!(sample(&sampler, ParentContext::RootSpan, trace_id(u64::MAX / 4)));
+        assert!(!sample(&sampler, ParentContext::RootSpan, trace_id(u64::MAX / 4 * 3)));
+    }
+
+    #[test]
+    fn test_probability_stable_decision() {
+        let sampler = ProbabilitySampler::new(0.3);
+        for &lower in &[3, u64::MAX / 3, u64::MAX / 7 * 5] {
+            let decision = sample(&sampler, ParentContext::RootSpan, trace_id(lower));
+            for parent in parents() {
+                assert_eq!(sample(&sampler, parent, trace_id(lower)), decision);
+            }
+        }
+    }
+
+    #[test]
+    fn test_probability_clamped() {
+        assert_eq!(ProbabilitySampler::new(1.5).probability(), 1.0);
+        assert_eq!(ProbabilitySampler::new(-1.0).probability(), 0.0);
+        assert_eq!(ProbabilitySampler::new(f64::NAN).probability(), 0.0);
+        assert!(!sample(&ProbabilitySampler::new(f64::NAN), ParentContext::RootSpan, trace_id(0)));
+        assert_eq!(ProbabilitySampler::new(0.0001).description(), "ProbabilitySampler{0.000100}");
+    }
+
+    #[test]
+    fn test_rate_limiting_burst() {
+        let sampler = RateLimitingSampler::new(10);
+        let sampled = (0..100)
+            .filter(|_| sample(&sampler, ParentContext::RootSpan, TraceId::from_bytes([2; 16])))
+            .count();
+        assert_eq!(sampled, 10);
+        assert_eq!(sampler.max_per_second(), 10);
+        assert_eq!(sampler.description(), "RateLimitingSampler{10}");
+    }
+
+    #[test]
+    fn test_rate_limiting_refill() {
+        let start = Instant::now();
+        let sampler = RateLimitingSampler::starting_at(100, start);
+        assert_eq!((0..200).filter(|_| sampler.try_acquire(start)).count(), 100);
+
+        // 100 per second is one token every 10ms.
+        let later = start + Duration::from_millis(55);
+        assert_eq!((0..200).filter(|_| sampler.try_acquire(later)).count(), 5);
+
+        // The bucket never holds more than one second worth of tokens.
+        let much_later = later + Duration::from_secs(60);
+        assert_eq!((0..200).filter(|_| sampler.try_acquire(much_later)).count(), 100);
+    }
+
+    #[test]
+    fn test_rate_limiting_zero() {
+        let sampler = RateLimitingSampler::new(0);
+        assert!(!sample(&sampler, ParentContext::RootSpan, TraceId::from_bytes([2; 16])));
+        assert!(!sampler.try_acquire(Instant::now() + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_rate_limiting_threads() {
+        let start = Instant::now();
+        let sampler = Arc::new(RateLimitingSampler::starting_at(50, start));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let sampler = sampler.clone();
+                thread::spawn(move || (0..50).filter(|_| sampler.try_acquire(start)).count())
+            })
+            .collect();
+        let sampled: usize = handles.into_iter().map(|handle| handle.join().unwrap()).sum();
+        assert_eq!(sampled, 50);
+    }
+
+    #[test]
+    fn test_parent_based_sampled_parent() {
+        let sampler = ParentBasedSampler::new(AlwaysOffSampler);
+        let parent = ParentContext::RemoteParent(span_context(TraceOptions::IS_SAMPLED));
+        assert!(sample(&sampler, parent, TraceId::from_bytes([2; 16])));
+        let parent = ParentContext::Parent(span_context(TraceOptions::IS_SAMPLED));
+        assert!(sample(&sampler, parent, TraceId::from_bytes([2; 16])));
+    }
+
+    #[test]
+    fn test_parent_based_unsampled_parent() {
+        let sampler = ParentBasedSampler::new(AlwaysOnSampler);
+        let parent = ParentContext::Parent(span_context(TraceOptions::default()));
+        assert!(!sample(&sampler, parent, TraceId::from_bytes([2; 16])));
+        let parent = ParentContext::RemoteParent(span_context(TraceOptions::default()));
+        assert!(!sample(&sampler, parent, TraceId::from_bytes([2; 16])));
+    }
+
+    #[test]
+    fn test_parent_based_root_delegates() {
+        let on = ParentBasedSampler::new(AlwaysOnSampler);
+        let off = ParentBasedSampler::new(AlwaysOffSampler);
+        assert!(sample(&on, ParentContext::RootSpan, TraceId::from_bytes([2; 16])));
+        assert!(!sample(&off, ParentContext::RootSpan, TraceId::from_bytes([2; 16])));
+        assert_eq!(on.description(), "ParentBasedSampler{root=AlwaysOnSampler}");
+    }
+}
@@ -15,14 +15,20 @@
  */
 
 use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
 use crate::trace::attribute_value::AttributeValue;
 use crate::trace::span_context::SpanContext;
 use crate::trace::event::Event;
 use crate::trace::link::Link;
+use crate::trace::span_data::{SpanEvent, SpanLink};
 use crate::trace::status::Status;
 
 /// Type of span. Can be used to specify additional relationships between spans in addition to a
 /// parent/child relationship.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "SCREAMING_SNAKE_CASE"))]
 pub enum SpanKind {
     /// Default value. Indicates that the span is used internally.
     Internal,
@@ -45,6 +51,53 @@ pub enum SpanKind {
     Consumer
 }
 
+impl SpanKind {
+    /// Returns the canonical name of the kind, e.g. `"SERVER"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpanKind::Internal => "INTERNAL",
+            SpanKind::Server => "SERVER",
+            SpanKind::Client => "CLIENT",
+            SpanKind::Producer => "PRODUCER",
+            SpanKind::Consumer => "CONSUMER",
+        }
+    }
+}
+
+impl fmt::Display for SpanKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The string is not the canonical name of a `SpanKind`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseSpanKindError(pub String);
+
+impl fmt::Display for ParseSpanKindError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown span kind {:?}", self.0)
+    }
+}
+
+impl Error for ParseSpanKindError {}
+
+impl FromStr for SpanKind {
+    type Err = ParseSpanKindError;
+
+    /// Parses the canonical name returned by `as_str`, case sensitively.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "INTERNAL" => Ok(SpanKind::Internal),
+            "SERVER" => Ok(SpanKind::Server),
+            "CLIENT" => Ok(SpanKind::Client),
+            "PRODUCER" => Ok(SpanKind::Producer),
+            "CONSUMER" => Ok(SpanKind::Consumer),
+            _ => Err(ParseSpanKindError(s.to_string())),
+        }
+    }
+}
+
 /// An interface that represents a span.
 ///
 /// It has an associated `SpanContext`.
@@ -72,7 +125,7 @@ pub trait Span: Drop {
     ///
     /// Only the value of the last call will be recorded, and implementations are free to ignore
     /// previous calls.
-    fn set_status(status: Status);
+    fn set_status(&mut self, status: Status);
 
     /// Updates the `Span` name.
     ///
@@ -80,7 +133,7 @@ pub trait Span: Drop {
     ///
     /// Upon this update, any sampling behavior based on `Span` name will depend on the
     /// implementation.
-    fn update_name<'a, N: Into<Cow<'a, str>>>(name: N);
+    fn update_name<'a, N: Into<Cow<'a, str>>>(&mut self, name: N);
 
     /// Marks the end of `Span` execution.
     ///
@@ -94,6 +147,40 @@ pub trait Span: Drop {
     /// Returns `true` if this `Span` records events (e.g, `addEvent`.
     fn is_recording_events(&self) -> bool;
 
+    /// Returns the number of attributes currently recorded on this `Span`.
+    ///
+    /// Instrumentation can use this to stay within a shared attribute budget. `Span`s that do not
+    /// record attributes return `0`.
+    fn attribute_count(&self) -> usize {
+        0
+    }
+
+    /// Returns the number of attributes this `Span` discarded because of limits.
+    fn dropped_attribute_count(&self) -> usize {
+        0
+    }
+
+    /// Returns the number of links currently recorded on this `Span`.
+    ///
+    /// `Span`s that do not record links return `0`.
+    fn link_count(&self) -> usize {
+        0
+    }
+
+    /// Returns the events recorded on this `Span`, in the order they were added.
+    ///
+    /// `Span`s that do not record events return no event.
+    fn events(&self) -> &[SpanEvent] {
+        &[]
+    }
+
+    /// Returns the links recorded on this `Span`, in the order they were added.
+    ///
+    /// `Span`s that do not record links return no link.
+    fn links(&self) -> &[SpanLink] {
+        &[]
+    }
+
     /*
     /**
      * {@link Builder} is used to construct {@link Span} instances which define arbitrary scopes of
@@ -346,3 +433,95 @@ pub trait Span: Drop {
     */
 }
 
+/// A `Span` that records nothing and only carries its `SpanContext`.
+///
+/// Useful where a `Span` is required but tracing is disabled.
+pub struct DefaultSpan {
+    context: SpanContext<'static>,
+}
+
+impl DefaultSpan {
+    /// Creates a `DefaultSpan` that propagates the given `SpanContext`.
+    pub fn new(context: SpanContext<'static>) -> Self {
+        DefaultSpan { context }
+    }
+
+    /// Creates a `DefaultSpan` with an invalid `SpanContext`.
+    pub fn invalid() -> Self {
+        DefaultSpan::new(SpanContext::invalid())
+    }
+}
+
+impl Span for DefaultSpan {
+    fn set_attribute<'a, K, V>(&mut self, _key: K, _value: V)
+        where K: Into<Cow<'a, str>>,
+              V: Into<AttributeValue<'a>> {}
+
+    fn add_event<E: Event>(&mut self, _event: E) {}
+
+    fn add_link<L: Link>(&mut self, _link: L) {}
+
+    fn set_status(&mut self, _status: Status) {}
+
+    fn update_name<'a, N: Into<Cow<'a, str>>>(&mut self, _name: N) {}
+
+    fn end(&mut self) {}
+
+    fn context(&self) -> &SpanContext<'_> {
+        &self.context
+    }
+
+    fn is_recording_events(&self) -> bool {
+        false
+    }
+}
+
+impl Drop for DefaultSpan {
+    fn drop(&mut self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+    use crate::trace::status::CanonicalCode;
+
+    struct TestEvent;
+
+    impl Event for TestEvent {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        fn attributes(&self) -> HashMap<&str, &AttributeValue<'_>> {
+            HashMap::new()
+        }
+    }
+
+    #[test]
+    fn test_mutators() {
+        let mut span = DefaultSpan::invalid();
+        span.set_attribute("key", "value");
+        span.add_event(TestEvent);
+        span.set_status(Status { status_code: CanonicalCode::Cancelled, description: "".into() });
+        span.update_name("renamed");
+        span.end();
+        assert!(!span.is_recording_events());
+    }
+
+    #[test]
+    fn test_span_kind_round_trip() {
+        let kinds = [SpanKind::Internal, SpanKind::Server, SpanKind::Client, SpanKind::Producer,
+                     SpanKind::Consumer];
+        for &kind in &kinds {
+            assert_eq!(kind.as_str().parse::<SpanKind>(), Ok(kind));
+            assert_eq!(kind.to_string(), kind.as_str());
+        }
+    }
+
+    #[test]
+    fn test_span_kind_rejects_unknown() {
+        assert_eq!("server".parse::<SpanKind>(), Err(ParseSpanKindError("server".to_string())));
+        assert_eq!("".parse::<SpanKind>(), Err(ParseSpanKindError("".to_string())));
+    }
+}
@@ -0,0 +1,122 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::borrow::Cow;
+
+use crate::trace::attribute_value::AttributeValue;
+use crate::trace::link::SimpleLink;
+use crate::trace::sampler::ParentContext;
+use crate::trace::span::SpanKind;
+use crate::trace::span_context::SpanContext;
+use crate::trace::tracer::Tracer;
+
+/// Configures and starts a new `Span`, obtained from `Tracer::span_builder`.
+///
+/// ```
+/// use opentelemetry_api::trace::span::Span;
+/// use opentelemetry_api::trace::tracer::{NoopTracer, Tracer};
+///
+/// let tracer = NoopTracer;
+/// let mut span = tracer.span_builder("my span")
+///     .attribute("component", "example")
+///     .start_span();
+/// span.end();
+/// ```
+pub struct SpanBuilder<'a, T: ?Sized> {
+    pub tracer: &'a T,
+    pub name: Cow<'a, str>,
+    /// The parent of the `Span`, if `None` the current `Span` at start time is used.
+    pub parent: Option<ParentContext<'a>>,
+    pub kind: SpanKind,
+    pub attributes: Vec<(Cow<'a, str>, AttributeValue<'a>)>,
+    pub links: Vec<SimpleLink<'a>>,
+}
+
+impl <'a, T: ?Sized> SpanBuilder<'a, T> {
+    pub fn new<N: Into<Cow<'a, str>>>(tracer: &'a T, name: N) -> Self {
+        SpanBuilder {
+            tracer,
+            name: name.into(),
+            parent: None,
+            kind: SpanKind::Internal,
+            attributes: Vec::new(),
+            links: Vec::new(),
+        }
+    }
+
+    /// Sets the parent to use, either a local `Span` or a remote one.
+    ///
+    /// If not set, the current `Span` at `start_span` time is used as parent.
+    pub fn parent(mut self, parent: ParentContext<'a>) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    /// Makes the `Span` the root of a new trace, discarding any parent set previously.
+    pub fn no_parent(mut self) -> Self {
+        self.parent = Some(ParentContext::RootSpan);
+        self
+    }
+
+    /// Sets the `SpanKind`, default value is `SpanKind::Internal`.
+    pub fn kind(mut self, kind: SpanKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Adds an attribute that the `Span` starts with.
+    pub fn attribute<K, V>(mut self, key: K, value: V) -> Self
+        where K: Into<Cow<'a, str>>,
+              V: Into<AttributeValue<'a>>
+    {
+        self.attributes.push((key.into(), value.into()));
+        self
+    }
+
+    /// Adds a `Link` that the `Span` starts with.
+    pub fn add_link(mut self, link: SimpleLink<'a>) -> Self {
+        self.links.push(link);
+        self
+    }
+
+    /// Adds a `Link` to the given `SpanContext`, without attributes, that the `Span` starts with.
+    pub fn add_link_context(self, context: SpanContext<'a>) -> Self {
+        self.add_link(SimpleLink::new(context))
+    }
+
+    /// Moves the configuration over to another `Tracer`.
+    pub(crate) fn with_tracer<U: ?Sized>(self, tracer: &'a U) -> SpanBuilder<'a, U> {
+        SpanBuilder {
+            tracer,
+            name: self.name,
+            parent: self.parent,
+            kind: self.kind,
+            attributes: self.attributes,
+            links: self.links,
+        }
+    }
+}
+
+impl <'a, T: Tracer> SpanBuilder<'a, T> {
+    /// Starts a new `Span`.
+    ///
+    /// The `Span` *must* be ended, and it is not installed as the current `Span`, see
+    /// `Tracer::with_span`.
+    pub fn start_span(self) -> T::Span {
+        let tracer = self.tracer;
+        tracer.start_span(self)
+    }
+}
@@ -26,12 +26,122 @@ use crate::trace::trace_state::TraceState;
 ///
 /// It contains the identifiers a `TraceId` and `SpanId` associated with the `Span` and a set of
 /// `TraceOption`s.
+///
+/// A context is remote when it was extracted from a carrier, that is when the `Span` it
+/// identifies lives in another process.
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SpanContext<'a> {
     pub trace_id: TraceId,
     pub span_id: SpanId,
     pub options: TraceOptions,
     pub state: TraceState<'a>,
+    pub is_remote: bool,
+}
+
+impl <'a> SpanContext<'a> {
+    /// Creates a local `SpanContext`.
+    pub fn new(trace_id: TraceId, span_id: SpanId, options: TraceOptions,
+               state: TraceState<'a>) -> Self {
+        SpanContext { trace_id, span_id, options, state, is_remote: false }
+    }
+
+    /// Creates a `SpanContext` propagated from a remote process.
+    pub fn new_remote(trace_id: TraceId, span_id: SpanId, options: TraceOptions,
+                      state: TraceState<'a>) -> Self {
+        SpanContext { trace_id, span_id, options, state, is_remote: true }
+    }
+
+    /// Returns the invalid `SpanContext`, which can be used for no-op operations.
+    pub fn invalid() -> Self {
+        SpanContext::new(TraceId::get_invalid(), SpanId::invalid(), TraceOptions::default(),
+                         TraceState::default())
+    }
+
+    /// Returns `true` if both the `TraceId` and the `SpanId` are valid.
+    pub fn is_valid(&self) -> bool {
+        self.trace_id.is_valid() && self.span_id.is_valid()
+    }
+
+    /// Returns `true` if this `SpanContext` was propagated from a remote process.
+    pub fn is_remote(&self) -> bool {
+        self.is_remote
+    }
+
+    /// Converts this `SpanContext` into one that owns its `TraceState`.
+    pub fn into_owned(self) -> SpanContext<'static> {
+        SpanContext {
+            trace_id: self.trace_id,
+            span_id: self.span_id,
+            options: self.options,
+            state: self.state.into_owned(),
+            is_remote: self.is_remote,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_remote() {
+        let trace_id = TraceId::from_bytes([1; 16]);
+        let span_id = SpanId::new(1);
+        let local = SpanContext::new(trace_id, span_id, TraceOptions::default(),
+                                     TraceState::default());
+        assert!(!local.is_remote());
+        assert!(!local.into_owned().is_remote());
+
+        let remote = SpanContext::new_remote(trace_id, span_id, TraceOptions::default(),
+                                             TraceState::default());
+        assert!(remote.is_remote());
+        assert!(remote.into_owned().is_remote());
+    }
+
+    #[test]
+    fn test_is_valid() {
+        assert!(!SpanContext::invalid().is_valid());
+        assert!(!SpanContext::invalid().is_remote());
+
+        let options = TraceOptions::default();
+        let valid = SpanContext::new(TraceId::from_bytes([1; 16]), SpanId::new(1), options,
+                                     TraceState::default());
+        assert!(valid.is_valid());
+        assert!(!SpanContext { trace_id: TraceId::get_invalid(), ..valid.clone() }.is_valid());
+        assert!(!SpanContext { span_id: SpanId::invalid(), ..valid }.is_valid());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let state = TraceState::from_header("rojo=00f067aa0ba902b7").unwrap();
+        let context = SpanContext::new_remote(TraceId::from_bytes([1; 16]), SpanId::new(1),
+                                              TraceOptions::IS_SAMPLED, state);
+        let json = serde_json::to_value(&context).unwrap();
+        assert_eq!(json, serde_json::json!({
+            "trace_id": "01010101010101010101010101010101",
+            "span_id": "0000000000000001",
+            "options": 1,
+            "state": "rojo=00f067aa0ba902b7",
+            "is_remote": true,
+        }));
+        assert_eq!(serde_json::from_value::<SpanContext<'_>>(json).unwrap(), context);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize_invalid_hex() {
+        let json = serde_json::json!({
+            "trace_id": "0101010101010101010101010101010g",
+            "span_id": "0000000000000001",
+            "options": 0,
+            "state": "",
+            "is_remote": false,
+        });
+        let err = serde_json::from_value::<SpanContext<'_>>(json).unwrap_err();
+        assert!(err.to_string().contains("invalid trace id"), "{}", err);
+    }
 }
 
 /*
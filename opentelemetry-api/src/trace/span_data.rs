@@ -0,0 +1,206 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use crate::trace::attribute_value::AttributeValue;
+use crate::trace::attributes::Attributes;
+use crate::trace::event::Event;
+use crate::trace::link::Link;
+use crate::trace::span::SpanKind;
+use crate::trace::span_context::SpanContext;
+use crate::trace::status::Status;
+
+/// An owned snapshot of a `Span`, as handed over to exporters.
+///
+/// Finished `RecordingSpan`s of the SDK produce one when they end, it can also be built by hand
+/// to record spans coming from another tracing system.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SpanData<'a> {
+    pub context: SpanContext<'a>,
+    pub name: Cow<'a, str>,
+    pub kind: SpanKind,
+    pub start_time: SystemTime,
+    pub end_time: SystemTime,
+    /// Attributes in the order they were first set.
+    pub attributes: Attributes<'a>,
+    /// Number of attributes that were dropped because of attribute limits.
+    pub dropped_attribute_count: usize,
+    pub events: Vec<SpanEvent>,
+    /// Number of events that were dropped because of span limits.
+    pub dropped_event_count: usize,
+    pub links: Vec<SpanLink>,
+    /// Number of links that were dropped because of span limits.
+    pub dropped_link_count: usize,
+    pub status: Status<'a>,
+}
+
+impl <'a> SpanData<'a> {
+    /// Creates an `Internal` `SpanData` without attributes, events or links, and with an `Ok`
+    /// status.
+    ///
+    /// Both its start and end time are set to now.
+    pub fn new<N: Into<Cow<'a, str>>>(name: N, context: SpanContext<'a>) -> Self {
+        let now = SystemTime::now();
+        SpanData {
+            context,
+            name: name.into(),
+            kind: SpanKind::Internal,
+            start_time: now,
+            end_time: now,
+            attributes: Attributes::new(),
+            dropped_attribute_count: 0,
+            events: Vec::new(),
+            dropped_event_count: 0,
+            links: Vec::new(),
+            dropped_link_count: 0,
+            status: Status::ok(),
+        }
+    }
+
+    /// Returns the time elapsed between the start and the end of the span, zero if the end time
+    /// precedes the start time.
+    pub fn duration(&self) -> Duration {
+        self.end_time.duration_since(self.start_time).unwrap_or_default()
+    }
+}
+
+/// An `Event` recorded on a `Span`.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SpanEvent {
+    pub name: String,
+    pub attributes: HashMap<String, AttributeValue<'static>>,
+    /// When the event was added to the span.
+    pub timestamp: SystemTime,
+}
+
+impl SpanEvent {
+    /// Copies the name and attributes of the given `Event`, added to the span at `timestamp`.
+    pub fn from_event<E: Event>(event: &E, timestamp: SystemTime) -> Self {
+        SpanEvent {
+            name: event.name().to_string(),
+            attributes: owned_attributes(event.attributes()),
+            timestamp,
+        }
+    }
+}
+
+/// A `Link` recorded on a `Span`.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SpanLink {
+    pub context: SpanContext<'static>,
+    pub attributes: HashMap<String, AttributeValue<'static>>,
+}
+
+impl SpanLink {
+    /// Copies the `SpanContext` and attributes of the given `Link`.
+    pub fn from_link<L: Link>(link: &L) -> Self {
+        SpanLink {
+            context: link.context().into_owned(),
+            attributes: owned_attributes(link.attributes()),
+        }
+    }
+}
+
+fn owned_attributes(attributes: HashMap<&str, &AttributeValue>) -> HashMap<String, AttributeValue<'static>> {
+    attributes.into_iter()
+        .map(|(k, v)| (k.to_string(), v.clone().into_owned()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::span_id::SpanId;
+    use crate::trace::trace_id::TraceId;
+    use crate::trace::trace_options::TraceOptions;
+    use crate::trace::trace_state::TraceState;
+
+    fn context(span_id: u64) -> SpanContext<'static> {
+        SpanContext::new(TraceId::from_bytes([1; 16]), SpanId::new(span_id), TraceOptions::IS_SAMPLED,
+                         TraceState::default())
+    }
+
+    fn span_data() -> SpanData<'static> {
+        let start_time = SystemTime::UNIX_EPOCH + Duration::from_secs(10);
+        let mut attributes = Attributes::new();
+        attributes.insert("http.method".to_string(), AttributeValue::from("GET"));
+
+        SpanData {
+            context: context(1),
+            name: "request".into(),
+            kind: SpanKind::Server,
+            start_time,
+            end_time: start_time + Duration::from_millis(5),
+            attributes,
+            dropped_attribute_count: 1,
+            events: vec![SpanEvent {
+                name: "retry".to_string(),
+                attributes: HashMap::new(),
+                timestamp: start_time + Duration::from_millis(1),
+            }],
+            dropped_event_count: 0,
+            links: vec![SpanLink { context: context(2), attributes: HashMap::new() }],
+            dropped_link_count: 0,
+            status: Status::not_found(),
+        }
+    }
+
+    #[test]
+    fn test_fields() {
+        let data = span_data();
+        assert_eq!(data.context, context(1));
+        assert_eq!(data.name, "request");
+        assert_eq!(data.kind, SpanKind::Server);
+        assert_eq!(data.duration(), Duration::from_millis(5));
+        assert_eq!(data.attributes["http.method"], AttributeValue::String("GET".into()));
+        assert_eq!(data.dropped_attribute_count, 1);
+        assert_eq!(data.events[0].name, "retry");
+        assert_eq!(data.events[0].timestamp, data.start_time + Duration::from_millis(1));
+        assert_eq!(data.links[0].context, context(2));
+        assert_eq!(data.status, Status::not_found());
+        assert_eq!(data.clone(), data);
+    }
+
+    #[test]
+    fn test_new() {
+        let data = SpanData::new("request", context(1));
+        assert_eq!(data.kind, SpanKind::Internal);
+        assert_eq!(data.start_time, data.end_time);
+        assert_eq!(data.duration(), Duration::from_secs(0));
+        assert!(data.attributes.is_empty() && data.events.is_empty() && data.links.is_empty());
+        assert_eq!(data.dropped_event_count + data.dropped_link_count, 0);
+        assert!(data.status.is_ok());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize() {
+        let json = serde_json::to_value(span_data()).unwrap();
+        assert_eq!(json["context"]["trace_id"], "01010101010101010101010101010101");
+        assert_eq!(json["context"]["span_id"], "0000000000000001");
+        assert_eq!(json["context"]["options"], 1);
+        assert_eq!(json["kind"], "SERVER");
+        assert_eq!(json["attributes"]["http.method"]["stringValue"], "GET");
+        assert_eq!(json["links"][0]["context"]["span_id"], "0000000000000002");
+        assert_eq!(json["status"]["status_code"], "NotFound");
+    }
+}
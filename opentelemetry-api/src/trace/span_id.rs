@@ -14,7 +14,10 @@
  * limitations under the License.
  */
 
+use std::error::Error;
+use std::fmt;
 use std::mem;
+use std::str::FromStr;
 use rand::Rng;
 
 /// Represents a span identifier.
@@ -49,7 +52,7 @@ impl SpanId {
 
     /// Generates a new random `SpanId`.
     pub fn generate_random_id(rng: &mut impl Rng) -> SpanId {
-        SpanId(rng.gen_range(1u64, std::u64::MAX))
+        SpanId(rng.gen_range(1u64, u64::MAX))
     }
 
     /// Returns a `SpanId` whose representation is copied from `src`
@@ -61,14 +64,117 @@ impl SpanId {
         self.0.to_be_bytes()
     }
 
-    /// Returns whether the `TraceId` is valid.
-    /// A valid trace identifier is a 16-byte array with at least one non-zero byte.
+    /// Returns whether the `SpanId` is valid.
+    /// A valid span identifier is an 8-byte array with at least one non-zero byte.
     pub fn is_valid(&self) -> bool {
-        *self == INVALID
+        *self != INVALID
     }
 
-    /// Returns the lowercase base16 encoding of this {@code TraceId}.
+    /// Returns the lowercase base16 encoding of this `SpanId`, padded to 16 characters.
     pub fn as_hex(&self) -> String {
-        format!("{:x}", self.0)
+        format!("{:016x}", self.0)
+    }
+
+    /// Parses a `SpanId` from exactly 16 base16 characters.
+    pub fn from_hex(hex: &str) -> Option<SpanId> {
+        if hex.len() != 2 * Self::get_size() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        u64::from_str_radix(hex, 16).ok().map(SpanId)
+    }
+}
+
+/// Formats the `SpanId` as `as_hex` does.
+impl fmt::Display for SpanId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.as_hex())
+    }
+}
+
+/// The string is not 16 base16 characters.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseSpanIdError(pub String);
+
+impl fmt::Display for ParseSpanIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid span id {:?}", self.0)
+    }
+}
+
+impl Error for ParseSpanIdError {}
+
+impl FromStr for SpanId {
+    type Err = ParseSpanIdError;
+
+    /// Parses the `SpanId` as `from_hex` does.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SpanId::from_hex(s).ok_or_else(|| ParseSpanIdError(s.to_string()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SpanId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.as_hex())
+    }
+}
+
+/// Deserializes the padded base16 form written by `Serialize`.
+#[cfg(feature = "serde")]
+impl <'de> serde::Deserialize<'de> for SpanId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = <String as serde::Deserialize>::deserialize(deserializer)?;
+        hex.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_round_trip() {
+        let id = SpanId::new(0xff);
+        assert_eq!(id.as_hex(), "00000000000000ff");
+        assert_eq!(SpanId::from_hex(&id.as_hex()), Some(id));
+    }
+
+    #[test]
+    fn test_from_hex_invalid() {
+        assert_eq!(SpanId::from_hex("ff"), None);
+        assert_eq!(SpanId::from_hex("-00000000000000f"), None);
+    }
+
+    #[test]
+    fn test_display_from_str_round_trip() {
+        let id = SpanId::new(0x00f067aa0ba902b7);
+        assert_eq!(id.to_string(), id.as_hex());
+        assert_eq!(id.to_string().parse::<SpanId>().unwrap(), id);
+        assert_eq!(format!("{}", SpanId::from_hex("0000000000000001").unwrap()), "0000000000000001");
+    }
+
+    #[test]
+    fn test_from_str_invalid() {
+        assert_eq!("xyz".parse::<SpanId>(), Err(ParseSpanIdError("xyz".to_string())));
+        assert_eq!(ParseSpanIdError("xyz".to_string()).to_string(), "invalid span id \"xyz\"");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let id = SpanId::new(0x00f067aa0ba902b7);
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, r#""00f067aa0ba902b7""#);
+        assert_eq!(serde_json::from_str::<SpanId>(&json).unwrap(), id);
+
+        let err = serde_json::from_str::<SpanId>(r#""xyz""#).unwrap_err();
+        assert!(err.to_string().contains("invalid span id"), "{}", err);
+        assert!(serde_json::from_str::<SpanId>("1").is_err());
+    }
+
+    #[test]
+    fn test_is_valid() {
+        assert!(!SpanId::invalid().is_valid());
+        assert!(SpanId::new(1).is_valid());
     }
 }
@@ -15,12 +15,16 @@
  */
 
 use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
 
 /// The set of canonical status codes.
 ///
 /// If new codes are added over time they must choose a numerical value that does not collide with
 /// any previously used value.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum CanonicalCode {
     /// The operation completed successfully.
     Ok = 0,
@@ -112,18 +116,167 @@ pub enum CanonicalCode {
     Unauthenticated = 16,
 }
 
+impl CanonicalCode {
+    /// Maps an HTTP status code onto the closest `CanonicalCode`.
+    ///
+    /// Any status in `200..=399` is `Ok`, the well known client and server errors map onto their
+    /// canonical equivalents, and everything else is `Unknown`.
+    pub fn from_http_status(code: u16) -> CanonicalCode {
+        match code {
+            200..=399 => CanonicalCode::Ok,
+            400 => CanonicalCode::InvalidArgument,
+            401 => CanonicalCode::Unauthenticated,
+            403 => CanonicalCode::PermissionDenied,
+            404 => CanonicalCode::NotFound,
+            429 => CanonicalCode::ResourceExhausted,
+            501 => CanonicalCode::Unimplemented,
+            503 => CanonicalCode::Unavailable,
+            504 => CanonicalCode::DeadlineExceeded,
+            _ => CanonicalCode::Unknown,
+        }
+    }
+
+    /// Returns `true` for every code but `Ok`.
+    pub fn is_error(&self) -> bool {
+        *self != CanonicalCode::Ok
+    }
+
+    /// Returns `true` for the codes of transient failures, which may succeed if retried:
+    /// `Unavailable`, `Aborted`, `ResourceExhausted` and `DeadlineExceeded`.
+    ///
+    /// Following the litmus test of `FailedPrecondition`, `Unavailable` may be retried as is,
+    /// while `Aborted` should be retried at a higher level, e.g. by restarting a transaction.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, CanonicalCode::Unavailable
+                     | CanonicalCode::Aborted
+                     | CanonicalCode::ResourceExhausted
+                     | CanonicalCode::DeadlineExceeded)
+    }
+
+    /// Returns the canonical name of this code, e.g. `"NOT_FOUND"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CanonicalCode::Ok => "OK",
+            CanonicalCode::Cancelled => "CANCELLED",
+            CanonicalCode::Unknown => "UNKNOWN",
+            CanonicalCode::InvalidArgument => "INVALID_ARGUMENT",
+            CanonicalCode::DeadlineExceeded => "DEADLINE_EXCEEDED",
+            CanonicalCode::NotFound => "NOT_FOUND",
+            CanonicalCode::AlreadyExists => "ALREADY_EXISTS",
+            CanonicalCode::PermissionDenied => "PERMISSION_DENIED",
+            CanonicalCode::ResourceExhausted => "RESOURCE_EXHAUSTED",
+            CanonicalCode::FailedPrecondition => "FAILED_PRECONDITION",
+            CanonicalCode::Aborted => "ABORTED",
+            CanonicalCode::OutOfRange => "OUT_OF_RANGE",
+            CanonicalCode::Unimplemented => "UNIMPLEMENTED",
+            CanonicalCode::Internal => "INTERNAL",
+            CanonicalCode::Unavailable => "UNAVAILABLE",
+            CanonicalCode::DataLoss => "DATA_LOSS",
+            CanonicalCode::Unauthenticated => "UNAUTHENTICATED",
+        }
+    }
+}
+
+/// Error returned when converting a numeric value that is not a known `CanonicalCode`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct UnknownCanonicalCode(pub i32);
+
+impl fmt::Display for UnknownCanonicalCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} is not a known canonical code", self.0)
+    }
+}
+
+impl Error for UnknownCanonicalCode {}
+
+impl TryFrom<i32> for CanonicalCode {
+    type Error = UnknownCanonicalCode;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(CanonicalCode::Ok),
+            1 => Ok(CanonicalCode::Cancelled),
+            2 => Ok(CanonicalCode::Unknown),
+            3 => Ok(CanonicalCode::InvalidArgument),
+            4 => Ok(CanonicalCode::DeadlineExceeded),
+            5 => Ok(CanonicalCode::NotFound),
+            6 => Ok(CanonicalCode::AlreadyExists),
+            7 => Ok(CanonicalCode::PermissionDenied),
+            8 => Ok(CanonicalCode::ResourceExhausted),
+            9 => Ok(CanonicalCode::FailedPrecondition),
+            10 => Ok(CanonicalCode::Aborted),
+            11 => Ok(CanonicalCode::OutOfRange),
+            12 => Ok(CanonicalCode::Unimplemented),
+            13 => Ok(CanonicalCode::Internal),
+            14 => Ok(CanonicalCode::Unavailable),
+            15 => Ok(CanonicalCode::DataLoss),
+            16 => Ok(CanonicalCode::Unauthenticated),
+            _ => Err(UnknownCanonicalCode(value)),
+        }
+    }
+}
+
 /// Defines the status of a `Span` by providing a standard `CanonicalCode` in conjunction
 /// with an optional descriptive message. Instances of `Status` are created by starting with
-/// the template for the appropriate `Status.CanonicalCode` and supplementing it with
-/// additional information: `Status.NotFound.withDescription("Could not find
+/// the template for the appropriate `CanonicalCode` and supplementing it with
+/// additional information: `Status::not_found().with_description("Could not find
 /// 'important_file.txt'");`
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Status<'a> {
     pub status_code: CanonicalCode,
     pub description: Cow<'a, str>,
 }
 
+macro_rules! status_constructors {
+    ($($name:ident => $code:ident),*) => (
+        $(
+            /// Returns a `Status` with the matching `CanonicalCode` and an empty description.
+            pub fn $name() -> Self {
+                Status::new(CanonicalCode::$code)
+            }
+        )*
+    );
+}
+
 impl <'a> Status<'a> {
+    /// Creates a `Status` with the given `CanonicalCode` and an empty description.
+    pub fn new(status_code: CanonicalCode) -> Self {
+        Status {
+            status_code,
+            description: "".into(),
+        }
+    }
+
+    status_constructors!(
+        ok => Ok,
+        cancelled => Cancelled,
+        unknown => Unknown,
+        invalid_argument => InvalidArgument,
+        deadline_exceeded => DeadlineExceeded,
+        not_found => NotFound,
+        already_exists => AlreadyExists,
+        permission_denied => PermissionDenied,
+        resource_exhausted => ResourceExhausted,
+        failed_precondition => FailedPrecondition,
+        aborted => Aborted,
+        out_of_range => OutOfRange,
+        unimplemented => Unimplemented,
+        internal => Internal,
+        unavailable => Unavailable,
+        data_loss => DataLoss,
+        unauthenticated => Unauthenticated
+    );
+
+    /// Returns `Status::ok()` for an `Ok`, and for an `Err` an `UNKNOWN` `Status` described by the
+    /// `Display` of the error.
+    pub fn from_result<T, E: fmt::Display>(result: &Result<T, E>) -> Self {
+        match result {
+            Ok(_) => Status::ok(),
+            Err(err) => Status::unknown().with_description(err.to_string()),
+        }
+    }
+
     /// Returns `true` if this `Status` is `OK`, i.e., not an error.
     pub fn is_ok(&self) -> bool {
         self.status_code == CanonicalCode::Ok
@@ -136,5 +289,130 @@ impl <'a> Status<'a> {
             description: description.into(),
         }
     }
+
+    /// Converts this `Status` into one that owns its description.
+    pub fn into_owned(self) -> Status<'static> {
+        Status {
+            status_code: self.status_code,
+            description: Cow::Owned(self.description.into_owned()),
+        }
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_http_status() {
+        let cases = [
+            (100, CanonicalCode::Unknown),
+            (199, CanonicalCode::Unknown),
+            (200, CanonicalCode::Ok),
+            (304, CanonicalCode::Ok),
+            (399, CanonicalCode::Ok),
+            (400, CanonicalCode::InvalidArgument),
+            (401, CanonicalCode::Unauthenticated),
+            (402, CanonicalCode::Unknown),
+            (403, CanonicalCode::PermissionDenied),
+            (404, CanonicalCode::NotFound),
+            (429, CanonicalCode::ResourceExhausted),
+            (500, CanonicalCode::Unknown),
+            (501, CanonicalCode::Unimplemented),
+            (502, CanonicalCode::Unknown),
+            (503, CanonicalCode::Unavailable),
+            (504, CanonicalCode::DeadlineExceeded),
+            (599, CanonicalCode::Unknown),
+        ];
+
+        for (http, expected) in cases.iter() {
+            assert_eq!(CanonicalCode::from_http_status(*http), *expected, "HTTP status {}", http);
+        }
+    }
+
+    #[test]
+    fn test_is_error() {
+        assert!(!CanonicalCode::Ok.is_error());
+        for code in ALL_CODES.iter().filter(|&&code| code != CanonicalCode::Ok) {
+            assert!(code.is_error(), "{:?}", code);
+        }
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        let retryable = [
+            CanonicalCode::Unavailable,
+            CanonicalCode::Aborted,
+            CanonicalCode::ResourceExhausted,
+            CanonicalCode::DeadlineExceeded,
+        ];
+        for code in ALL_CODES.iter() {
+            assert_eq!(code.is_retryable(), retryable.contains(code), "{:?}", code);
+        }
+        assert!(!CanonicalCode::Ok.is_retryable());
+        assert!(!CanonicalCode::FailedPrecondition.is_retryable());
+        assert!(!CanonicalCode::Internal.is_retryable());
+    }
+
+    #[test]
+    fn test_from_result() {
+        let ok: Result<u32, UnknownCanonicalCode> = Ok(1);
+        assert_eq!(Status::from_result(&ok), Status::ok());
+        assert!(Status::from_result(&ok).description.is_empty());
+
+        let err = CanonicalCode::try_from(42);
+        let status = Status::from_result(&err);
+        assert_eq!(status.status_code, CanonicalCode::Unknown);
+        assert_eq!(status.description, err.unwrap_err().to_string());
+        assert!(!status.is_ok());
+    }
+
+    const ALL_CODES: [CanonicalCode; 17] = [
+        CanonicalCode::Ok,
+        CanonicalCode::Cancelled,
+        CanonicalCode::Unknown,
+        CanonicalCode::InvalidArgument,
+        CanonicalCode::DeadlineExceeded,
+        CanonicalCode::NotFound,
+        CanonicalCode::AlreadyExists,
+        CanonicalCode::PermissionDenied,
+        CanonicalCode::ResourceExhausted,
+        CanonicalCode::FailedPrecondition,
+        CanonicalCode::Aborted,
+        CanonicalCode::OutOfRange,
+        CanonicalCode::Unimplemented,
+        CanonicalCode::Internal,
+        CanonicalCode::Unavailable,
+        CanonicalCode::DataLoss,
+        CanonicalCode::Unauthenticated,
+    ];
+
+    #[test]
+    fn test_try_from_round_trip() {
+        for code in ALL_CODES.iter() {
+            assert_eq!(CanonicalCode::try_from(*code as i32), Ok(*code));
+        }
+        assert_eq!(CanonicalCode::try_from(-1), Err(UnknownCanonicalCode(-1)));
+        assert_eq!(CanonicalCode::try_from(17), Err(UnknownCanonicalCode(17)));
+    }
+
+    #[test]
+    fn test_as_str() {
+        assert_eq!(CanonicalCode::Ok.as_str(), "OK");
+        assert_eq!(CanonicalCode::NotFound.as_str(), "NOT_FOUND");
+        assert_eq!(CanonicalCode::Unauthenticated.as_str(), "UNAUTHENTICATED");
+    }
+
+    #[test]
+    fn test_status_constructors() {
+        let status = Status::not_found().with_description("x");
+        assert_eq!(status.status_code, CanonicalCode::NotFound);
+        assert_eq!(status.description, "x");
+
+        assert!(Status::ok().is_ok());
+        assert_eq!(Status::ok().description, "");
+        assert_eq!(Status::cancelled().status_code, CanonicalCode::Cancelled);
+        assert_eq!(Status::unauthenticated().status_code, CanonicalCode::Unauthenticated);
+        assert!(!Status::internal().is_ok());
+    }
+}
@@ -14,7 +14,10 @@
  * limitations under the License.
  */
 
+use std::error::Error;
+use std::fmt;
 use std::mem;
+use std::str::FromStr;
 use rand::Rng;
 
 const INVALID: TraceId = TraceId(0);
@@ -44,7 +47,7 @@ impl TraceId {
 
     /// Generates a new random `TraceId`.
     pub fn generate_random_id(rng: &mut impl Rng) -> TraceId {
-        TraceId(rng.gen_range(1u128, std::u128::MAX))
+        TraceId(rng.gen_range(1u128, u128::MAX))
     }
 
     /// Returns a `TraceId` whose representation is copied from `src`
@@ -59,11 +62,115 @@ impl TraceId {
     /// Returns whether the `TraceId` is valid.
     /// A valid trace identifier is a 16-byte array with at least one non-zero byte.
     pub fn is_valid(&self) -> bool {
-        *self == INVALID
+        *self != INVALID
     }
 
-    /// Returns the lowercase base16 encoding of this {@code TraceId}.
+    /// Returns the lowercase base16 encoding of this `TraceId`, padded to 32 characters.
     pub fn as_hex(&self) -> String {
-        format!("{:x}", self.0)
+        format!("{:032x}", self.0)
+    }
+
+    /// Parses a `TraceId` from exactly 32 base16 characters.
+    pub fn from_hex(hex: &str) -> Option<TraceId> {
+        if hex.len() != 2 * Self::get_size() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        u128::from_str_radix(hex, 16).ok().map(TraceId)
+    }
+}
+
+/// Formats the `TraceId` as `as_hex` does.
+impl fmt::Display for TraceId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.as_hex())
+    }
+}
+
+/// The string is not 32 base16 characters.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseTraceIdError(pub String);
+
+impl fmt::Display for ParseTraceIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid trace id {:?}", self.0)
+    }
+}
+
+impl Error for ParseTraceIdError {}
+
+impl FromStr for TraceId {
+    type Err = ParseTraceIdError;
+
+    /// Parses the `TraceId` as `from_hex` does.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TraceId::from_hex(s).ok_or_else(|| ParseTraceIdError(s.to_string()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for TraceId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.as_hex())
+    }
+}
+
+/// Deserializes the padded base16 form written by `Serialize`.
+#[cfg(feature = "serde")]
+impl <'de> serde::Deserialize<'de> for TraceId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = <String as serde::Deserialize>::deserialize(deserializer)?;
+        hex.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_round_trip() {
+        let id = TraceId::from_bytes([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(id.as_hex(), "00000000000000000000000000000001");
+        assert_eq!(TraceId::from_hex(&id.as_hex()), Some(id));
+    }
+
+    #[test]
+    fn test_from_hex_invalid() {
+        assert_eq!(TraceId::from_hex("1"), None);
+        assert_eq!(TraceId::from_hex("+0000000000000000000000000000001"), None);
+        assert_eq!(TraceId::from_hex("0000000000000000000000000000000g"), None);
+    }
+
+    #[test]
+    fn test_display_from_str_round_trip() {
+        let id = TraceId::from_bytes([0x4b; 16]);
+        assert_eq!(id.to_string(), id.as_hex());
+        assert_eq!(id.to_string().parse::<TraceId>().unwrap(), id);
+        assert_eq!(format!("{}", TraceId::from_hex("00000000000000000000000000000001").unwrap()), "00000000000000000000000000000001");
+    }
+
+    #[test]
+    fn test_from_str_invalid() {
+        assert_eq!("xyz".parse::<TraceId>(), Err(ParseTraceIdError("xyz".to_string())));
+        assert_eq!(ParseTraceIdError("xyz".to_string()).to_string(), "invalid trace id \"xyz\"");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let id = TraceId::from_bytes([0x4b; 16]);
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, r#""4b4b4b4b4b4b4b4b4b4b4b4b4b4b4b4b""#);
+        assert_eq!(serde_json::from_str::<TraceId>(&json).unwrap(), id);
+
+        let err = serde_json::from_str::<TraceId>(r#""xyz""#).unwrap_err();
+        assert!(err.to_string().contains("invalid trace id"), "{}", err);
+        assert!(serde_json::from_str::<TraceId>("1").is_err());
+    }
+
+    #[test]
+    fn test_is_valid() {
+        assert!(!TraceId::get_invalid().is_valid());
+        assert!(TraceId::from_bytes([1; 16]).is_valid());
     }
 }
@@ -33,6 +33,36 @@ impl TraceOptions {
     pub const fn get_size() -> usize {
         mem::size_of::<Self>()
     }
+
+    /// Returns the single byte used to encode these options on the wire.
+    pub fn to_byte(&self) -> u8 {
+        self.bits()
+    }
+
+    /// Decodes options from their wire byte.
+    ///
+    /// Bits that do not correspond to a known flag are dropped, so options sent by a peer
+    /// implementing a newer version of the format are still accepted. Dropped flags are not
+    /// propagated to children.
+    pub fn from_byte(byte: u8) -> TraceOptions {
+        TraceOptions::from_bits_truncate(byte)
+    }
+
+    /// Returns `true` if the `IS_SAMPLED` flag is set.
+    pub fn is_sampled(&self) -> bool {
+        self.contains(TraceOptions::IS_SAMPLED)
+    }
+
+    /// Returns these options with the `IS_SAMPLED` flag set or cleared, other flags are unchanged.
+    pub fn with_sampled(mut self, sampled: bool) -> TraceOptions {
+        self.set_sampled(sampled);
+        self
+    }
+
+    /// Sets or clears the `IS_SAMPLED` flag, other flags are unchanged.
+    pub fn set_sampled(&mut self, sampled: bool) {
+        self.set(TraceOptions::IS_SAMPLED, sampled);
+    }
 }
 
 impl Default for TraceOptions {
@@ -40,3 +70,60 @@ impl Default for TraceOptions {
         TraceOptions::DEFAULT_OPTIONS
     }
 }
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for TraceOptions {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.to_byte())
+    }
+}
+
+/// Deserializes the byte written by `Serialize`, unknown flags are dropped as in `from_byte`.
+#[cfg(feature = "serde")]
+impl <'de> serde::Deserialize<'de> for TraceOptions {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <u8 as serde::Deserialize>::deserialize(deserializer).map(TraceOptions::from_byte)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sampled() {
+        assert!(!TraceOptions::default().is_sampled());
+        assert!(TraceOptions::default().with_sampled(true).is_sampled());
+        assert_eq!(TraceOptions::IS_SAMPLED.with_sampled(false), TraceOptions::DEFAULT_OPTIONS);
+
+        let mut options = TraceOptions::default();
+        options.set_sampled(true);
+        assert_eq!(options, TraceOptions::IS_SAMPLED);
+        options.set_sampled(false);
+        assert_eq!(options.bits(), 0);
+    }
+
+    #[test]
+    fn test_byte_round_trip() {
+        assert_eq!(TraceOptions::default().to_byte(), 0x00);
+        assert_eq!(TraceOptions::IS_SAMPLED.to_byte(), 0x01);
+        assert_eq!(TraceOptions::from_byte(0x00), TraceOptions::DEFAULT_OPTIONS);
+        assert_eq!(TraceOptions::from_byte(0x01), TraceOptions::IS_SAMPLED);
+    }
+
+    #[test]
+    fn test_from_byte_drops_unknown_bits() {
+        assert_eq!(TraceOptions::from_byte(0x80), TraceOptions::DEFAULT_OPTIONS);
+        assert_eq!(TraceOptions::from_byte(0x81), TraceOptions::IS_SAMPLED);
+        assert_eq!(TraceOptions::from_byte(0xff).to_byte(), 0x01);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        assert_eq!(serde_json::to_string(&TraceOptions::IS_SAMPLED).unwrap(), "1");
+        assert_eq!(serde_json::from_str::<TraceOptions>("1").unwrap(), TraceOptions::IS_SAMPLED);
+        assert_eq!(serde_json::from_str::<TraceOptions>("129").unwrap(), TraceOptions::IS_SAMPLED);
+        assert!(serde_json::from_str::<TraceOptions>("256").is_err());
+    }
+}
@@ -15,6 +15,8 @@
  */
 
 use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
 
 /// Carries tracing-system specific context in a list of key-value pairs. TraceState allows different
 /// vendors propagate additional information and inter-operate with their legacy Id formats.
@@ -29,7 +31,7 @@ use std::borrow::Cow;
 /// range 0x20 to 0x7E) except comma , and =.
 #[derive(Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct TraceState<'a> {
-    pub entries: Vec<Entry<'a>>
+    entries: Vec<Entry<'a>>
 }
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
@@ -40,12 +42,34 @@ pub struct Entry<'a> {
 
 const MAX_KEY_LEN: usize = 255;
 const MAX_VAL_LEN: usize = 255;
-const MAX_KEY_VALUE_PAIRS: usize = 32;
+/// Maximum number of entries in a `TraceState`.
+pub const MAX_KEY_VALUE_PAIRS: usize = 32;
 
 impl <'a> TraceState<'a> {
     fn new(entries: Vec<Entry<'a>>) -> Self {
-        assert!(entries.len() <= MAX_KEY_VALUE_PAIRS, "Invalid size");
-        TraceState { entries }
+        TraceState::try_new(entries).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_new(entries: Vec<Entry<'a>>) -> Result<Self, TooManyEntries> {
+        if entries.len() > MAX_KEY_VALUE_PAIRS {
+            return Err(TooManyEntries { len: entries.len() });
+        }
+        Ok(TraceState { entries })
+    }
+
+    /// Returns the entries, the most recently written first.
+    pub fn entries(&self) -> &[Entry<'a>] {
+        &self.entries
+    }
+
+    /// Returns the number of entries, at most `MAX_KEY_VALUE_PAIRS`.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
     }
 
     /// Returns the value to which the specified key is mapped
@@ -53,6 +77,44 @@ impl <'a> TraceState<'a> {
         self.entries.iter().find(|x| x.key == key)
     }
 
+    /// Returns the W3C `tracestate` header representation, e.g. `rojo=00f067aa0ba902b7,congo=t61rc`.
+    pub fn to_header(&self) -> String {
+        self.entries
+            .iter()
+            .map(|e| format!("{}={}", e.key, e.value))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Parses a W3C `tracestate` header.
+    ///
+    /// Returns `None` if any entry is malformed or duplicated, or if there are too many entries, in
+    /// which case the whole header must be discarded.
+    pub fn from_header(header: &str) -> Option<TraceState<'static>> {
+        let mut entries: Vec<Entry<'static>> = Vec::new();
+        for member in header.split(',').map(str::trim).filter(|m| !m.is_empty()) {
+            let (key, value) = member.split_once('=')?;
+            if !is_valid_key(key) || !is_valid_value(value) || entries.iter().any(|e| e.key == key) {
+                return None;
+            }
+            entries.push(Entry { key: key.to_string().into(), value: value.to_string().into() });
+        }
+
+        if entries.len() > MAX_KEY_VALUE_PAIRS {
+            return None;
+        }
+        Some(TraceState::new(entries))
+    }
+
+    /// Converts this `TraceState` into one that owns all of its entries.
+    pub fn into_owned(self) -> TraceState<'static> {
+        TraceState {
+            entries: self.entries.into_iter()
+                .map(|e| Entry { key: Cow::Owned(e.key.into_owned()), value: Cow::Owned(e.value.into_owned()) })
+                .collect()
+        }
+    }
+
     /// Return this tracestate as a builder
     pub fn as_builder(&'a self) -> TraceStateBuilder<'a> {
         TraceStateBuilder {
@@ -71,12 +133,13 @@ pub struct TraceStateBuilder<'a> {
 impl <'a> TraceStateBuilder<'a> {
     /// Adds or updates the `Entry` that has the given `key if it is present.
     ///
-    /// The new `Entry` will always be added in the front of the list of entries.
+    /// The new `Entry` will always be added in the front of the list of entries, an updated key
+    /// being moved there, so the most recently written entry comes first as W3C requires.
     pub fn set<K, V>(mut self, key: K, value: V) -> Self
         where K: Into<Cow<'a, str>>,
               V: Into<Cow<'a, str>>
     {
-        let mut entries = self.entries.get_or_insert(self.parent.map_or(vec![], |x| x.entries.clone()));
+        let entries = self.entries.get_or_insert(self.parent.map_or(vec![], |x| x.entries.clone()));
         let key = validate_key(key);
         let value = validate_value(value);
         entries.retain(|x| x.key != key);
@@ -84,10 +147,35 @@ impl <'a> TraceStateBuilder<'a> {
         self
     }
 
+    /// Adds or updates the entries with the given key-value pairs, which end up in front of the
+    /// list of entries in the given order.
+    ///
+    /// This is `set` called with each pair from the last to the first, so a key given more than
+    /// once keeps its first value.
+    pub fn set_many<I, K, V>(mut self, pairs: I) -> Self
+        where I: IntoIterator<Item=(K, V)>,
+              K: Into<Cow<'a, str>>,
+              V: Into<Cow<'a, str>>
+    {
+        let pairs: Vec<_> = pairs.into_iter().collect();
+        for (key, value) in pairs.into_iter().rev() {
+            self = self.set(key, value);
+        }
+        self
+    }
+
+    /// Discards the parent and every entry added so far, so the `TraceState` is built from the
+    /// entries added afterwards only.
+    pub fn clear(mut self) -> Self {
+        self.parent = None;
+        self.entries = Some(Vec::new());
+        self
+    }
+
     /// Removes the `Entry` that has the given `key` if it is present.
     pub fn remove<K: Into<Cow<'a, str>>>(mut self, key: K) -> Self {
         let key = validate_key(key);
-        let mut entries = self.entries.get_or_insert(self.parent.map_or(vec![], |x| x.entries.clone()));
+        let entries = self.entries.get_or_insert(self.parent.map_or(vec![], |x| x.entries.clone()));
         entries.retain(|x| x.key != key);
         self
     }
@@ -99,41 +187,241 @@ impl <'a> TraceStateBuilder<'a> {
 
     /// Builds a TraceState by adding the entries to the parent in front of the key-value pairs list
     /// and removing duplicate entries.
+    ///
+    /// # Panics
+    /// If the parent and the added entries hold more than `MAX_KEY_VALUE_PAIRS` distinct keys, see
+    /// `try_build`.
     pub fn build(self) -> TraceState<'a> {
+        self.try_build().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Builds a TraceState like `build`, returning an error instead of panicking when the parent
+    /// and the added entries hold more than `MAX_KEY_VALUE_PAIRS` distinct keys.
+    ///
+    /// Callers adding entries to a parent received from another process should expect it to be
+    /// full, and remove an entry first, e.g. the last one.
+    pub fn try_build(self) -> Result<TraceState<'a>, TooManyEntries> {
         match self.entries {
-            None => TraceState::new(self.parent.map_or(vec![], |x| x.entries.clone())),
-            Some(values) => TraceState::new(values),
+            None => TraceState::try_new(self.parent.map_or(vec![], |x| x.entries.clone())),
+            Some(values) => TraceState::try_new(values),
         }
     }
 }
 
+/// Error returned when a `TraceState` would hold more than `MAX_KEY_VALUE_PAIRS` entries.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct TooManyEntries {
+    /// Number of entries the `TraceState` would have held.
+    pub len: usize,
+}
+
+impl fmt::Display for TooManyEntries {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "trace state has {} entries, at most {} are allowed", self.len, MAX_KEY_VALUE_PAIRS)
+    }
+}
+
+impl Error for TooManyEntries {}
+
 // Key is opaque string up to 256 characters printable. It MUST begin with a lowercase letter, and
 // can only contain lowercase letters a-z, digits 0-9, underscores _, dashes -, asterisks *, and
 // forward slashes /.
 fn validate_key<'a, N: Into<Cow<'a, str>>>(key: N) -> Cow<'a, str> {
     let key = key.into();
-    assert!(key.len() <= MAX_KEY_LEN, "Should be an ASCII string not longer than {}", MAX_KEY_LEN);
-    assert!(!key.is_empty(), "Key should not be empty");
-    assert!(key.chars().nth(0).unwrap().is_ascii_lowercase(), "First char of key must be 'a'-'z'");
-    assert!(key.chars().all(|c| {
-        c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-' || c == '*' || c == '/'
-    }), "Key cannot contain characters outside of {'a'-'z', '0'-'9', _, -, *, / }");
+    assert!(is_valid_key(&key),
+            "Key should be 1 to {} characters in {{'a'-'z', '0'-'9', _, -, *, / }}, starting with 'a'-'z'",
+            MAX_KEY_LEN);
     key
 }
 
 fn validate_value<'a, V: Into<Cow<'a, str>>>(value: V) -> Cow<'a, str> {
     let value = value.into();
-    assert!(value.len() <= MAX_VAL_LEN, "Should be an ASCII string not longer than {}", MAX_VAL_LEN);
-    assert!(value.chars().all(|c| !c.is_ascii_control() || c != ',' || c != '='),
-            "Value cannot contain none ascii chars, unprintable chars or ',' & '='");
+    assert!(is_valid_value(&value),
+            "Value should be 1 to {} printable ASCII characters other than ',' and '=', not ending with a space",
+            MAX_VAL_LEN);
     value
 }
 
+fn is_valid_key(key: &str) -> bool {
+    key.len() <= MAX_KEY_LEN
+        && key.starts_with(|c: char| c.is_ascii_lowercase())
+        && key.chars().all(|c| {
+            c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-' || c == '*' || c == '/'
+        })
+}
+
+// Value is opaque string up to 256 characters printable ASCII RFC0020 characters (i.e., the range
+// 0x20 to 0x7E) except comma , and =. The last character must not be a space.
+fn is_valid_value(value: &str) -> bool {
+    value.len() <= MAX_VAL_LEN
+        && !value.is_empty()
+        && !value.ends_with(' ')
+        && value.chars().all(|c| (' '..='~').contains(&c) && c != ',' && c != '=')
+}
+
+#[cfg(feature = "serde")]
+impl <'a> serde::Serialize for TraceState<'a> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_header())
+    }
+}
+
+/// Deserializes the `tracestate` header form written by `Serialize`.
+#[cfg(feature = "serde")]
+impl <'de, 'a> serde::Deserialize<'de> for TraceState<'a> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let header = <String as serde::Deserialize>::deserialize(deserializer)?;
+        TraceState::from_header(&header)
+            .ok_or_else(|| D::Error::custom(format!("invalid tracestate header {:?}", header)))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use proptest::prelude::*;
 
+    #[test]
+    fn test_header_round_trip() {
+        let state = TraceState::from_header("rojo=00f067aa0ba902b7, congo=t61rcWkgMzE").unwrap();
+        assert_eq!(state.len(), 2);
+        assert_eq!(state.get("rojo").unwrap().value, "00f067aa0ba902b7");
+        assert_eq!(state.to_header(), "rojo=00f067aa0ba902b7,congo=t61rcWkgMzE");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let state = TraceState::from_header("rojo=00f067aa0ba902b7,congo=t61rcWkgMzE").unwrap();
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(json, r#""rojo=00f067aa0ba902b7,congo=t61rcWkgMzE""#);
+        assert_eq!(serde_json::from_str::<TraceState<'_>>(&json).unwrap(), state);
+
+        let err = serde_json::from_str::<TraceState<'_>>(r#""rojo""#).unwrap_err();
+        assert!(err.to_string().contains("invalid tracestate header"), "{}", err);
+    }
+
+    #[test]
+    fn test_clear() {
+        let parent = TraceState::from_header("rojo=00f067aa0ba902b7,congo=t61rcWkgMzE").unwrap();
+        let state = parent.as_builder().set("b", "2").clear().set("a", "1").build();
+        assert_eq!(state.to_header(), "a=1");
+        assert!(parent.as_builder().clear().build().is_empty());
+    }
+
+    #[test]
+    fn test_set_many() {
+        let parent = TraceState::from_header("rojo=00f067aa0ba902b7,a=0").unwrap();
+        let state = parent.as_builder().set_many(vec![("a", "1"), ("b", "2"), ("c", "3")]).build();
+        assert_eq!(state.to_header(), "a=1,b=2,c=3,rojo=00f067aa0ba902b7");
+
+        let state = TraceStateBuilder::builder().set_many(vec![("a", "1"), ("a", "2")]).build();
+        assert_eq!(state.to_header(), "a=1");
+    }
+
+    fn full_state() -> TraceState<'static> {
+        let header = (0..MAX_KEY_VALUE_PAIRS).map(|i| format!("k{}=v{}", i, i)).collect::<Vec<_>>();
+        TraceState::from_header(&header.join(",")).unwrap()
+    }
+
+    #[test]
+    fn test_build_rejects_too_many_entries() {
+        let parent = full_state();
+        assert_eq!(parent.as_builder().set("extra", "1").try_build(), Err(TooManyEntries { len: 33 }));
+
+        // Updating an existing key, or making room first, keeps the state within the cap
+        let updated = parent.as_builder().set("k5", "new").try_build().unwrap();
+        assert_eq!(updated.len(), MAX_KEY_VALUE_PAIRS);
+        assert_eq!(updated.entries()[0].value, "new");
+        let stamped = parent.as_builder().remove("k31").set("extra", "1").build();
+        assert_eq!(stamped.len(), MAX_KEY_VALUE_PAIRS);
+        assert_eq!(parent.as_builder().build(), parent);
+    }
+
+    #[test]
+    #[should_panic(expected = "trace state has 33 entries, at most 32 are allowed")]
+    fn test_build_panics_with_too_many_entries() {
+        full_state().as_builder().set("extra", "1").build();
+    }
+
+    fn keys(state: &TraceState) -> Vec<String> {
+        state.entries().iter().map(|e| e.key.to_string()).collect()
+    }
+
+    #[test]
+    fn test_set_moves_key_to_front() {
+        let state = TraceStateBuilder::builder().set("a", "1").set("b", "2").set("a", "3").build();
+        assert_eq!(keys(&state), vec!["a", "b"]);
+        assert_eq!(state.get("a").unwrap().value, "3");
+    }
+
+    #[test]
+    fn test_set_on_parent_moves_key_to_front() {
+        let parent = TraceState::from_header("a=1,b=2,c=3").unwrap();
+        let state = parent.as_builder().set("c", "4").build();
+        assert_eq!(state.to_header(), "c=4,a=1,b=2");
+
+        let state = state.as_builder().set("b", "5").remove("a").set("d", "6").build();
+        assert_eq!(keys(&state), vec!["d", "b", "c"]);
+
+        let state = state.as_builder().remove("d").set("a", "7").set("c", "8").remove("b").build();
+        assert_eq!(state.to_header(), "c=8,a=7");
+        assert_eq!(keys(&parent), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_len() {
+        let state = TraceState::default();
+        assert_eq!(state.len(), 0);
+        assert!(state.is_empty());
+        assert!(state.entries().is_empty());
+
+        let state = TraceState::from_header("rojo=00f067aa0ba902b7,congo=t61rcWkgMzE").unwrap();
+        assert_eq!(state.len(), 2);
+        assert!(!state.is_empty());
+        assert_eq!(state.entries()[1].key, "congo");
+        assert!(state.as_builder().remove("rojo").remove("congo").build().is_empty());
+    }
+
+    #[test]
+    fn test_from_header_empty() {
+        assert_eq!(TraceState::from_header(""), Some(TraceState::default()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_validate_value_rejects_comma() {
+        validate_value("a,b");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_validate_value_rejects_equals() {
+        validate_value("a=b");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_validate_value_rejects_empty() {
+        validate_value("");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_validate_value_rejects_trailing_space() {
+        validate_value("a ");
+    }
+
+    #[test]
+    fn test_from_header_invalid() {
+        assert_eq!(TraceState::from_header("Rojo=1"), None);
+        assert_eq!(TraceState::from_header("rojo"), None);
+        assert_eq!(TraceState::from_header("rojo=1,rojo=2"), None);
+        assert_eq!(TraceState::from_header("rojo=a\u{7f}b"), None);
+    }
+
     proptest! {
         /// Valid key alphabets should always work
         #[test]
@@ -157,7 +445,7 @@ mod test {
 
         /// Valid value alphabets should always work
         #[test]
-        fn test_validate_value_correct_alphabet(s in "[[:ascii:]&&[^,=]]{0, 255}") {
+        fn test_validate_value_correct_alphabet(s in "[ -~&&[^,=]]{0, 254}[!-~&&[^,=]]") {
             assert_eq!(validate_value(s.clone()), s)
         }
 
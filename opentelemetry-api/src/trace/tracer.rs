@@ -1,6 +1,9 @@
 use std::borrow::Cow;
-use crate::trace::span::Span;
-use crate::context::Scope;
+use crate::trace::sampler::ParentContext;
+use crate::trace::span::{DefaultSpan, Span};
+use crate::trace::span_builder::SpanBuilder;
+use crate::trace::span_data::SpanData;
+use crate::context::{self, SpanContextScope};
 
 /// Tracer is a simple, interface for `Span` creation and in-process context interaction.
 ///
@@ -60,7 +63,12 @@ pub trait Tracer {
     /// @return a default {@code Span} that does nothing and has an invalid {@link SpanContext} if no
     ///    {@code Span} is associated with the current Context, otherwise the current {@code Span}
     ///    from the Context.
-    fn current_span(&self) -> &Self::Span;
+    ///
+    /// The returned `Span` only carries the current `SpanContext`, recording is done through the
+    /// `Span` that was started.
+    fn current_span(&self) -> DefaultSpan {
+        context::current_span_context().map_or_else(DefaultSpan::invalid, DefaultSpan::new)
+    }
 
     /// Enters the scope of code where the given {@link Span} is in the current Context, and returns an
     /// object that represents that scope. The scope is exited when the returned object is closed.
@@ -109,27 +117,59 @@ pub trait Tracer {
     /// @return an object that defines a scope where the given {@link Span} will be set to the current
     ///     Context.
     /// @throws NullPointerException if {@code span} is {@code null}.
-    fn with_span<S: Scope>(&self, span: &Self::Span) -> S;
+    fn with_span(&self, span: &Self::Span) -> SpanContextScope {
+        context::with_span_context(span.context().clone().into_owned())
+    }
 
-    /*
-    /// Returns a {@link Span.Builder} to create and start a new {@link Span}.
+    /// Starts a `Span` with the given name, runs `f` with it as the current span, then ends it and
+    /// returns the result of `f`.
     ///
-    /// <p>See {@link Span.Builder} for usage examples.
+    /// The span is ended and the previous current span restored even if `f` panics.
     ///
-    /// @param spanName The name of the returned Span.
-    /// @return a {@code Span.Builder} to create and start a new {@code Span}.
-    /// @throws NullPointerException if {@code spanName} is {@code null}.
-    /// @since 0.1.0
+    /// ```
+    /// use opentelemetry_api::trace::span::Span;
+    /// use opentelemetry_api::trace::tracer::{NoopTracer, Tracer};
+    ///
+    /// let answer = NoopTracer.in_span("compute", |span| {
+    ///     span.set_attribute("input", 21);
+    ///     21 * 2
+    /// });
+    /// assert_eq!(answer, 42);
+    /// ```
+    fn in_span<'a, N, T, F>(&'a self, name: N, f: F) -> T
+        where N: Into<Cow<'a, str>>,
+              F: FnOnce(&mut Self::Span) -> T,
+              Self: Sized
+    {
+        let mut span = EndOnDrop(self.span_builder(name).start_span());
+        // Declared after the span so it is dropped, restoring the previous span, before the end
+        let _scope = self.with_span(&span.0);
+        f(&mut span.0)
+    }
+
+    /// Returns a `SpanBuilder` to create and start a new `Span`.
+    ///
+    /// See `SpanBuilder` for usage examples.
+    fn span_builder<'a, N: Into<Cow<'a, str>>>(&'a self, name: N) -> SpanBuilder<'a, Self> {
+        SpanBuilder::new(self, name)
+    }
+
+    /// Starts a new `Span` configured by the given `SpanBuilder`.
     ///
-    fn span_builder<'a, N: Into<Cow<'a, str>>>(&self, name: N) -> SpanBuilder<'a>;
+    /// This is called by `SpanBuilder::start_span`, which should be preferred.
+    fn start_span(&self, builder: SpanBuilder<Self>) -> Self::Span;
 
     /// Records a `SpanData`.
     ///
-    /// This API allows to send a pre-populated span object to the exporter.
+    /// This API allows to send a pre-populated span object to the exporter, e.g. when adapting
+    /// spans of another tracing system.
     /// Sampling and recording decisions as well as other collection optimizations is a
     /// responsibility of a caller. Note, the `SpanContext` object on the span population with
-    /// the values that will allow correlation of telemetry is also a caller responsibility.
-    fn record_span_data(&self, span: SpanData);
+    /// the values that will allow correlation of telemetry is also a caller responsibility, as are
+    /// its start and end times.
+    fn record_span_data(&self, span: SpanData<'static>);
+
+    /*
 
     /// Returns the {@link BinaryFormat} for this tracer implementation.
     ///
@@ -234,4 +274,117 @@ pub trait Tracer {
     ///
     fn get_http_text_format(&self) -> HttpTextFormat<SpanContext>;
     */
-}
\ No newline at end of file
+}
+
+/// Ends the wrapped `Span` when dropped, including while unwinding.
+struct EndOnDrop<S: Span>(S);
+
+impl <S: Span> Drop for EndOnDrop<S> {
+    fn drop(&mut self) {
+        self.0.end();
+    }
+}
+
+/// Provides named `Tracer`s, usually one per instrumented library.
+pub trait TracerProvider {
+    type Tracer: Tracer;
+
+    /// Returns the `Tracer` for the instrumentation library with the given name.
+    fn tracer(&self, name: &str) -> Self::Tracer;
+}
+
+/// A `Tracer` whose `Span`s never record, for zero overhead when tracing is disabled, the tracing
+/// counterpart of `DefaultMeter`.
+///
+/// Started spans carry the `SpanContext` of their parent so it is still propagated. As they share
+/// that context, `with_span` leaves the current context untouched.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct NoopTracer;
+
+impl Tracer for NoopTracer {
+    type Span = DefaultSpan;
+
+    fn with_span(&self, _span: &DefaultSpan) -> SpanContextScope {
+        SpanContextScope::noop()
+    }
+
+    fn record_span_data(&self, _span: SpanData<'static>) {}
+
+    fn start_span(&self, builder: SpanBuilder<Self>) -> DefaultSpan {
+        let parent = match builder.parent {
+            Some(parent) => parent,
+            None => context::current_parent(),
+        };
+        match parent {
+            ParentContext::Parent(ctx) | ParentContext::RemoteParent(ctx) => DefaultSpan::new(ctx.into_owned()),
+            ParentContext::RootSpan => DefaultSpan::invalid(),
+        }
+    }
+}
+
+/// `TracerProvider` of `NoopTracer`s.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct NoopTracerProvider;
+
+impl TracerProvider for NoopTracerProvider {
+    type Tracer = NoopTracer;
+
+    fn tracer(&self, _name: &str) -> NoopTracer {
+        NoopTracer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::span_context::SpanContext;
+    use crate::trace::span_id::SpanId;
+    use crate::trace::trace_id::TraceId;
+    use crate::trace::trace_options::TraceOptions;
+    use crate::trace::trace_state::TraceState;
+
+    fn span_context() -> SpanContext<'static> {
+        SpanContext::new(TraceId::from_bytes([1; 16]), SpanId::new(1), TraceOptions::IS_SAMPLED,
+                         TraceState::default())
+    }
+
+    #[test]
+    fn test_noop_spans_never_record() {
+        let tracer = NoopTracerProvider.tracer("test");
+        let mut span = tracer.span_builder("root").attribute("a", 1).start_span();
+        span.set_attribute("b", 2);
+        assert!(!span.is_recording_events());
+        assert!(!span.context().is_valid());
+        assert_eq!(span.attribute_count(), 0);
+
+        let child = tracer.span_builder("child").parent(ParentContext::Parent(span_context())).start_span();
+        assert!(!child.is_recording_events());
+        assert_eq!(*child.context(), span_context());
+    }
+
+    #[test]
+    fn test_noop_current_span() {
+        let tracer = NoopTracer;
+        assert!(!tracer.current_span().context().is_valid());
+        assert!(!tracer.current_span().is_recording_events());
+
+        let _scope = context::with_span_context(span_context());
+        assert_eq!(*tracer.current_span().context(), span_context());
+        assert_eq!(*tracer.span_builder("child").start_span().context(), span_context());
+    }
+
+    #[test]
+    fn test_noop_with_span() {
+        let tracer = NoopTracer;
+        let span = DefaultSpan::new(span_context());
+        {
+            let _scope = tracer.with_span(&span);
+            assert!(context::current_span_context().is_none());
+        }
+        assert!(context::current_span_context().is_none());
+
+        let _outer = context::with_span_context(span_context());
+        drop(tracer.with_span(&DefaultSpan::invalid()));
+        assert_eq!(context::current_span_context(), Some(span_context()));
+    }
+}
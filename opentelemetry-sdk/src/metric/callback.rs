@@ -0,0 +1,50 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::{Arc, Mutex};
+
+type CallbackFn<M> = Arc<dyn Fn(&M) + Send + Sync>;
+
+/// The callback set with `Metric::set_callback`, run before collecting the metric.
+///
+/// Clones share the same callback.
+pub(crate) struct Callback<M>(Arc<Mutex<Option<CallbackFn<M>>>>);
+
+impl <M> Callback<M> {
+    pub fn set<F: Fn(&M) + Send + Sync + 'static>(&self, callback: F) {
+        *self.0.lock().unwrap() = Some(Arc::new(callback));
+    }
+
+    /// Runs the callback, if any, without holding the lock so that it can replace itself.
+    pub fn run(&self, metric: &M) {
+        let callback = self.0.lock().unwrap().clone();
+        if let Some(callback) = callback {
+            callback(metric);
+        }
+    }
+}
+
+impl <M> Clone for Callback<M> {
+    fn clone(&self) -> Self {
+        Callback(self.0.clone())
+    }
+}
+
+impl <M> Default for Callback<M> {
+    fn default() -> Self {
+        Callback(Arc::new(Mutex::new(None)))
+    }
+}
@@ -0,0 +1,226 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::time::SystemTime;
+
+use opentelemetry_api::metric::{
+    Metric, MetricBuildError, MetricBuilder, LabelArityError, LabelValue, Counter, CounterDouble,
+    CounterLong, CounterTimeSeries,
+};
+use opentelemetry_api::resource::Resource;
+
+use super::{series_key, Collect, Descriptor, MetricData, MetricKind, Point, PointValue};
+use super::callback::Callback;
+use super::series::{MaxTimeSeries, SeriesMap};
+use super::timeseries::{AtomicDoubleTimeSeries, AtomicLongTimeSeries};
+
+macro_rules! impl_counter {
+    ($name:ident, $ts:ident, $value:path, $kind:expr, $counter:ident) => (
+        impl Collect for $name {
+            fn descriptor(&self) -> &Descriptor {
+                &self.descriptor
+            }
+
+            /// Returns the cumulative value of every `TimeSeries` of this counter.
+            fn collect(&self) -> MetricData {
+                self.callback.run(self);
+                let time = SystemTime::now();
+                let points = self.series.snapshot()
+                    .into_iter()
+                    .map(|series| Point {
+                        label_values: series.label_values,
+                        value: $value(series.ts.inner().get()),
+                        overflow: series.overflow,
+                        start_time: series.start_time,
+                        time,
+                    })
+                    .collect();
+                MetricData { descriptor: self.descriptor.clone(), resource: self.resource.clone(), points }
+            }
+        }
+
+        impl Metric for $name {
+            type Error = MetricBuildError;
+            type TS = CounterTimeSeries<$ts>;
+
+            fn timeseries(&self, label_values: Vec<LabelValue>) -> Self::TS {
+                self.try_timeseries(label_values).unwrap_or_else(|err| panic!("{}", err))
+            }
+
+            fn try_timeseries(&self, label_values: Vec<LabelValue>) -> Result<Self::TS, LabelArityError> {
+                let key = self.series.key(&label_values)?;
+                Ok(self.series.get_or_create(key, Self::TS::default))
+            }
+
+            fn default_timeseries(&self) -> Self::TS {
+                self.series.get_or_create(self.series.default_key(), Self::TS::default)
+            }
+
+            fn remove_timeseries(&self, label_values: Vec<LabelValue>) {
+                self.series.remove(&series_key(&label_values));
+            }
+
+            fn clear(&self) {
+                self.series.clear();
+            }
+
+            fn set_callback<F: Fn(&Self) + Send + Sync + 'static>(&self, callback: F) {
+                self.callback.set(callback);
+            }
+
+            fn build(mb: MetricBuilder<Self>) -> Result<Self, Self::Error> {
+                Ok($name {
+                    descriptor: Descriptor::from_builder(&mb, $kind),
+                    resource: mb.resource.clone().map(Resource::into_owned),
+                    series: SeriesMap::new(mb.label_keys.len()),
+                    callback: Callback::default(),
+                })
+            }
+        }
+
+        impl MaxTimeSeries for $name {
+            fn set_max_timeseries(&mut self, max: usize) {
+                self.series.set_max_series(max);
+            }
+        }
+
+        impl Counter for $name {}
+        impl $counter for $name {}
+    );
+}
+
+/// A `CounterLong` that keeps the cumulative value of each of its `TimeSeries` in memory.
+///
+/// Its `TimeSeries` are wrapped in a `CounterTimeSeries`, so negative deltas are rejected. Clones
+/// share the same `TimeSeries`, and the callback run by `collect` to set their values.
+#[derive(Clone)]
+pub struct LongCounter {
+    descriptor: Descriptor,
+    resource: Option<Resource<'static>>,
+    series: SeriesMap<CounterTimeSeries<AtomicLongTimeSeries>>,
+    callback: Callback<Self>,
+}
+
+impl_counter!(LongCounter, AtomicLongTimeSeries, PointValue::Long, MetricKind::CounterLong, CounterLong);
+
+/// A `CounterDouble` that keeps the cumulative value of each of its `TimeSeries` in memory.
+///
+/// Its `TimeSeries` are wrapped in a `CounterTimeSeries`, so negative deltas are rejected. Clones
+/// share the same `TimeSeries`, and the callback run by `collect` to set their values.
+#[derive(Clone)]
+pub struct DoubleCounter {
+    descriptor: Descriptor,
+    resource: Option<Resource<'static>>,
+    series: SeriesMap<CounterTimeSeries<AtomicDoubleTimeSeries>>,
+    callback: Callback<Self>,
+}
+
+impl_counter!(DoubleCounter, AtomicDoubleTimeSeries, PointValue::Double, MetricKind::CounterDouble, CounterDouble);
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+    use super::*;
+    use opentelemetry_api::metric::TimeSeries;
+    use opentelemetry_api::metric::{LabelKey, NegativeDeltaError};
+    use crate::metric::DoubleGauge;
+
+    #[test]
+    fn test_collect_timestamps() {
+        let counter: LongCounter = MetricBuilder::new("requests").build().unwrap();
+        counter.default_timeseries().add(1);
+
+        let first = counter.collect();
+        thread::sleep(Duration::from_millis(5));
+        counter.default_timeseries().add(2);
+        let second = counter.collect();
+
+        assert_eq!(first.points.len(), 1);
+        assert_eq!(second.points.len(), 1);
+        assert_eq!(first.points[0].value, PointValue::Long(1));
+        assert_eq!(second.points[0].value, PointValue::Long(3));
+        assert_eq!(first.points[0].start_time, second.points[0].start_time);
+        assert!(second.points[0].time > first.points[0].time);
+        assert!(first.points[0].time >= first.points[0].start_time);
+    }
+
+    #[test]
+    fn test_rejects_negative_delta() {
+        let counter: LongCounter = MetricBuilder::new("requests").build().unwrap();
+        let ts = counter.default_timeseries();
+        ts.add(2);
+        assert_eq!(ts.try_add(-1), Err(NegativeDeltaError));
+        ts.add(-1);
+        assert_eq!(ts.inner().get(), 2);
+        assert_eq!(ts.rejected_count(), 2);
+
+        let gauge: DoubleGauge = MetricBuilder::new("temperature").build().unwrap();
+        gauge.default_timeseries().add(-1.0);
+        assert_eq!(gauge.default_timeseries().get(), -1.0);
+    }
+
+    #[test]
+    fn test_double_counter() {
+        let counter: DoubleCounter = MetricBuilder::new("bytes").unit("By").build().unwrap();
+        counter.default_timeseries().add(1.5);
+        counter.default_timeseries().add(1.0);
+
+        let data = counter.collect();
+        assert_eq!(data.descriptor.name, "bytes");
+        assert_eq!(data.descriptor.unit, "By");
+        assert_eq!(data.descriptor.kind, MetricKind::CounterDouble);
+        assert_eq!(data.points[0].value, PointValue::Double(2.5));
+    }
+
+    fn method_counter() -> LongCounter {
+        MetricBuilder::new("requests")
+            .label_keys(vec![LabelKey { key: "method".into(), description: "".into() }])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_try_timeseries_arity() {
+        let counter = method_counter();
+        let get = LabelValue::new("GET");
+        assert!(counter.try_timeseries(vec![get]).is_ok());
+        assert_eq!(counter.try_timeseries(vec![]).err(), Some(LabelArityError { expected: 1, actual: 0 }));
+    }
+
+    #[test]
+    #[should_panic(expected = "expected 1 label values, got 2")]
+    fn test_timeseries_arity_panics() {
+        let get = LabelValue::new("GET");
+        let put = LabelValue::new("PUT");
+        method_counter().timeseries(vec![get, put]);
+    }
+
+    #[test]
+    fn test_unset_label_values() {
+        let counter = method_counter();
+        counter.timeseries(vec![LabelValue::empty()]).add(1);
+        counter.default_timeseries().add(2);
+        counter.timeseries(vec![LabelValue::new("")]).add(4);
+
+        let mut points: Vec<_> = counter.collect().points.into_iter()
+            .map(|p| (p.label_values, p.value))
+            .collect();
+        points.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(points, vec![
+            (vec![None], PointValue::Long(3)),
+            (vec![Some("".to_string())], PointValue::Long(4)),
+        ]);
+    }
+}
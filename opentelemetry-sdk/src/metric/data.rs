@@ -0,0 +1,140 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::time::SystemTime;
+
+use opentelemetry_api::metric::{Measure, MeasureBuilder, Metric, MetricBuilder};
+use opentelemetry_api::resource::Resource;
+
+use super::summary::SummaryAggregation;
+
+/// The kind of a metric, which determines how its points should be interpreted by exporters.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum MetricKind {
+    CounterLong,
+    CounterDouble,
+    GaugeLong,
+    GaugeDouble,
+    /// Raw measurements, aggregated per set of label values as selected by a `View`, summed by
+    /// default.
+    Measure,
+}
+
+impl MetricKind {
+    /// Returns `true` if points of this kind accumulate from their `start_time`.
+    pub fn is_cumulative(&self) -> bool {
+        match self {
+            MetricKind::CounterLong | MetricKind::CounterDouble | MetricKind::Measure => true,
+            MetricKind::GaugeLong | MetricKind::GaugeDouble => false,
+        }
+    }
+}
+
+/// Describes a metric independently of its recorded values.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Descriptor {
+    /// The full name of the metric, prefixed by its component if any.
+    pub name: String,
+    pub description: String,
+    pub unit: String,
+    pub label_keys: Vec<String>,
+    pub kind: MetricKind,
+}
+
+impl Descriptor {
+    pub(crate) fn from_builder<M: Metric>(mb: &MetricBuilder<M>, kind: MetricKind) -> Self {
+        Descriptor {
+            name: mb.full_name().into_owned(),
+            description: mb.description.to_string(),
+            unit: mb.unit.to_string(),
+            label_keys: mb.label_keys.iter().map(|k| k.key.to_string()).collect(),
+            kind,
+        }
+    }
+
+    pub(crate) fn from_measure_builder<M: Measure>(mb: &MeasureBuilder<M>) -> Self {
+        Descriptor {
+            name: mb.name.to_string(),
+            description: mb.description.to_string(),
+            unit: mb.unit.to_string(),
+            label_keys: mb.label_keys.iter().map(|k| k.key.to_string()).collect(),
+            kind: MetricKind::Measure,
+        }
+    }
+}
+
+/// The value of a single `Point`.
+#[derive(Clone, PartialEq, Debug)]
+pub enum PointValue {
+    Long(i64),
+    Double(f64),
+    Histogram(Histogram),
+    Summary(SummaryAggregation),
+}
+
+/// The distribution of the values recorded for a `Measure` aggregated as a histogram.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Histogram {
+    /// The inclusive upper bounds of the buckets, the last bucket being unbounded.
+    pub boundaries: Vec<f64>,
+    /// The number of values in each bucket, one more than the number of `boundaries`.
+    pub bucket_counts: Vec<u64>,
+    pub count: u64,
+    pub sum: f64,
+}
+
+impl Histogram {
+    pub(crate) fn new(boundaries: Vec<f64>) -> Self {
+        let bucket_counts = vec![0; boundaries.len() + 1];
+        Histogram { boundaries, bucket_counts, count: 0, sum: 0.0 }
+    }
+
+    pub(crate) fn record(&mut self, value: f64) {
+        let bucket = self.boundaries.iter()
+            .position(|boundary| value <= *boundary)
+            .unwrap_or(self.boundaries.len());
+        self.bucket_counts[bucket] += 1;
+        self.count += 1;
+        self.sum += value;
+    }
+}
+
+/// The label exporters should add to the overflow series, with the value `true`.
+pub const OVERFLOW_LABEL_KEY: &str = "otel_overflow";
+
+/// The value of a `TimeSeries` at collection time.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Point {
+    /// The label values identifying the series, `None` for labels that are not set.
+    pub label_values: Vec<Option<String>>,
+    pub value: PointValue,
+    /// Whether this is the overflow series of the metric, aggregating all the label values
+    /// recorded once the metric had reached its maximum number of series. Its label values are
+    /// unset, and it should be labeled `otel_overflow=true` instead.
+    pub overflow: bool,
+    /// When the series started accumulating, for cumulative metrics. Equal to `time` otherwise.
+    pub start_time: SystemTime,
+    /// When the point was collected.
+    pub time: SystemTime,
+}
+
+/// A snapshot of a metric and all of its `TimeSeries`, ready to be exported.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MetricData {
+    pub descriptor: Descriptor,
+    /// The `Resource` the metric was built with, or inherited from its `Meter`.
+    pub resource: Option<Resource<'static>>,
+    pub points: Vec<Point>,
+}
@@ -0,0 +1,408 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::{
+    sync::{Arc, atomic::{AtomicUsize, Ordering}},
+    time::SystemTime,
+};
+
+use opentelemetry_api::metric::{
+    Metric, MetricBuildError, MetricBuilder, TimeSeries, LabelArityError, LabelValue, Gauge,
+    GaugeDouble, GaugeLong,
+};
+use opentelemetry_api::resource::Resource;
+
+use super::{series_key, Collect, Descriptor, MetricData, MetricKind, Point, PointValue};
+use super::callback::Callback;
+use super::series::{MaxTimeSeries, SeriesMap};
+use super::timeseries::{AtomicDoubleTimeSeries, AtomicLongTimeSeries};
+
+macro_rules! impl_gauge {
+    ($name:ident, $ts:ident, $value:path, $kind:expr, $gauge:ident) => (
+        impl Collect for $name {
+            fn descriptor(&self) -> &Descriptor {
+                &self.descriptor
+            }
+
+            /// Returns the latest value of every `TimeSeries` of this gauge.
+            fn collect(&self) -> MetricData {
+                self.callback.run(self);
+                let time = SystemTime::now();
+                let points = self.series.snapshot()
+                    .into_iter()
+                    .map(|series| Point {
+                        label_values: series.label_values,
+                        value: $value(series.ts.get()),
+                        overflow: series.overflow,
+                        start_time: time,
+                        time,
+                    })
+                    .collect();
+                MetricData { descriptor: self.descriptor.clone(), resource: self.resource.clone(), points }
+            }
+        }
+
+        impl Metric for $name {
+            type Error = MetricBuildError;
+            type TS = $ts;
+
+            fn timeseries(&self, label_values: Vec<LabelValue>) -> Self::TS {
+                self.try_timeseries(label_values).unwrap_or_else(|err| panic!("{}", err))
+            }
+
+            fn try_timeseries(&self, label_values: Vec<LabelValue>) -> Result<Self::TS, LabelArityError> {
+                let key = self.series.key(&label_values)?;
+                let range = self.range;
+                Ok(self.series.get_or_create(key, || $ts::with_range(range)))
+            }
+
+            fn default_timeseries(&self) -> Self::TS {
+                let range = self.range;
+                self.series.get_or_create(self.series.default_key(), || $ts::with_range(range))
+            }
+
+            fn remove_timeseries(&self, label_values: Vec<LabelValue>) {
+                self.series.remove(&series_key(&label_values));
+            }
+
+            fn clear(&self) {
+                self.series.clear();
+            }
+
+            fn set_callback<F: Fn(&Self) + Send + Sync + 'static>(&self, callback: F) {
+                self.callback.set(callback);
+            }
+
+            fn build(mb: MetricBuilder<Self>) -> Result<Self, Self::Error> {
+                Ok($name {
+                    descriptor: Descriptor::from_builder(&mb, $kind),
+                    resource: mb.resource.clone().map(Resource::into_owned),
+                    range: mb.range,
+                    series: SeriesMap::new(mb.label_keys.len()),
+                    callback: Callback::default(),
+                })
+            }
+        }
+
+        impl MaxTimeSeries for $name {
+            fn set_max_timeseries(&mut self, max: usize) {
+                self.series.set_max_series(max);
+            }
+        }
+
+        impl Gauge for $name {}
+        impl $gauge for $name {}
+    );
+}
+
+/// A `GaugeLong` that keeps the latest value of each of its `TimeSeries` in memory.
+///
+/// If the builder was given a `range`, values outside of it are clamped into the range and
+/// counted, as they usually indicate a bug in the instrumentation. Clones share the same
+/// `TimeSeries`, and the callback run by `collect` to set their values.
+#[derive(Clone)]
+pub struct LongGauge {
+    descriptor: Descriptor,
+    resource: Option<Resource<'static>>,
+    range: Option<(f64, f64)>,
+    series: SeriesMap<LongGaugeTimeSeries>,
+    callback: Callback<Self>,
+}
+
+impl_gauge!(LongGauge, LongGaugeTimeSeries, PointValue::Long, MetricKind::GaugeLong, GaugeLong);
+
+/// A `GaugeDouble` that keeps the latest value of each of its `TimeSeries` in memory.
+///
+/// If the builder was given a `range`, values outside of it are clamped into the range and
+/// counted, as they usually indicate a bug in the instrumentation. Clones share the same
+/// `TimeSeries`, and the callback run by `collect` to set their values.
+#[derive(Clone)]
+pub struct DoubleGauge {
+    descriptor: Descriptor,
+    resource: Option<Resource<'static>>,
+    range: Option<(f64, f64)>,
+    series: SeriesMap<DoubleGaugeTimeSeries>,
+    callback: Callback<Self>,
+}
+
+impl_gauge!(DoubleGauge, DoubleGaugeTimeSeries, PointValue::Double, MetricKind::GaugeDouble, GaugeDouble);
+
+/// `TimeSeries` handle for a `LongGauge`, clones share the same underlying value.
+#[derive(Clone, Default, Debug)]
+pub struct LongGaugeTimeSeries {
+    value: AtomicLongTimeSeries,
+    out_of_range: Arc<AtomicUsize>,
+    range: Option<(f64, f64)>,
+}
+
+impl LongGaugeTimeSeries {
+    fn with_range(range: Option<(f64, f64)>) -> Self {
+        LongGaugeTimeSeries {
+            range,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the current value of this `TimeSeries`.
+    pub fn get(&self) -> i64 {
+        self.value.get()
+    }
+
+    /// Returns how many values were clamped because they fell outside of the gauge's range.
+    pub fn out_of_range_count(&self) -> usize {
+        self.out_of_range.load(Ordering::SeqCst)
+    }
+
+    /// Returns `val` clamped into the range, and whether it was out of range.
+    fn clamp(&self, val: i64) -> (i64, bool) {
+        match self.range {
+            Some((min, _)) if (val as f64) < min => (min.ceil() as i64, true),
+            Some((_, max)) if (val as f64) > max => (max.floor() as i64, true),
+            _ => (val, false),
+        }
+    }
+
+    /// Atomically applies `f` to the current value and clamps the result, the out of range
+    /// counter is only incremented once the new value is stored.
+    fn update<F: Fn(i64) -> i64>(&self, f: F) {
+        let previous = self.value.update(|current| self.clamp(f(current)).0);
+        if self.clamp(f(previous)).1 {
+            self.out_of_range.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+impl TimeSeries for LongGaugeTimeSeries {
+    type V = i64;
+
+    fn add(&self, delta: i64) {
+        self.update(|current| current.wrapping_add(delta));
+    }
+
+    fn set(&self, val: i64) {
+        self.update(|_| val);
+    }
+}
+
+/// `TimeSeries` handle for a `DoubleGauge`, clones share the same underlying value.
+#[derive(Clone, Default, Debug)]
+pub struct DoubleGaugeTimeSeries {
+    value: AtomicDoubleTimeSeries,
+    out_of_range: Arc<AtomicUsize>,
+    range: Option<(f64, f64)>,
+}
+
+impl DoubleGaugeTimeSeries {
+    fn with_range(range: Option<(f64, f64)>) -> Self {
+        DoubleGaugeTimeSeries {
+            range,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the current value of this `TimeSeries`.
+    pub fn get(&self) -> f64 {
+        self.value.get()
+    }
+
+    /// Returns how many values were clamped because they fell outside of the gauge's range.
+    pub fn out_of_range_count(&self) -> usize {
+        self.out_of_range.load(Ordering::SeqCst)
+    }
+
+    /// Returns `val` clamped into the range, and whether it was out of range.
+    ///
+    /// A `NaN` is never in range, it is rejected and `current` is kept instead.
+    fn clamp(&self, current: f64, val: f64) -> (f64, bool) {
+        match self.range {
+            Some(_) if val.is_nan() => (current, true),
+            Some((min, max)) if val < min || val > max => (val.clamp(min, max), true),
+            _ => (val, false),
+        }
+    }
+
+    /// Atomically applies `f` to the current value and clamps the result, the out of range
+    /// counter is only incremented once the new value is stored.
+    fn update<F: Fn(f64) -> f64>(&self, f: F) {
+        let previous = self.value.update(|current| self.clamp(current, f(current)).0);
+        if self.clamp(previous, f(previous)).1 {
+            self.out_of_range.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+impl TimeSeries for DoubleGaugeTimeSeries {
+    type V = f64;
+
+    fn add(&self, delta: f64) {
+        self.update(|current| current + delta);
+    }
+
+    fn set(&self, val: f64) {
+        self.update(|_| val);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_without_range() {
+        let gauge: DoubleGauge = MetricBuilder::new("cpu").build().unwrap();
+        let ts = gauge.default_timeseries();
+        ts.set(150.0);
+        ts.add(-200.0);
+        assert_eq!(ts.get(), -50.0);
+        assert_eq!(ts.out_of_range_count(), 0);
+    }
+
+    #[test]
+    fn test_set_clamps_to_range() {
+        let gauge: DoubleGauge = MetricBuilder::new("cpu")
+            .range(0.0, 100.0)
+            .build()
+            .unwrap();
+        let ts = gauge.default_timeseries();
+
+        ts.set(150.0);
+        assert_eq!(ts.get(), 100.0);
+        assert_eq!(ts.out_of_range_count(), 1);
+
+        ts.set(-1.0);
+        assert_eq!(ts.get(), 0.0);
+        assert_eq!(ts.out_of_range_count(), 2);
+
+        ts.set(42.0);
+        assert_eq!(ts.get(), 42.0);
+        assert_eq!(ts.out_of_range_count(), 2);
+
+        ts.add(100.0);
+        assert_eq!(ts.get(), 100.0);
+        assert_eq!(ts.out_of_range_count(), 3);
+    }
+
+    #[test]
+    fn test_rejects_nan() {
+        let gauge: DoubleGauge = MetricBuilder::new("cpu")
+            .range(0.0, 100.0)
+            .build()
+            .unwrap();
+        let ts = gauge.default_timeseries();
+        ts.set(42.0);
+
+        ts.set(f64::NAN);
+        assert_eq!(ts.get(), 42.0);
+        ts.add(f64::NAN);
+        assert_eq!(ts.get(), 42.0);
+        assert_eq!(ts.out_of_range_count(), 2);
+    }
+
+    #[test]
+    fn test_out_of_range_counted_once_under_contention() {
+        let gauge: LongGauge = MetricBuilder::new("queue_size")
+            .range(0.0, 10.0)
+            .build()
+            .unwrap();
+        let ts = gauge.default_timeseries();
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let ts = ts.clone();
+                std::thread::spawn(move || for _ in 0..1000 { ts.add(20) })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(ts.get(), 10);
+        assert_eq!(ts.out_of_range_count(), 8000);
+    }
+
+    #[test]
+    fn test_timeseries_share_state() {
+        let gauge: DoubleGauge = MetricBuilder::new("cpu").build().unwrap();
+        gauge.default_timeseries().set(5.0);
+        assert_eq!(gauge.default_timeseries().get(), 5.0);
+
+        gauge.clear();
+        assert_eq!(gauge.default_timeseries().get(), 0.0);
+    }
+
+    #[test]
+    fn test_collect() {
+        let gauge: DoubleGauge = MetricBuilder::new("cpu").build().unwrap();
+        gauge.default_timeseries().set(5.0);
+
+        let data = gauge.collect();
+        assert_eq!(data.descriptor.kind, MetricKind::GaugeDouble);
+        assert_eq!(data.points.len(), 1);
+        assert_eq!(data.points[0].value, PointValue::Double(5.0));
+        assert_eq!(data.points[0].start_time, data.points[0].time);
+    }
+
+    #[test]
+    fn test_long_gauge() {
+        let gauge: LongGauge = MetricBuilder::new("queue_size")
+            .range(0.0, 10.5)
+            .build()
+            .unwrap();
+        let ts = gauge.default_timeseries();
+        ts.add(4);
+        ts.add(-1);
+        assert_eq!(ts.get(), 3);
+
+        ts.set(11);
+        assert_eq!(ts.get(), 10);
+        ts.add(-20);
+        assert_eq!(ts.get(), 0);
+        assert_eq!(ts.out_of_range_count(), 2);
+
+        let data = gauge.collect();
+        assert_eq!(data.descriptor.kind, MetricKind::GaugeLong);
+        assert_eq!(data.points[0].value, PointValue::Long(0));
+    }
+
+    #[test]
+    fn test_callback() {
+        let cpu_usage = Arc::new(AtomicUsize::new(0));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let gauge: LongGauge = MetricBuilder::new("cpu").build().unwrap();
+        {
+            let (cpu_usage, calls) = (cpu_usage.clone(), calls.clone());
+            gauge.set_callback(move |gauge: &LongGauge| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                gauge.default_timeseries().set(cpu_usage.load(Ordering::SeqCst) as i64);
+            });
+        }
+
+        cpu_usage.store(42, Ordering::SeqCst);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        assert_eq!(gauge.collect().points[0].value, PointValue::Long(42));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        cpu_usage.store(7, Ordering::SeqCst);
+        assert_eq!(gauge.clone().collect().points[0].value, PointValue::Long(7));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_callback_replaced() {
+        let gauge: DoubleGauge = MetricBuilder::new("cpu").build().unwrap();
+        gauge.set_callback(|gauge: &DoubleGauge| gauge.default_timeseries().set(1.0));
+        gauge.set_callback(|gauge: &DoubleGauge| gauge.default_timeseries().set(2.0));
+        assert_eq!(gauge.collect().points[0].value, PointValue::Double(2.0));
+    }
+}
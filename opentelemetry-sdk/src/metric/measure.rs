@@ -0,0 +1,230 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use opentelemetry_api::metric::{LabelValue, Measure, MeasureBuilder, Measurement, MeasurementValue};
+use opentelemetry_api::resource::Resource;
+
+use super::{series_key, Collect, Descriptor, Histogram, MetricData, Point, PointValue, SeriesKey};
+use super::series::{MaxTimeSeries, SeriesMap};
+use super::summary::SummaryAggregation;
+use super::view::{Aggregation, View};
+
+/// A `Measure` that aggregates the values recorded for each set of label values, summing them
+/// unless built with a `View`.
+///
+/// Clones share the same recorded values.
+#[derive(Clone)]
+pub struct InMemoryMeasure {
+    descriptor: Descriptor,
+    resource: Option<Resource<'static>>,
+    /// The label keys of the builder, before selecting those of the view.
+    label_keys: Vec<String>,
+    aggregation: Aggregation,
+    /// Positions of the label values to keep, all of them if `None`.
+    kept_labels: Option<Vec<usize>>,
+    series: SeriesMap<Aggregator>,
+}
+
+impl InMemoryMeasure {
+    /// Builds the measure with the aggregation and label keys selected by the `View`, if any.
+    ///
+    /// Label keys of the view that the measure does not have are ignored.
+    pub(crate) fn with_view(builder: &MeasureBuilder<Self>, view: Option<&View>) -> Self {
+        let mut descriptor = Descriptor::from_measure_builder(builder);
+        let label_keys = descriptor.label_keys.clone();
+        let aggregation = view.map(|view| view.aggregation.clone()).unwrap_or_default();
+        let kept_labels = view.and_then(|view| view.label_keys.as_ref()).map(|keys| {
+            descriptor.label_keys.iter()
+                .enumerate()
+                .filter(|(_, key)| keys.contains(key))
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>()
+        });
+        if let Some(kept) = &kept_labels {
+            descriptor.label_keys = kept.iter().map(|&i| descriptor.label_keys[i].clone()).collect();
+        }
+        let series = SeriesMap::new(descriptor.label_keys.len());
+        InMemoryMeasure { descriptor, resource: None, label_keys, aggregation, kept_labels, series }
+    }
+
+    /// Sets the `Resource` of the measure, measures having none of their own.
+    pub(crate) fn with_resource(mut self, resource: Option<Resource<'static>>) -> Self {
+        self.resource = resource;
+        self
+    }
+
+    /// The label keys the measurements are recorded with, including those dropped by the view.
+    pub(crate) fn label_keys(&self) -> &[String] {
+        &self.label_keys
+    }
+
+    pub(crate) fn record(&self, value: f64, label_values: SeriesKey) {
+        let label_values = match &self.kept_labels {
+            Some(kept) => kept.iter().map(|&i| label_values.get(i).cloned().unwrap_or(None)).collect(),
+            None => label_values,
+        };
+        let aggregation = &self.aggregation;
+        self.series.get_or_create(label_values, || Aggregator::new(aggregation)).record(value);
+    }
+}
+
+impl Collect for InMemoryMeasure {
+    fn descriptor(&self) -> &Descriptor {
+        &self.descriptor
+    }
+
+    /// Returns the aggregated value of the measurements recorded for each set of label values.
+    fn collect(&self) -> MetricData {
+        let time = SystemTime::now();
+        let points = self.series.snapshot()
+            .into_iter()
+            .map(|series| Point {
+                label_values: series.label_values,
+                value: series.ts.value(),
+                overflow: series.overflow,
+                start_time: series.start_time,
+                time,
+            })
+            .collect();
+        MetricData { descriptor: self.descriptor.clone(), resource: self.resource.clone(), points }
+    }
+}
+
+impl MaxTimeSeries for InMemoryMeasure {
+    fn set_max_timeseries(&mut self, max: usize) {
+        self.series.set_max_series(max);
+    }
+}
+
+/// The aggregated value of a series of an `InMemoryMeasure`.
+enum Aggregated {
+    Sum(f64),
+    LastValue(f64),
+    Histogram(Histogram),
+    Summary(SummaryAggregation),
+}
+
+/// Aggregates the values recorded for a series of an `InMemoryMeasure`.
+///
+/// Clones share the same aggregated value.
+#[derive(Clone)]
+struct Aggregator(Arc<Mutex<Aggregated>>);
+
+impl Aggregator {
+    fn new(aggregation: &Aggregation) -> Self {
+        let aggregated = match aggregation {
+            Aggregation::Sum => Aggregated::Sum(0.0),
+            Aggregation::LastValue => Aggregated::LastValue(0.0),
+            Aggregation::Histogram { boundaries } => Aggregated::Histogram(Histogram::new(boundaries.clone())),
+            Aggregation::Summary { relative_accuracy } => {
+                Aggregated::Summary(SummaryAggregation::new(*relative_accuracy))
+            }
+        };
+        Aggregator(Arc::new(Mutex::new(aggregated)))
+    }
+
+    fn record(&self, value: f64) {
+        match &mut *self.0.lock().unwrap() {
+            Aggregated::Sum(sum) => *sum += value,
+            Aggregated::LastValue(last) => *last = value,
+            Aggregated::Histogram(histogram) => histogram.record(value),
+            Aggregated::Summary(summary) => summary.record(value),
+        }
+    }
+
+    fn value(&self) -> PointValue {
+        match &*self.0.lock().unwrap() {
+            Aggregated::Sum(value) | Aggregated::LastValue(value) => PointValue::Double(*value),
+            Aggregated::Histogram(histogram) => PointValue::Histogram(histogram.clone()),
+            Aggregated::Summary(summary) => PointValue::Summary(summary.clone()),
+        }
+    }
+}
+
+impl Measure for InMemoryMeasure {
+    type Measurement = InMemoryMeasurement;
+    type Error = ();
+
+    fn double_measurement(&self, value: f64) -> Self::Measurement {
+        InMemoryMeasurement {
+            measure: self.clone(),
+            value: MeasurementValue::Double(value),
+            label_values: Vec::new(),
+        }
+    }
+
+    fn long_measurement(&self, value: i64) -> Self::Measurement {
+        InMemoryMeasurement {
+            measure: self.clone(),
+            value: MeasurementValue::Long(value),
+            label_values: Vec::new(),
+        }
+    }
+
+    fn double_measurement_with_labels(&self, value: f64, labels: &[LabelValue]) -> Self::Measurement {
+        InMemoryMeasurement {
+            measure: self.clone(),
+            value: MeasurementValue::Double(value),
+            label_values: series_key(labels),
+        }
+    }
+
+    fn build(builder: MeasureBuilder<Self>) -> Result<Self, Self::Error> {
+        Ok(InMemoryMeasure::with_view(&builder, None))
+    }
+}
+
+/// A value recorded for an `InMemoryMeasure`.
+pub struct InMemoryMeasurement {
+    measure: InMemoryMeasure,
+    value: MeasurementValue,
+    label_values: SeriesKey,
+}
+
+impl InMemoryMeasurement {
+    pub(crate) fn measure(&self) -> &InMemoryMeasure {
+        &self.measure
+    }
+
+    /// The label values the measurement was created with, none being set by default.
+    pub(crate) fn label_values(&self) -> &SeriesKey {
+        &self.label_values
+    }
+}
+
+impl Measurement for InMemoryMeasurement {
+    fn value(&self) -> MeasurementValue {
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measurement_value() {
+        let measure: InMemoryMeasure = MeasureBuilder::new("latency").build().unwrap();
+        let double = measure.double_measurement(1.5);
+        assert_eq!(double.value(), MeasurementValue::Double(1.5));
+        assert_eq!(double.measure().descriptor().name, "latency");
+        let long = measure.long_measurement(3);
+        assert_eq!(long.value(), MeasurementValue::Long(3));
+        assert_eq!(long.measure().descriptor().name, "latency");
+    }
+}
@@ -0,0 +1,643 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::{
+    any::Any,
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use opentelemetry_api::distributedcontext::DistributedContext;
+use opentelemetry_api::metric::{
+    MeasureBuilder, Measurement, Meter, MeterProvider, Metric, MetricBuilder,
+};
+use opentelemetry_api::resource::Resource;
+
+use super::{Collect, MetricData};
+use super::counter::{DoubleCounter, LongCounter};
+use super::gauge::{DoubleGauge, LongGauge};
+use super::measure::{InMemoryMeasure, InMemoryMeasurement};
+use super::series::{MaxTimeSeries, DEFAULT_MAX_TIMESERIES};
+use super::timeseries::{validate_boundaries, InvalidBoundariesError};
+use super::view::{Aggregation, View};
+
+/// A registered metric, kept both for collection and to hand it out again on re-registration.
+struct Registered {
+    metric: Box<dyn Collect>,
+    handle: Box<dyn Any + Send + Sync>,
+}
+
+type Registry = Arc<Mutex<BTreeMap<String, Registered>>>;
+
+/// A `Meter` that keeps every metric built from it in an in-memory registry keyed by full name,
+/// i.e. prefixed by the component of the metric if any.
+///
+/// Useful for tests and local debugging, `collect` returns a snapshot of every registered metric.
+/// Clones share the same registry.
+///
+/// Building a metric with the same name and `Descriptor` as a registered one returns the
+/// registered metric, so both share their `TimeSeries`. Building a metric whose name is already
+/// registered with a different `Descriptor`, e.g. another kind or unit, panics.
+///
+/// Measures are summed per set of label values, unless a `View` with their name was added first.
+///
+/// Metrics built without a `Resource` inherit the one of the meter, if set with `resource`.
+///
+/// Each metric has at most `DEFAULT_MAX_TIMESERIES` series unless set otherwise with
+/// `max_timeseries`, further label values are all recorded in an overflow series.
+#[derive(Clone)]
+pub struct InMemoryMeter {
+    registry: Registry,
+    views: Arc<Mutex<HashMap<String, View>>>,
+    context_labels: Arc<Mutex<HashSet<String>>>,
+    max_timeseries: usize,
+    resource: Option<Resource<'static>>,
+}
+
+impl InMemoryMeter {
+    pub fn new() -> Self {
+        InMemoryMeter {
+            registry: Registry::default(),
+            views: Arc::default(),
+            context_labels: Arc::default(),
+            max_timeseries: DEFAULT_MAX_TIMESERIES,
+            resource: None,
+        }
+    }
+
+    /// Sets the `Resource` of the in-process metrics built afterwards, i.e. those built without a
+    /// `Resource` of their own.
+    pub fn resource(mut self, resource: Resource<'static>) -> Self {
+        self.resource = Some(resource);
+        self
+    }
+
+    /// Sets the maximum number of series of each metric built afterwards, beyond which new label
+    /// values are recorded in the overflow series of the metric.
+    pub fn max_timeseries(mut self, max_timeseries: usize) -> Self {
+        self.max_timeseries = max_timeseries;
+        self
+    }
+
+    /// Sets how the measure with the name of the `View` aggregates its measurements, replacing
+    /// any view added before with the same name.
+    ///
+    /// Only measures built afterwards are affected.
+    ///
+    /// # Panics
+    /// * if the view has a histogram aggregation whose boundaries are invalid, see
+    ///   `try_add_view`.
+    pub fn add_view(&self, view: View) {
+        self.try_add_view(view).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Adds the `View` like `add_view`, failing if it has a histogram aggregation whose boundaries
+    /// are not strictly increasing, or one of them is NaN.
+    pub fn try_add_view(&self, view: View) -> Result<(), InvalidBoundariesError> {
+        if let Aggregation::Histogram { boundaries } = &view.aggregation {
+            validate_boundaries(boundaries)?;
+        }
+        self.views.lock().unwrap().insert(view.name.clone(), view);
+        Ok(())
+    }
+
+    /// Promotes the entries of the `DistributedContext` with the given key to label values, for
+    /// the measurements recorded with `record_with_context`.
+    pub fn add_context_label<K: Into<String>>(&self, key: K) {
+        self.context_labels.lock().unwrap().insert(key.into());
+    }
+
+    /// Records all given measurements, with label values taken from an explicit
+    /// `DistributedContext`.
+    ///
+    /// Each label key of the measure that was promoted with `add_context_label` gets the value of
+    /// the context entry with the same key. Label keys that are not promoted, or have no entry in
+    /// the context, are unset.
+    pub fn record_with_context<'c, I, C>(&mut self, measurements: I, context: &C)
+        where I: IntoIterator<Item=InMemoryMeasurement>,
+              C: DistributedContext<'c>
+    {
+        let context_labels = self.context_labels.lock().unwrap().clone();
+        for measurement in measurements {
+            let measure = measurement.measure();
+            let label_values = measure.label_keys().iter()
+                .map(|key| {
+                    if !context_labels.contains(key) {
+                        return None;
+                    }
+                    context.iter()
+                        .find(|entry| entry.key.as_str() == key)
+                        .map(|entry| entry.value.as_str().to_string())
+                })
+                .collect();
+            measure.record(measurement.value().as_f64(), label_values);
+        }
+    }
+
+    /// Returns a snapshot of all `TimeSeries` values of the registered metrics, ordered by name.
+    pub fn collect(&self) -> Vec<MetricData> {
+        self.registry.lock().unwrap()
+            .values()
+            .map(|registered| registered.metric.collect())
+            .collect()
+    }
+
+    fn register<M: Collect + Clone + MaxTimeSeries + 'static>(&self) -> impl Fn(M) -> M + Send + Sync {
+        let registry = self.registry.clone();
+        let max_timeseries = self.max_timeseries;
+        move |mut metric: M| {
+            let mut registry = registry.lock().unwrap();
+            let name = metric.descriptor().name.clone();
+            if let Some(registered) = registry.get(&name) {
+                if registered.metric.descriptor() == metric.descriptor() {
+                    // Identical descriptors imply the same kind, hence the same type
+                    return registered.handle.downcast_ref::<M>().unwrap().clone();
+                }
+                let existing = registered.metric.descriptor().clone();
+                // Release the lock first so the registry is not poisoned
+                drop(registry);
+                panic!("Metric {} is already registered as {:?}, cannot register it as {:?}",
+                       name, existing, metric.descriptor());
+            }
+
+            metric.set_max_timeseries(max_timeseries);
+            registry.insert(name, Registered {
+                metric: Box::new(metric.clone()),
+                handle: Box::new(metric.clone()),
+            });
+            metric
+        }
+    }
+}
+
+impl Meter for InMemoryMeter {
+    type CL = LongCounter;
+    type CD = DoubleCounter;
+    type GL = LongGauge;
+    type GD = DoubleGauge;
+    type Measure = InMemoryMeasure;
+
+    fn gauge_long<'a, N: Into<Cow<'a, str>>>(&mut self, name: N) -> MetricBuilder<'a, Self::GL> {
+        let register = self.register();
+        let resource = self.resource.clone();
+        MetricBuilder::new(name).factory(move |builder| {
+            Metric::build(inherit_resource(builder, &resource)).map(&register)
+        })
+    }
+
+    fn gauge_double<'a, N: Into<Cow<'a, str>>>(&mut self, name: N) -> MetricBuilder<'a, Self::GD> {
+        let register = self.register();
+        let resource = self.resource.clone();
+        MetricBuilder::new(name).factory(move |builder| {
+            Metric::build(inherit_resource(builder, &resource)).map(&register)
+        })
+    }
+
+    fn counter_double<'a, N: Into<Cow<'a, str>>>(&mut self, name: N) -> MetricBuilder<'a, Self::CD> {
+        let register = self.register();
+        let resource = self.resource.clone();
+        MetricBuilder::new(name).factory(move |builder| {
+            Metric::build(inherit_resource(builder, &resource)).map(&register)
+        })
+    }
+
+    fn counter_long<'a, N: Into<Cow<'a, str>>>(&mut self, name: N) -> MetricBuilder<'a, Self::CL> {
+        let register = self.register();
+        let resource = self.resource.clone();
+        MetricBuilder::new(name).factory(move |builder| {
+            Metric::build(inherit_resource(builder, &resource)).map(&register)
+        })
+    }
+
+    fn measure<'a, N: Into<Cow<'a, str>>>(&mut self, name: N) -> MeasureBuilder<'a, Self::Measure> {
+        let register = self.register();
+        let views = self.views.clone();
+        let resource = self.resource.clone();
+        MeasureBuilder::new(name).factory(move |builder| {
+            let measure = InMemoryMeasure::with_view(&builder, views.lock().unwrap().get(builder.name.as_ref()))
+                .with_resource(resource.clone());
+            Ok(register(measure))
+        })
+    }
+
+    /// Records the measurements against their `InMemoryMeasure`, with the label values they were
+    /// created with.
+    fn record<I>(&mut self, measurements: I) where I: IntoIterator<Item=InMemoryMeasurement> {
+        for measurement in measurements {
+            measurement.measure().record(measurement.value().as_f64(), measurement.label_values().clone());
+        }
+    }
+}
+
+/// Sets the `Resource` of the meter on a builder without one, an explicit `Resource` being kept
+/// for out-of-band metrics.
+fn inherit_resource<'a, M: Metric>(mut builder: MetricBuilder<'a, M>, resource: &Option<Resource<'static>>)
+                                   -> MetricBuilder<'a, M> {
+    if builder.resource.is_none() {
+        builder.resource = resource.clone();
+    }
+    builder
+}
+
+impl Default for InMemoryMeter {
+    fn default() -> Self {
+        InMemoryMeter::new()
+    }
+}
+
+/// Hands out clones of the `InMemoryMeter`, so all named meters share a single registry.
+impl MeterProvider for InMemoryMeter {
+    type Meter = InMemoryMeter;
+
+    fn meter(&self, _name: &str) -> InMemoryMeter {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry_api::distributedcontext::{
+        DistributedContextMap, Entry, EntryKey, EntryMetadata, EntryValue,
+    };
+    use opentelemetry_api::global;
+    use opentelemetry_api::metric::{LabelKey, LabelValue, Measure, TimeSeries};
+    use opentelemetry_api::metric::meter::NoopMeterProvider;
+    use crate::metric::{Histogram, MetricKind, PointValue};
+
+    #[test]
+    fn test_collect_counter() {
+        let mut meter = InMemoryMeter::new();
+        let counter = meter.counter_long("requests").build().unwrap();
+        counter.default_timeseries().add(3);
+        counter.default_timeseries().add(4);
+
+        let collected = meter.collect();
+        assert_eq!(collected.len(), 1);
+        assert_eq!(collected[0].descriptor.name, "requests");
+        assert_eq!(collected[0].descriptor.kind, MetricKind::CounterLong);
+        assert_eq!(collected[0].points[0].value, PointValue::Long(7));
+    }
+
+    #[test]
+    fn test_collect_all_kinds() {
+        let mut meter = InMemoryMeter::new();
+        meter.gauge_long("a").build().unwrap().default_timeseries().set(1);
+        meter.gauge_double("b").build().unwrap().default_timeseries().set(2.0);
+        meter.counter_double("c").build().unwrap().default_timeseries().add(3.0);
+        let measure = meter.measure("d").build().unwrap();
+        meter.record(vec![measure.double_measurement(2.0), measure.long_measurement(2)]);
+
+        let values: Vec<_> = meter.collect()
+            .into_iter()
+            .map(|m| (m.descriptor.name, m.points[0].value.clone()))
+            .collect();
+        assert_eq!(values, vec![
+            ("a".to_string(), PointValue::Long(1)),
+            ("b".to_string(), PointValue::Double(2.0)),
+            ("c".to_string(), PointValue::Double(3.0)),
+            ("d".to_string(), PointValue::Double(4.0)),
+        ]);
+    }
+
+    #[test]
+    fn test_collect_runs_callbacks() {
+        let mut meter = InMemoryMeter::new();
+        let gauge = meter.gauge_double("cpu").build().unwrap();
+        gauge.set_callback(|gauge: &DoubleGauge| gauge.default_timeseries().set(0.25));
+
+        let collected = meter.collect();
+        assert_eq!(collected[0].points[0].value, PointValue::Double(0.25));
+    }
+
+    #[test]
+    fn test_record_value() {
+        let mut meter = InMemoryMeter::new();
+        let measure = meter.measure("latency").unit("ms").build().unwrap();
+        let get = LabelValue::new("GET");
+        let put = LabelValue::new("PUT");
+        meter.record_value(&measure, 2.0, &[get]);
+        meter.record_value(&measure, 3.0, &[put]);
+        meter.record_value(&measure, 5.0, &[LabelValue::new("GET")]);
+        meter.record(vec![measure.double_measurement_with_labels(1.0, &[LabelValue::new("PUT")])]);
+
+        let collected = meter.collect();
+        assert_eq!(collected.len(), 1);
+        assert_eq!(collected[0].descriptor.unit, "ms");
+        let mut points: Vec<_> = collected[0].points.iter()
+            .map(|p| (p.label_values.clone(), p.value.clone()))
+            .collect();
+        points.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(points, vec![
+            (vec![Some("GET".to_string())], PointValue::Double(7.0)),
+            (vec![Some("PUT".to_string())], PointValue::Double(4.0)),
+        ]);
+    }
+
+    #[test]
+    fn test_record_double_and_long() {
+        let mut meter = InMemoryMeter::new();
+        meter.add_view(View::new("queue_size", Aggregation::LastValue));
+        let latency = meter.measure("latency").build().unwrap();
+        let queue_size = meter.measure("queue_size").build().unwrap();
+        meter.record_double(&latency, 1.5);
+        meter.record_long(&latency, 2);
+        meter.record_long(&queue_size, 7);
+        meter.record_double(&queue_size, 3.0);
+
+        let values: Vec<_> = meter.collect()
+            .into_iter()
+            .map(|m| (m.descriptor.name, m.points[0].value.clone()))
+            .collect();
+        assert_eq!(values, vec![
+            ("latency".to_string(), PointValue::Double(3.5)),
+            ("queue_size".to_string(), PointValue::Double(3.0)),
+        ]);
+    }
+
+    fn label_key(key: &'static str) -> LabelKey<'static> {
+        LabelKey { key: key.into(), description: "".into() }
+    }
+
+    #[test]
+    fn test_resource_inherited() {
+        let resource = Resource::service("checkout", "1.0.0");
+        let mut meter = InMemoryMeter::new().resource(resource.clone());
+        let explicit = Resource::empty().with_label("host.name", "batch-01");
+        meter.counter_long("in_process").build().unwrap();
+        meter.gauge_double("out_of_band").resource(explicit.clone()).build().unwrap();
+        meter.measure("latency").build().unwrap();
+
+        let resources: Vec<_> = meter.collect()
+            .into_iter()
+            .map(|m| (m.descriptor.name, m.resource))
+            .collect();
+        assert_eq!(resources, vec![
+            ("in_process".to_string(), Some(resource.clone())),
+            ("latency".to_string(), Some(resource)),
+            ("out_of_band".to_string(), Some(explicit)),
+        ]);
+
+        let mut meter = InMemoryMeter::new();
+        meter.counter_long("requests").build().unwrap();
+        assert_eq!(meter.collect()[0].resource, None);
+    }
+
+    #[test]
+    fn test_max_timeseries() {
+        let mut meter = InMemoryMeter::new().max_timeseries(3);
+        let counter = meter.counter_long("requests").label_keys(vec![label_key("user")]).build().unwrap();
+        for user in 0..10 {
+            counter.timeseries(vec![LabelValue::new(user.to_string())]).add(user);
+        }
+
+        let points = &meter.collect()[0].points;
+        assert_eq!(points.len(), 4);
+        let (overflow, series): (Vec<_>, Vec<_>) = points.iter().partition(|p| p.overflow);
+        assert_eq!(series.len(), 3);
+        assert!(series.iter().all(|p| p.label_values[0].is_some()));
+        assert_eq!(overflow[0].label_values, vec![None]);
+
+        let total: i64 = series.iter()
+            .map(|p| match p.value {
+                PointValue::Long(v) => v,
+                _ => panic!("expected a long"),
+            })
+            .sum();
+        assert_eq!(overflow[0].value, PointValue::Long(45 - total));
+    }
+
+    #[test]
+    fn test_max_timeseries_measure() {
+        let mut meter = InMemoryMeter::new().max_timeseries(1);
+        let measure = meter.measure("latency").label_keys(vec![label_key("path")]).build().unwrap();
+        meter.record_value(&measure, 1.0, &[LabelValue::new("/a")]);
+        meter.record_value(&measure, 2.0, &[LabelValue::new("/b")]);
+        meter.record_value(&measure, 3.0, &[LabelValue::new("/c")]);
+
+        let points = &meter.collect()[0].points;
+        assert_eq!(points.len(), 2);
+        assert!(!points[0].overflow);
+        assert_eq!(points[0].value, PointValue::Double(1.0));
+        assert!(points[1].overflow);
+        assert_eq!(points[1].value, PointValue::Double(5.0));
+    }
+
+    fn points(meter: &InMemoryMeter) -> Vec<(Vec<Option<String>>, PointValue)> {
+        let mut points: Vec<_> = meter.collect()[0].points.iter()
+            .map(|p| (p.label_values.clone(), p.value.clone()))
+            .collect();
+        points.sort_by(|a, b| a.0.cmp(&b.0));
+        points
+    }
+
+    #[test]
+    fn test_sum_view() {
+        let mut meter = InMemoryMeter::new();
+        meter.add_view(View::new("latency", Aggregation::Sum));
+        let measure = meter.measure("latency").build().unwrap();
+        meter.record(vec![measure.double_measurement(2.5), measure.long_measurement(3)]);
+        meter.record(vec![measure.double_measurement(1.0)]);
+
+        assert_eq!(points(&meter), vec![(vec![], PointValue::Double(6.5))]);
+    }
+
+    #[test]
+    fn test_last_value_view() {
+        let mut meter = InMemoryMeter::new();
+        meter.add_view(View::new("queue_size", Aggregation::LastValue));
+        let measure = meter.measure("queue_size").build().unwrap();
+        meter.record(vec![measure.long_measurement(3), measure.long_measurement(7)]);
+        meter.record(vec![measure.long_measurement(5)]);
+
+        assert_eq!(points(&meter), vec![(vec![], PointValue::Double(5.0))]);
+    }
+
+    #[test]
+    fn test_histogram_view() {
+        let mut meter = InMemoryMeter::new();
+        meter.add_view(View::new("latency", Aggregation::Histogram { boundaries: vec![1.0, 10.0] }));
+        let measure = meter.measure("latency").build().unwrap();
+        meter.record(vec![0.5, 1.0, 5.0, 20.0, 30.0].into_iter().map(|v| measure.double_measurement(v)));
+
+        assert_eq!(points(&meter), vec![(vec![], PointValue::Histogram(Histogram {
+            boundaries: vec![1.0, 10.0],
+            bucket_counts: vec![2, 1, 2],
+            count: 5,
+            sum: 56.5,
+        }))]);
+    }
+
+    #[test]
+    fn test_invalid_histogram_view() {
+        let meter = InMemoryMeter::new();
+        for boundaries in &[vec![10.0, 1.0], vec![1.0, 1.0], vec![1.0, f64::NAN]] {
+            let view = View::new("latency", Aggregation::Histogram { boundaries: boundaries.clone() });
+            assert!(meter.try_add_view(view).is_err());
+        }
+        assert!(meter.views.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_summary_view() {
+        let mut meter = InMemoryMeter::new();
+        meter.add_view(View::new("latency", Aggregation::Summary { relative_accuracy: 0.01 }));
+        let measure = meter.measure("latency").build().unwrap();
+        meter.record((1..=100).map(|v| measure.long_measurement(v)));
+
+        match &meter.collect()[0].points[0].value {
+            PointValue::Summary(summary) => {
+                assert_eq!(summary.count(), 100);
+                assert!((summary.quantile(0.99) - 99.0).abs() <= 0.99);
+            }
+            value => panic!("expected a summary, got {:?}", value),
+        }
+    }
+
+    #[test]
+    fn test_view_label_keys() {
+        let mut meter = InMemoryMeter::new();
+        meter.add_view(View::new("latency", Aggregation::Sum).label_keys(vec!["method", "missing"]));
+        let measure = meter.measure("latency")
+            .label_keys(vec![label_key("host"), label_key("method")])
+            .build()
+            .unwrap();
+        meter.record_value(&measure, 2.0, &[LabelValue::new("a"), LabelValue::new("GET")]);
+        meter.record_value(&measure, 3.0, &[LabelValue::new("b"), LabelValue::new("GET")]);
+        meter.record_value(&measure, 4.0, &[LabelValue::new("a"), LabelValue::new("PUT")]);
+
+        assert_eq!(meter.collect()[0].descriptor.label_keys, vec!["method".to_string()]);
+        assert_eq!(points(&meter), vec![
+            (vec![Some("GET".to_string())], PointValue::Double(5.0)),
+            (vec![Some("PUT".to_string())], PointValue::Double(4.0)),
+        ]);
+    }
+
+    #[test]
+    fn test_view_of_other_measure() {
+        let mut meter = InMemoryMeter::new();
+        meter.add_view(View::new("queue_size", Aggregation::LastValue));
+        let measure = meter.measure("latency").build().unwrap();
+        meter.record(vec![measure.long_measurement(3), measure.long_measurement(7)]);
+
+        assert_eq!(points(&meter), vec![(vec![], PointValue::Double(10.0))]);
+    }
+
+    #[test]
+    fn test_record_with_context() {
+        let mut meter = InMemoryMeter::new();
+        meter.add_context_label("tenant");
+        let measure = meter.measure("latency")
+            .label_keys(vec![label_key("tenant"), label_key("user")])
+            .build()
+            .unwrap();
+
+        let mut context = DistributedContextMap::new();
+        for (key, value) in &[("tenant", "acme"), ("user", "alice")] {
+            context.insert(Entry::new(EntryKey::new(*key), EntryValue::new(*value), EntryMetadata::default()));
+        }
+        meter.record_with_context(vec![measure.double_measurement(2.0)], &&context);
+        meter.record_with_context(vec![measure.double_measurement(3.0)], &&context);
+        meter.record_with_context(vec![measure.double_measurement(5.0)], &&DistributedContextMap::new());
+
+        assert_eq!(points(&meter), vec![
+            (vec![None, None], PointValue::Double(5.0)),
+            (vec![Some("acme".to_string()), None], PointValue::Double(5.0)),
+        ]);
+    }
+
+    #[test]
+    fn test_registry_keys_on_full_name() {
+        let mut meter = InMemoryMeter::new();
+        let plain = meter.counter_long("requests").build().unwrap();
+        let http = meter.counter_long("requests").component("http").build().unwrap();
+        plain.default_timeseries().add(1);
+        http.default_timeseries().add(2);
+
+        let collected = meter.collect();
+        assert_eq!(collected.len(), 2);
+        assert_eq!(collected[0].descriptor.name, "http_requests");
+        assert_eq!(collected[0].points[0].value, PointValue::Long(2));
+        assert_eq!(collected[1].descriptor.name, "requests");
+        assert_eq!(collected[1].points[0].value, PointValue::Long(1));
+    }
+
+    #[test]
+    fn test_idempotent_registration() {
+        let mut meter = InMemoryMeter::new();
+        let first = meter.counter_long("requests").unit("1").build().unwrap();
+        let second = meter.counter_long("requests").unit("1").build().unwrap();
+        first.default_timeseries().add(1);
+        second.default_timeseries().add(2);
+
+        let collected = meter.collect();
+        assert_eq!(collected.len(), 1);
+        assert_eq!(collected[0].points[0].value, PointValue::Long(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Metric requests is already registered")]
+    fn test_conflicting_kind_panics() {
+        let mut meter = InMemoryMeter::new();
+        meter.counter_long("requests").build().unwrap();
+        meter.gauge_long("requests").build().unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Metric requests is already registered")]
+    fn test_conflicting_unit_panics() {
+        let mut meter = InMemoryMeter::new();
+        meter.counter_long("requests").unit("1").build().unwrap();
+        meter.counter_long("requests").unit("By").build().unwrap();
+    }
+
+    #[test]
+    fn test_metrics_built_elsewhere_are_not_registered() {
+        let meter = InMemoryMeter::new();
+        let _counter: LongCounter = MetricBuilder::new("requests").build().unwrap();
+        assert!(meter.collect().is_empty());
+    }
+
+    /// Restores the no-op global `MeterProvider` when dropped.
+    struct ResetGlobalMeter;
+
+    impl Drop for ResetGlobalMeter {
+        fn drop(&mut self) {
+            global::set_meter_provider(NoopMeterProvider);
+        }
+    }
+
+    #[test]
+    fn test_global_meter_provider() {
+        let meter = InMemoryMeter::new();
+        let _reset = ResetGlobalMeter;
+        global::set_meter_provider(meter.clone());
+
+        let mut global_meter = global::meter("test");
+        let counter = global_meter.counter_long("requests").unit("1").build().unwrap();
+        counter.default_timeseries().add(2);
+        let measure = global_meter.measure("latency").unit("ms").build().unwrap();
+        global_meter.record_value(&measure, 3.0, &[]);
+
+        let collected = meter.collect();
+        assert_eq!(collected.len(), 2);
+        assert_eq!(collected[0].descriptor.name, "latency");
+        assert_eq!(collected[0].descriptor.unit, "ms");
+        assert_eq!(collected[0].points[0].value, PointValue::Double(3.0));
+        assert_eq!(collected[1].descriptor.kind, MetricKind::CounterLong);
+        assert_eq!(collected[1].points[0].value, PointValue::Long(2));
+    }
+}
@@ -0,0 +1,53 @@
+use opentelemetry_api::metric::LabelValue;
+
+mod callback;
+mod series;
+pub use series::DEFAULT_MAX_TIMESERIES;
+
+pub mod data;
+pub use data::{Descriptor, Histogram, MetricData, MetricKind, Point, PointValue, OVERFLOW_LABEL_KEY};
+
+pub mod timeseries;
+pub use timeseries::{AtomicDoubleTimeSeries, AtomicLongTimeSeries, HistogramTimeSeries, InvalidBoundariesError};
+
+pub mod counter;
+pub use counter::{DoubleCounter, LongCounter};
+
+pub mod gauge;
+pub use gauge::{DoubleGauge, DoubleGaugeTimeSeries, LongGauge, LongGaugeTimeSeries};
+
+pub mod summary;
+pub use summary::SummaryAggregation;
+
+pub mod view;
+pub use view::{Aggregation, View};
+
+pub mod measure;
+pub use measure::{InMemoryMeasure, InMemoryMeasurement};
+
+pub mod meter;
+pub use meter::InMemoryMeter;
+
+pub mod sanitize;
+pub use sanitize::{sanitize_label_key, sanitize_name};
+
+pub mod prometheus;
+pub use prometheus::PrometheusExporter;
+
+/// A metric whose current values can be snapshotted for export.
+pub trait Collect: Send + Sync {
+    /// Returns the `Descriptor` of this metric.
+    fn descriptor(&self) -> &Descriptor;
+
+    /// Returns the current value of every `TimeSeries` of this metric.
+    fn collect(&self) -> MetricData;
+}
+
+/// Key identifying a `TimeSeries` within a metric, labels without a value are `None`.
+pub(crate) type SeriesKey = Vec<Option<String>>;
+
+pub(crate) fn series_key(label_values: &[LabelValue]) -> SeriesKey {
+    label_values.iter()
+        .map(|v| if v.has_value { Some(v.value.to_string()) } else { None })
+        .collect()
+}
@@ -0,0 +1,234 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//!
+//! Prometheus text exposition format of collected metrics, to be served to a Prometheus scraper.
+//!
+//! See <https://prometheus.io/docs/instrumenting/exposition_formats/#text-based-format>
+use std::fmt::{self, Write};
+
+use super::{Descriptor, Histogram, MetricData, MetricKind, Point, PointValue, SummaryAggregation, OVERFLOW_LABEL_KEY};
+use super::sanitize::{sanitize_label_key, sanitize_name};
+
+/// The `Content-Type` of the exported text.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// The quantiles written for metrics aggregated as a summary.
+const SUMMARY_QUANTILES: [f64; 3] = [0.5, 0.9, 0.99];
+
+/// Renders collected metrics, e.g. from `InMemoryMeter::collect`, in the Prometheus text format.
+///
+/// Counters are written as `counter`s and gauges as `gauge`s. Measures are written as `histogram`s
+/// or `summary`s when aggregated as such, and as `untyped` otherwise, since the other aggregations
+/// are not recorded in the collected metrics.
+///
+/// Metric names and label keys are sanitized with `sanitize_name` and `sanitize_label_key`, unset
+/// labels are omitted, and the points of a metric are ordered by label values, the overflow series
+/// last.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct PrometheusExporter;
+
+impl PrometheusExporter {
+    pub fn new() -> Self {
+        PrometheusExporter
+    }
+
+    /// Returns the metrics in the Prometheus text format.
+    pub fn export(&self, metrics: &[MetricData]) -> String {
+        let mut out = String::new();
+        self.write(metrics, &mut out).expect("writing to a String cannot fail");
+        out
+    }
+
+    /// Writes the metrics in the Prometheus text format.
+    pub fn write<W: Write>(&self, metrics: &[MetricData], out: &mut W) -> fmt::Result {
+        for metric in metrics {
+            write_metric(metric, out)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_metric<W: Write>(metric: &MetricData, out: &mut W) -> fmt::Result {
+    let descriptor = &metric.descriptor;
+    let name = sanitize_name(&descriptor.name);
+    if !descriptor.description.is_empty() {
+        writeln!(out, "# HELP {} {}", name, escape_help(&descriptor.description))?;
+    }
+    writeln!(out, "# TYPE {} {}", name, metric_type(metric))?;
+
+    let mut points: Vec<_> = metric.points.iter().collect();
+    points.sort_by(|a, b| (a.overflow, &a.label_values).cmp(&(b.overflow, &b.label_values)));
+    for point in points {
+        let labels = labels(descriptor, point);
+        match &point.value {
+            PointValue::Long(value) => writeln!(out, "{}{} {}", name, format_labels(&labels), value)?,
+            PointValue::Double(value) => {
+                writeln!(out, "{}{} {}", name, format_labels(&labels), format_float(*value))?
+            }
+            PointValue::Histogram(histogram) => write_histogram(&name, labels, histogram, out)?,
+            PointValue::Summary(summary) => write_summary(&name, labels, summary, out)?,
+        }
+    }
+    Ok(())
+}
+
+fn metric_type(metric: &MetricData) -> &'static str {
+    match metric.descriptor.kind {
+        MetricKind::CounterLong | MetricKind::CounterDouble => "counter",
+        MetricKind::GaugeLong | MetricKind::GaugeDouble => "gauge",
+        MetricKind::Measure => match metric.points.first().map(|point| &point.value) {
+            Some(PointValue::Histogram(_)) => "histogram",
+            Some(PointValue::Summary(_)) => "summary",
+            _ => "untyped",
+        },
+    }
+}
+
+fn write_histogram<W: Write>(name: &str, labels: Vec<(String, String)>, histogram: &Histogram,
+                             out: &mut W) -> fmt::Result {
+    let mut cumulative_count = 0;
+    let upper_bounds = histogram.boundaries.iter().cloned().chain(Some(f64::INFINITY));
+    for (upper_bound, count) in upper_bounds.zip(&histogram.bucket_counts) {
+        cumulative_count += count;
+        let mut bucket_labels = labels.clone();
+        bucket_labels.push(("le".to_string(), format_float(upper_bound)));
+        writeln!(out, "{}_bucket{} {}", name, format_labels(&bucket_labels), cumulative_count)?;
+    }
+    writeln!(out, "{}_sum{} {}", name, format_labels(&labels), format_float(histogram.sum))?;
+    writeln!(out, "{}_count{} {}", name, format_labels(&labels), histogram.count)
+}
+
+fn write_summary<W: Write>(name: &str, labels: Vec<(String, String)>, summary: &SummaryAggregation,
+                           out: &mut W) -> fmt::Result {
+    for &quantile in &SUMMARY_QUANTILES {
+        let mut quantile_labels = labels.clone();
+        quantile_labels.push(("quantile".to_string(), format_float(quantile)));
+        writeln!(out, "{}{} {}", name, format_labels(&quantile_labels), format_float(summary.quantile(quantile)))?;
+    }
+    writeln!(out, "{}_sum{} {}", name, format_labels(&labels), format_float(summary.sum()))?;
+    writeln!(out, "{}_count{} {}", name, format_labels(&labels), summary.count())
+}
+
+/// The set labels of the point as sanitized keys and values, along with the overflow label.
+fn labels(descriptor: &Descriptor, point: &Point) -> Vec<(String, String)> {
+    let mut labels: Vec<_> = descriptor.label_keys.iter()
+        .zip(&point.label_values)
+        .filter_map(|(key, value)| value.as_ref().map(|value| (sanitize_label_key(key), value.clone())))
+        .collect();
+    if point.overflow {
+        labels.push((OVERFLOW_LABEL_KEY.to_string(), "true".to_string()));
+    }
+    labels
+}
+
+fn format_labels(labels: &[(String, String)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let labels: Vec<_> = labels.iter()
+        .map(|(key, value)| format!("{}=\"{}\"", key, escape_label_value(value)))
+        .collect();
+    format!("{{{}}}", labels.join(","))
+}
+
+/// Formats a sample value, or a `le` or `quantile` label value, as Prometheus parses them.
+fn format_float(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Escapes backslashes and line feeds, as required in `# HELP` lines.
+fn escape_help(help: &str) -> String {
+    help.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// Escapes backslashes, double quotes and line feeds, as required in label values.
+fn escape_label_value(value: &str) -> String {
+    escape_help(value).replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry_api::metric::{LabelKey, LabelValue, Meter, Metric, TimeSeries};
+    use crate::metric::{Aggregation, InMemoryMeter, View};
+
+    const GOLDEN: &str = include_str!("testdata/prometheus.txt");
+
+    fn label_key(key: &'static str) -> LabelKey<'static> {
+        LabelKey { key: key.into(), description: "".into() }
+    }
+
+    #[test]
+    fn test_golden_output() {
+        let mut meter = InMemoryMeter::new();
+        meter.add_view(View::new("http.server.duration", Aggregation::Histogram { boundaries: vec![0.1, 0.5] }));
+
+        let requests = meter.counter_long("http.requests")
+            .description("Number of HTTP requests.\nBy method and path.")
+            .label_keys(vec![label_key("http.method"), label_key("http.path")])
+            .build()
+            .unwrap();
+        requests.timeseries(vec![LabelValue::new("GET"), LabelValue::new("/users")]).add(3);
+        requests.timeseries(vec![LabelValue::new("POST"), LabelValue::new("/say \"hi\"\\\n")]).add(1);
+        requests.timeseries(vec![LabelValue::new("GET"), LabelValue::empty()]).add(2);
+
+        let duration = meter.measure("http.server.duration")
+            .label_keys(vec![label_key("http.method")])
+            .build()
+            .unwrap();
+        for &value in &[0.05, 0.3, 0.4, 2.0] {
+            meter.record_value(&duration, value, &[LabelValue::new("GET")]);
+        }
+
+        assert_eq!(PrometheusExporter::new().export(&meter.collect()), GOLDEN);
+    }
+
+    #[test]
+    fn test_types() {
+        let mut meter = InMemoryMeter::new().max_timeseries(1);
+        meter.add_view(View::new("latency", Aggregation::Summary { relative_accuracy: 0.01 }));
+        let gauge = meter.gauge_double("cpu").label_keys(vec![label_key("core")]).build().unwrap();
+        gauge.timeseries(vec![LabelValue::new("0")]).set(0.5);
+        gauge.timeseries(vec![LabelValue::new("1")]).set(f64::INFINITY);
+        let latency = meter.measure("latency").build().unwrap();
+        meter.record_double(&latency, 2.0);
+        let queue = meter.measure("queue").build().unwrap();
+        meter.record_long(&queue, 3);
+
+        assert_eq!(PrometheusExporter::new().export(&meter.collect()), "\
+# TYPE cpu gauge
+cpu{core=\"0\"} 0.5
+cpu{otel_overflow=\"true\"} +Inf
+# TYPE latency summary
+latency{quantile=\"0.5\"} 2
+latency{quantile=\"0.9\"} 2
+latency{quantile=\"0.99\"} 2
+latency_sum 2
+latency_count 1
+# TYPE queue untyped
+queue 3
+");
+    }
+}
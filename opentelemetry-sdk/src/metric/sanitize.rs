@@ -0,0 +1,65 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//!
+//! Sanitization of metric names and label keys into identifiers valid for most backends.
+//!
+//! These follow the Prometheus rules, the strictest of the supported formats: metric names match
+//! `[a-zA-Z_:][a-zA-Z0-9_:]*` and label keys match `[a-zA-Z_][a-zA-Z0-9_]*`.
+
+/// Replaces the characters not allowed in a metric name with `_`, and prefixes names that are
+/// empty or start with a digit with `_`, e.g. `http.server.duration` becomes
+/// `http_server_duration`.
+pub fn sanitize_name(name: &str) -> String {
+    sanitize(name, |c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+}
+
+/// Replaces the characters not allowed in a label key with `_`, and prefixes keys that are empty
+/// or start with a digit with `_`, e.g. `http.method` becomes `http_method`.
+pub fn sanitize_label_key(key: &str) -> String {
+    sanitize(key, |c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn sanitize<F: Fn(char) -> bool>(name: &str, is_valid: F) -> String {
+    let mut sanitized: String = name.chars().map(|c| if is_valid(c) { c } else { '_' }).collect();
+    if sanitized.is_empty() || sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+        sanitized.insert(0, '_');
+    }
+    sanitized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_name() {
+        assert_eq!(sanitize_name("http.server.duration"), "http_server_duration");
+        assert_eq!(sanitize_name("process:cpu-seconds"), "process:cpu_seconds");
+        assert_eq!(sanitize_name("2xx_responses"), "_2xx_responses");
+        assert_eq!(sanitize_name("répertoire"), "r_pertoire");
+        assert_eq!(sanitize_name(""), "_");
+        assert_eq!(sanitize_name("already_valid"), "already_valid");
+    }
+
+    #[test]
+    fn test_sanitize_label_key() {
+        assert_eq!(sanitize_label_key("http.method"), "http_method");
+        assert_eq!(sanitize_label_key("http:method"), "http_method");
+        assert_eq!(sanitize_label_key("0xff"), "_0xff");
+        assert_eq!(sanitize_label_key(""), "_");
+    }
+}
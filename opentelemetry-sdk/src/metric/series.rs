@@ -0,0 +1,171 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use opentelemetry_api::metric::{LabelArityError, LabelValue};
+
+use super::{series_key, SeriesKey};
+
+/// The default maximum number of `TimeSeries` of a metric, beyond which new label values are
+/// recorded in the overflow series.
+pub const DEFAULT_MAX_TIMESERIES: usize = 2000;
+
+/// The `TimeSeries` handles of a metric, along with the time each series was created.
+///
+/// Once `max_series` series exist, new label values all share a single overflow series, so
+/// unbounded label values cannot grow the metric without bounds.
+///
+/// Clones share the same series.
+#[derive(Clone)]
+pub(crate) struct SeriesMap<TS> {
+    label_count: usize,
+    max_series: usize,
+    series: Arc<Mutex<Series<TS>>>,
+}
+
+struct Series<TS> {
+    by_key: HashMap<SeriesKey, (TS, SystemTime)>,
+    overflow: Option<(TS, SystemTime)>,
+}
+
+/// A series returned by `SeriesMap::snapshot`.
+pub(crate) struct SeriesSnapshot<TS> {
+    /// The label values of the series, all unset for the overflow series.
+    pub label_values: SeriesKey,
+    pub ts: TS,
+    pub start_time: SystemTime,
+    pub overflow: bool,
+}
+
+/// A metric whose number of `TimeSeries` can be limited.
+pub(crate) trait MaxTimeSeries {
+    /// Sets the number of series beyond which new label values are recorded in the overflow
+    /// series, without affecting the existing series.
+    fn set_max_timeseries(&mut self, max: usize);
+}
+
+impl <TS: Clone> SeriesMap<TS> {
+    pub fn new(label_count: usize) -> Self {
+        SeriesMap {
+            label_count,
+            max_series: DEFAULT_MAX_TIMESERIES,
+            series: Arc::new(Mutex::new(Series { by_key: HashMap::new(), overflow: None })),
+        }
+    }
+
+    pub fn set_max_series(&mut self, max_series: usize) {
+        self.max_series = max_series;
+    }
+
+    /// Returns the series with the given key, creating it if needed, or the overflow series if
+    /// there is no such series and the maximum number of series is reached.
+    pub fn get_or_create<F: FnOnce() -> TS>(&self, key: SeriesKey, create: F) -> TS {
+        let mut series = self.series.lock().unwrap();
+        if let Some((ts, _)) = series.by_key.get(&key) {
+            return ts.clone();
+        }
+        if series.by_key.len() >= self.max_series {
+            return series.overflow.get_or_insert_with(|| (create(), SystemTime::now())).0.clone();
+        }
+        series.by_key.entry(key)
+            .or_insert_with(|| (create(), SystemTime::now()))
+            .0
+            .clone()
+    }
+
+    /// Key of the series with the given label values, which must match the number of label keys.
+    pub fn key(&self, label_values: &[LabelValue]) -> Result<SeriesKey, LabelArityError> {
+        if label_values.len() != self.label_count {
+            return Err(LabelArityError { expected: self.label_count, actual: label_values.len() });
+        }
+        Ok(series_key(label_values))
+    }
+
+    /// Key of the series with all labels unset.
+    pub fn default_key(&self) -> SeriesKey {
+        vec![None; self.label_count]
+    }
+
+    pub fn remove(&self, key: &SeriesKey) {
+        self.series.lock().unwrap().by_key.remove(key);
+    }
+
+    /// Removes every series, including the overflow series.
+    pub fn clear(&self) {
+        let mut series = self.series.lock().unwrap();
+        series.by_key.clear();
+        series.overflow = None;
+    }
+
+    /// Returns every series along with its start time, the overflow series last if any.
+    pub fn snapshot(&self) -> Vec<SeriesSnapshot<TS>> {
+        let series = self.series.lock().unwrap();
+        let overflow = series.overflow.iter().map(|(ts, start_time)| SeriesSnapshot {
+            label_values: self.default_key(),
+            ts: ts.clone(),
+            start_time: *start_time,
+            overflow: true,
+        });
+        series.by_key
+            .iter()
+            .map(|(key, (ts, start_time))| SeriesSnapshot {
+                label_values: key.clone(),
+                ts: ts.clone(),
+                start_time: *start_time,
+                overflow: false,
+            })
+            .chain(overflow)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(value: &str) -> SeriesKey {
+        vec![Some(value.to_string())]
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut series = SeriesMap::new(1);
+        series.set_max_series(2);
+        assert_eq!(series.get_or_create(key("a"), || 1), 1);
+        assert_eq!(series.get_or_create(key("b"), || 2), 2);
+        assert_eq!(series.get_or_create(key("c"), || 3), 3);
+        assert_eq!(series.get_or_create(key("d"), || 4), 3);
+        assert_eq!(series.get_or_create(key("a"), || 5), 1);
+
+        let mut snapshot = series.snapshot();
+        assert_eq!(snapshot.len(), 3);
+        let overflow = snapshot.pop().unwrap();
+        assert!(overflow.overflow);
+        assert_eq!(overflow.label_values, vec![None]);
+        assert_eq!(overflow.ts, 3);
+        assert!(snapshot.iter().all(|series| !series.overflow));
+
+        series.remove(&key("a"));
+        assert_eq!(series.get_or_create(key("e"), || 6), 6);
+        series.clear();
+        assert!(series.snapshot().is_empty());
+    }
+}
@@ -0,0 +1,220 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::BTreeMap;
+
+/// Relative accuracy of the quantiles of a `SummaryAggregation` built with `Default`.
+pub const DEFAULT_RELATIVE_ACCURACY: f64 = 0.01;
+
+/// Values closer to zero than this are counted as zero.
+const MIN_INDEXABLE_VALUE: f64 = 1e-9;
+
+/// Approximate distribution of the values recorded for a `Measure`, to estimate any quantile
+/// without choosing bucket boundaries upfront.
+///
+/// This is a DDSketch: values are counted in buckets whose bounds grow exponentially, so that every
+/// quantile is estimated within the relative accuracy of the true value, using memory logarithmic
+/// in the range of the values.
+///
+/// See <https://arxiv.org/abs/1908.10693>
+#[derive(Clone, PartialEq, Debug)]
+pub struct SummaryAggregation {
+    relative_accuracy: f64,
+    /// Natural logarithm of the ratio between the bounds of a bucket.
+    gamma_ln: f64,
+    positive: BTreeMap<i32, u64>,
+    negative: BTreeMap<i32, u64>,
+    zero_count: u64,
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl SummaryAggregation {
+    /// Creates an empty summary whose quantiles are within `relative_accuracy` of the true values.
+    ///
+    /// # Panics
+    /// * if `relative_accuracy` is not strictly between 0 and 1.
+    pub fn new(relative_accuracy: f64) -> Self {
+        assert!(relative_accuracy > 0.0 && relative_accuracy < 1.0,
+                "relative accuracy must be between 0 and 1, got {}", relative_accuracy);
+        let gamma = (1.0 + relative_accuracy) / (1.0 - relative_accuracy);
+        SummaryAggregation {
+            relative_accuracy,
+            gamma_ln: gamma.ln(),
+            positive: BTreeMap::new(),
+            negative: BTreeMap::new(),
+            zero_count: 0,
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    pub fn relative_accuracy(&self) -> f64 {
+        self.relative_accuracy
+    }
+
+    /// Records the value, NaN values are ignored.
+    pub fn record(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        if value > MIN_INDEXABLE_VALUE {
+            *self.positive.entry(self.index(value)).or_insert(0) += 1;
+        } else if value < -MIN_INDEXABLE_VALUE {
+            *self.negative.entry(self.index(-value)).or_insert(0) += 1;
+        } else {
+            self.zero_count += 1;
+        }
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Returns the estimated value below which a fraction `q` of the recorded values fall, e.g.
+    /// `quantile(0.99)` for the 99th percentile.
+    ///
+    /// `q` is clamped between 0 and 1, and NaN is returned if no value was recorded.
+    pub fn quantile(&self, q: f64) -> f64 {
+        if self.count == 0 {
+            return f64::NAN;
+        }
+        let rank = (q.clamp(0.0, 1.0) * (self.count - 1) as f64) as u64;
+
+        let mut seen = 0;
+        for (&index, &count) in self.negative.iter().rev() {
+            seen += count;
+            if seen > rank {
+                return self.clamp(-self.value(index));
+            }
+        }
+        seen += self.zero_count;
+        if seen > rank {
+            return 0.0;
+        }
+        for (&index, &count) in &self.positive {
+            seen += count;
+            if seen > rank {
+                return self.clamp(self.value(index));
+            }
+        }
+        self.max
+    }
+
+    /// Returns the number of values recorded.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the sum of the values recorded.
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// Index of the bucket `(gamma^(index - 1), gamma^index]` holding the positive value.
+    fn index(&self, value: f64) -> i32 {
+        (value.ln() / self.gamma_ln).ceil() as i32
+    }
+
+    /// The value of the bucket with the given index, within the relative accuracy of its bounds.
+    fn value(&self, index: i32) -> f64 {
+        let gamma = self.gamma_ln.exp();
+        2.0 * (index as f64 * self.gamma_ln).exp() / (1.0 + gamma)
+    }
+
+    /// Estimates can not be outside of the range of the recorded values.
+    fn clamp(&self, value: f64) -> f64 {
+        value.max(self.min).min(self.max)
+    }
+}
+
+impl Default for SummaryAggregation {
+    fn default() -> Self {
+        SummaryAggregation::new(DEFAULT_RELATIVE_ACCURACY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(estimate: f64, expected: f64, relative_accuracy: f64) {
+        assert!((estimate - expected).abs() <= expected.abs() * relative_accuracy,
+                "{} is not within {} of {}", estimate, relative_accuracy, expected);
+    }
+
+    #[test]
+    fn test_uniform_quantiles() {
+        let mut summary = SummaryAggregation::default();
+        for value in 1..=1000 {
+            summary.record(value as f64);
+        }
+
+        assert_eq!(summary.count(), 1000);
+        assert_eq!(summary.sum(), 500_500.0);
+        assert_close(summary.quantile(0.5), 500.0, DEFAULT_RELATIVE_ACCURACY);
+        assert_close(summary.quantile(0.95), 950.0, DEFAULT_RELATIVE_ACCURACY);
+        assert_close(summary.quantile(0.99), 990.0, DEFAULT_RELATIVE_ACCURACY);
+        assert_close(summary.quantile(0.0), 1.0, DEFAULT_RELATIVE_ACCURACY);
+        assert_close(summary.quantile(1.0), 1000.0, DEFAULT_RELATIVE_ACCURACY);
+    }
+
+    #[test]
+    fn test_exponential_quantiles() {
+        // Inverse of the CDF of the exponential distribution of mean 100, sampled evenly
+        let quantile = |q: f64| -100.0 * (1.0 - q).ln();
+        let mut summary = SummaryAggregation::new(0.02);
+        for i in 0..10_000 {
+            summary.record(quantile((i as f64 + 0.5) / 10_000.0));
+        }
+
+        for &q in &[0.5, 0.95, 0.99] {
+            assert_close(summary.quantile(q), quantile(q), 0.03);
+        }
+    }
+
+    #[test]
+    fn test_negative_and_zero_values() {
+        let mut summary = SummaryAggregation::default();
+        for value in -50..=50 {
+            summary.record(value as f64);
+        }
+
+        assert_close(summary.quantile(0.0), -50.0, DEFAULT_RELATIVE_ACCURACY);
+        assert_eq!(summary.quantile(0.5), 0.0);
+        assert_close(summary.quantile(0.25), -25.0, DEFAULT_RELATIVE_ACCURACY);
+        assert_close(summary.quantile(0.75), 25.0, DEFAULT_RELATIVE_ACCURACY);
+        assert_close(summary.quantile(1.0), 50.0, DEFAULT_RELATIVE_ACCURACY);
+    }
+
+    #[test]
+    fn test_empty() {
+        let mut summary = SummaryAggregation::default();
+        assert!(summary.quantile(0.5).is_nan());
+        summary.record(f64::NAN);
+        assert_eq!(summary.count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "relative accuracy must be between 0 and 1")]
+    fn test_invalid_accuracy() {
+        SummaryAggregation::new(1.0);
+    }
+}
@@ -0,0 +1,265 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::{
+    error::Error,
+    fmt,
+    sync::{Arc, Mutex, atomic::{AtomicI64, AtomicU64, Ordering}},
+};
+
+use opentelemetry_api::metric::TimeSeries;
+
+use super::data::Histogram;
+
+/// A thread-safe `TimeSeries` of `i64` values.
+///
+/// Clones share the same underlying value, so a handle can be kept by the metric and every
+/// thread that records into it.
+#[derive(Clone, Default, Debug)]
+pub struct AtomicLongTimeSeries {
+    value: Arc<AtomicI64>,
+}
+
+impl AtomicLongTimeSeries {
+    /// Returns the current value of this `TimeSeries`.
+    pub fn get(&self) -> i64 {
+        self.value.load(Ordering::SeqCst)
+    }
+
+    /// Atomically replaces the current value with `f(current)`, retrying on contention.
+    ///
+    /// Returns the value that was replaced.
+    pub(crate) fn update<F: Fn(i64) -> i64>(&self, f: F) -> i64 {
+        let mut current = self.value.load(Ordering::SeqCst);
+        loop {
+            match self.value.compare_exchange(current, f(current), Ordering::SeqCst, Ordering::SeqCst) {
+                Ok(previous) => return previous,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+}
+
+impl TimeSeries for AtomicLongTimeSeries {
+    type V = i64;
+
+    fn add(&self, delta: i64) {
+        self.value.fetch_add(delta, Ordering::SeqCst);
+    }
+
+    fn set(&self, val: i64) {
+        self.value.store(val, Ordering::SeqCst);
+    }
+}
+
+/// A thread-safe `TimeSeries` of `f64` values, stored as their bit pattern.
+///
+/// Clones share the same underlying value, so a handle can be kept by the metric and every
+/// thread that records into it.
+#[derive(Clone, Default, Debug)]
+pub struct AtomicDoubleTimeSeries {
+    value: Arc<AtomicU64>,
+}
+
+impl AtomicDoubleTimeSeries {
+    /// Returns the current value of this `TimeSeries`.
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.value.load(Ordering::SeqCst))
+    }
+
+    /// Atomically replaces the current value with `f(current)`, retrying on contention.
+    ///
+    /// Returns the value that was replaced.
+    pub(crate) fn update<F: Fn(f64) -> f64>(&self, f: F) -> f64 {
+        let mut current = self.value.load(Ordering::SeqCst);
+        loop {
+            let new = f(f64::from_bits(current)).to_bits();
+            match self.value.compare_exchange(current, new, Ordering::SeqCst, Ordering::SeqCst) {
+                Ok(previous) => return f64::from_bits(previous),
+                Err(actual) => current = actual,
+            }
+        }
+    }
+}
+
+impl TimeSeries for AtomicDoubleTimeSeries {
+    type V = f64;
+
+    fn add(&self, delta: f64) {
+        self.update(|current| current + delta);
+    }
+
+    fn set(&self, val: f64) {
+        self.value.store(val.to_bits(), Ordering::SeqCst);
+    }
+}
+
+/// The boundaries given to a `HistogramTimeSeries` are not strictly increasing.
+#[derive(Clone, PartialEq, Debug)]
+pub struct InvalidBoundariesError {
+    pub boundaries: Vec<f64>,
+}
+
+impl fmt::Display for InvalidBoundariesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "histogram boundaries must be strictly increasing, got {:?}", self.boundaries)
+    }
+}
+
+impl Error for InvalidBoundariesError {}
+
+/// Fails if the histogram boundaries are not strictly increasing, or one of them is NaN.
+pub(crate) fn validate_boundaries(boundaries: &[f64]) -> Result<(), InvalidBoundariesError> {
+    let increasing = boundaries.windows(2).all(|pair| pair[0] < pair[1]);
+    if !increasing || boundaries.iter().any(|boundary| boundary.is_nan()) {
+        return Err(InvalidBoundariesError { boundaries: boundaries.to_vec() });
+    }
+    Ok(())
+}
+
+/// A thread-safe series of `f64` values counted in buckets, along with their count and sum.
+///
+/// Clones share the same underlying histogram, so a handle can be kept by the metric and every
+/// thread that records into it.
+#[derive(Clone, Debug)]
+pub struct HistogramTimeSeries {
+    histogram: Arc<Mutex<Histogram>>,
+}
+
+impl HistogramTimeSeries {
+    /// Creates a histogram whose buckets have the given inclusive upper bounds, plus an unbounded
+    /// last bucket.
+    ///
+    /// # Panics
+    /// * if the boundaries are not strictly increasing, or one of them is NaN.
+    pub fn new(boundaries: Vec<f64>) -> Self {
+        HistogramTimeSeries::try_new(boundaries).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Creates a histogram whose buckets have the given inclusive upper bounds, plus an unbounded
+    /// last bucket, failing if the boundaries are not strictly increasing or one of them is NaN.
+    pub fn try_new(boundaries: Vec<f64>) -> Result<Self, InvalidBoundariesError> {
+        validate_boundaries(&boundaries)?;
+        Ok(HistogramTimeSeries { histogram: Arc::new(Mutex::new(Histogram::new(boundaries))) })
+    }
+
+    /// Counts the value in the first bucket whose upper bound is greater than or equal to it.
+    pub fn record(&self, value: f64) {
+        self.histogram.lock().unwrap().record(value);
+    }
+
+    /// Returns the current bucket counts, count and sum.
+    pub fn snapshot(&self) -> Histogram {
+        self.histogram.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use super::*;
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<AtomicLongTimeSeries>();
+        assert_send_sync::<AtomicDoubleTimeSeries>();
+        assert_send_sync::<HistogramTimeSeries>();
+    }
+
+    #[test]
+    fn test_long_concurrent_add() {
+        let ts = AtomicLongTimeSeries::default();
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let ts = ts.clone();
+                thread::spawn(move || for _ in 0..1000 { ts.add(1) })
+            })
+            .collect();
+        handles.into_iter().for_each(|h| h.join().unwrap());
+        assert_eq!(ts.get(), 8000);
+    }
+
+    #[test]
+    fn test_double_concurrent_add() {
+        let ts = AtomicDoubleTimeSeries::default();
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let ts = ts.clone();
+                thread::spawn(move || for _ in 0..1000 { ts.add(1.0) })
+            })
+            .collect();
+        handles.into_iter().for_each(|h| h.join().unwrap());
+        assert_eq!(ts.get(), 8000.0);
+    }
+
+    #[test]
+    fn test_set() {
+        let long = AtomicLongTimeSeries::default();
+        long.add(5);
+        long.set(-2);
+        assert_eq!(long.get(), -2);
+
+        let double = AtomicDoubleTimeSeries::default();
+        double.add(0.5);
+        double.set(-2.5);
+        assert_eq!(double.get(), -2.5);
+    }
+
+    #[test]
+    fn test_histogram_buckets() {
+        let ts = HistogramTimeSeries::new(vec![0.0, 10.0, 100.0]);
+        for value in &[-1.0, 0.0, 0.5, 10.0, 10.5, 100.0, 1000.0] {
+            ts.record(*value);
+        }
+
+        let histogram = ts.snapshot();
+        assert_eq!(histogram.boundaries, vec![0.0, 10.0, 100.0]);
+        assert_eq!(histogram.bucket_counts, vec![2, 2, 2, 1]);
+        assert_eq!(histogram.count, 7);
+        assert_eq!(histogram.sum, 1120.0);
+    }
+
+    #[test]
+    fn test_histogram_shared_by_clones() {
+        let ts = HistogramTimeSeries::new(vec![1.0]);
+        ts.clone().record(2.0);
+        assert_eq!(ts.snapshot().bucket_counts, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_histogram_no_boundaries() {
+        let ts = HistogramTimeSeries::new(Vec::new());
+        ts.record(3.0);
+        ts.record(4.0);
+        let histogram = ts.snapshot();
+        assert_eq!(histogram.bucket_counts, vec![2]);
+        assert_eq!(histogram.sum, 7.0);
+    }
+
+    #[test]
+    fn test_histogram_invalid_boundaries() {
+        for boundaries in [vec![1.0, 1.0], vec![2.0, 1.0], vec![f64::NAN], vec![0.0, f64::NAN]] {
+            let err = HistogramTimeSeries::try_new(boundaries.clone()).unwrap_err();
+            assert_eq!(err.boundaries.len(), boundaries.len());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "histogram boundaries must be strictly increasing")]
+    fn test_histogram_new_panics() {
+        HistogramTimeSeries::new(vec![5.0, 1.0]);
+    }
+}
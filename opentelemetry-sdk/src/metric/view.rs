@@ -0,0 +1,64 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+/// How the measurements recorded for a `Measure` are aggregated into the value of each `Point`.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Aggregation {
+    /// The sum of the recorded values, the default.
+    Sum,
+    /// The most recently recorded value.
+    LastValue,
+    /// The distribution of the recorded values over buckets delimited by `boundaries`, which must
+    /// be sorted in increasing order. Each boundary is the inclusive upper bound of its bucket.
+    Histogram { boundaries: Vec<f64> },
+    /// A `SummaryAggregation` estimating quantiles of the recorded values within
+    /// `relative_accuracy` of the true values, e.g. `0.01` for 1%.
+    Summary { relative_accuracy: f64 },
+}
+
+// Deriving `Default` for an enum needs Rust 1.62
+#[allow(clippy::derivable_impls)]
+impl Default for Aggregation {
+    fn default() -> Self {
+        Aggregation::Sum
+    }
+}
+
+/// Selects the `Aggregation` of the measure with the given name, and optionally the subset of its
+/// label keys to aggregate by.
+///
+/// Measurements whose label values differ only on dropped keys are aggregated together.
+#[derive(Clone, PartialEq, Debug)]
+pub struct View {
+    pub name: String,
+    pub aggregation: Aggregation,
+    /// The label keys to keep, all of them if `None`.
+    pub label_keys: Option<Vec<String>>,
+}
+
+impl View {
+    pub fn new<N: Into<String>>(name: N, aggregation: Aggregation) -> Self {
+        View { name: name.into(), aggregation, label_keys: None }
+    }
+
+    /// Keeps only the given label keys, in the order of the label keys of the measure.
+    ///
+    /// Default is to keep all of them.
+    pub fn label_keys<K: Into<String>>(mut self, label_keys: Vec<K>) -> Self {
+        self.label_keys = Some(label_keys.into_iter().map(Into::into).collect());
+        self
+    }
+}
@@ -0,0 +1,278 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use opentelemetry_api::trace::span_data::SpanData;
+
+use super::export::SpanExporter;
+use super::processor::SpanProcessor;
+
+/// Default `BatchConfig::max_queue_size`.
+pub const DEFAULT_MAX_QUEUE_SIZE: usize = 2048;
+/// Default `BatchConfig::max_export_batch_size`.
+pub const DEFAULT_MAX_EXPORT_BATCH_SIZE: usize = 512;
+/// Default `BatchConfig::scheduled_delay`.
+pub const DEFAULT_SCHEDULED_DELAY: Duration = Duration::from_secs(5);
+
+/// Configuration of a `BatchSpanProcessor`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct BatchConfig {
+    /// Maximum number of spans waiting to be exported, further spans are dropped.
+    pub max_queue_size: usize,
+    /// Maximum number of spans handed to the exporter at once.
+    pub max_export_batch_size: usize,
+    /// Delay after which an incomplete batch is exported anyway.
+    pub scheduled_delay: Duration,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        BatchConfig {
+            max_queue_size: DEFAULT_MAX_QUEUE_SIZE,
+            max_export_batch_size: DEFAULT_MAX_EXPORT_BATCH_SIZE,
+            scheduled_delay: DEFAULT_SCHEDULED_DELAY,
+        }
+    }
+}
+
+impl BatchConfig {
+    /// Sets the maximum number of spans waiting to be exported.
+    ///
+    /// Default value is `DEFAULT_MAX_QUEUE_SIZE`.
+    pub fn max_queue_size(mut self, max_queue_size: usize) -> Self {
+        self.max_queue_size = max_queue_size;
+        self
+    }
+
+    /// Sets the maximum number of spans handed to the exporter at once, it must be positive.
+    ///
+    /// Default value is `DEFAULT_MAX_EXPORT_BATCH_SIZE`.
+    pub fn max_export_batch_size(mut self, max_export_batch_size: usize) -> Self {
+        self.max_export_batch_size = max_export_batch_size;
+        self
+    }
+
+    /// Sets the delay after which an incomplete batch is exported anyway.
+    ///
+    /// Default value is `DEFAULT_SCHEDULED_DELAY`.
+    pub fn scheduled_delay(mut self, scheduled_delay: Duration) -> Self {
+        self.scheduled_delay = scheduled_delay;
+        self
+    }
+}
+
+/// A `SpanProcessor` exporting sampled spans in batches from a background thread.
+///
+/// Ended spans are queued in a bounded channel, so that ending a span never blocks on the
+/// exporter. When the queue is full, spans are dropped and counted in `dropped_span_count`.
+///
+/// Dropping the processor exports the spans still queued and waits for the background thread.
+pub struct BatchSpanProcessor {
+    sender: Option<SyncSender<SpanData<'static>>>,
+    worker: Option<JoinHandle<()>>,
+    dropped_spans: AtomicUsize,
+}
+
+impl BatchSpanProcessor {
+    /// Starts the background thread exporting to `exporter`.
+    ///
+    /// # Panics
+    /// * if `config.max_export_batch_size` is zero
+    pub fn new<E: SpanExporter + 'static>(exporter: E, config: BatchConfig) -> Self {
+        assert!(config.max_export_batch_size > 0, "max_export_batch_size must be positive");
+        let (sender, receiver) = mpsc::sync_channel(config.max_queue_size);
+        let worker = thread::Builder::new()
+            .name("opentelemetry-batch-span-processor".to_string())
+            .spawn(move || export_batches(exporter, receiver, config))
+            .expect("failed to spawn the batch span processor thread");
+
+        BatchSpanProcessor {
+            sender: Some(sender),
+            worker: Some(worker),
+            dropped_spans: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of spans dropped because the queue was full.
+    pub fn dropped_span_count(&self) -> usize {
+        self.dropped_spans.load(Ordering::Relaxed)
+    }
+}
+
+impl SpanProcessor for BatchSpanProcessor {
+    fn on_end(&self, span: SpanData<'static>) {
+        if !span.context.options.is_sampled() {
+            return;
+        }
+        let sent = match &self.sender {
+            Some(sender) => sender.try_send(span).is_ok(),
+            None => false,
+        };
+        if !sent {
+            self.dropped_spans.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Drop for BatchSpanProcessor {
+    fn drop(&mut self) {
+        // Disconnecting the channel makes the worker export what is left and exit.
+        self.sender.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn export_batches<E: SpanExporter>(exporter: E, receiver: Receiver<SpanData<'static>>,
+                                   config: BatchConfig) {
+    let mut batch = Vec::with_capacity(config.max_export_batch_size);
+    let mut deadline = Instant::now() + config.scheduled_delay;
+    loop {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(timeout) {
+            Ok(span) => {
+                batch.push(span);
+                if batch.len() < config.max_export_batch_size {
+                    continue;
+                }
+            },
+            Err(RecvTimeoutError::Timeout) => {},
+            Err(RecvTimeoutError::Disconnected) => {
+                if !batch.is_empty() {
+                    exporter.export(batch);
+                }
+                return;
+            }
+        }
+
+        if !batch.is_empty() {
+            let capacity = config.max_export_batch_size;
+            exporter.export(mem::replace(&mut batch, Vec::with_capacity(capacity)));
+        }
+        deadline = Instant::now() + config.scheduled_delay;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use opentelemetry_api::trace::span_context::SpanContext;
+    use opentelemetry_api::trace::span_id::SpanId;
+    use opentelemetry_api::trace::trace_id::TraceId;
+    use opentelemetry_api::trace::trace_options::TraceOptions;
+    use opentelemetry_api::trace::trace_state::TraceState;
+
+    fn span_data(span_id: u64, options: TraceOptions) -> SpanData<'static> {
+        let context = SpanContext::new(TraceId::from_bytes([1; 16]), SpanId::new(span_id), options,
+                                       TraceState::default());
+        SpanData::new("test", context)
+    }
+
+    /// Records the span ids of every exported batch.
+    #[derive(Clone, Default)]
+    struct TestExporter {
+        batches: Arc<Mutex<Vec<Vec<u64>>>>,
+    }
+
+    impl SpanExporter for TestExporter {
+        fn export(&self, spans: Vec<SpanData<'static>>) {
+            let ids = spans.iter().map(|span| span.context.span_id.to_bytes()).map(u64::from_be_bytes);
+            self.batches.lock().unwrap().push(ids.collect());
+        }
+    }
+
+    #[test]
+    fn test_exports_in_batches() {
+        let exporter = TestExporter::default();
+        let config = BatchConfig::default()
+            .max_export_batch_size(2)
+            .scheduled_delay(Duration::from_secs(3600));
+        let processor = BatchSpanProcessor::new(exporter.clone(), config);
+        for span_id in 1..=5 {
+            processor.on_end(span_data(span_id, TraceOptions::IS_SAMPLED));
+        }
+        processor.on_end(span_data(6, TraceOptions::default()));
+        drop(processor);
+
+        let batches = exporter.batches.lock().unwrap();
+        assert_eq!(*batches, vec![vec![1, 2], vec![3, 4], vec![5]]);
+    }
+
+    #[test]
+    fn test_exports_after_scheduled_delay() {
+        let exporter = TestExporter::default();
+        let config = BatchConfig::default().scheduled_delay(Duration::from_millis(10));
+        let processor = BatchSpanProcessor::new(exporter.clone(), config);
+        processor.on_end(span_data(1, TraceOptions::IS_SAMPLED));
+
+        let start = Instant::now();
+        while exporter.batches.lock().unwrap().is_empty() {
+            assert!(start.elapsed() < Duration::from_secs(10), "batch was never exported");
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(*exporter.batches.lock().unwrap(), vec![vec![1]]);
+    }
+
+    /// Blocks every export until released, announcing it has started.
+    struct BlockingExporter {
+        started: Mutex<mpsc::Sender<()>>,
+        release: Mutex<Receiver<()>>,
+        inner: TestExporter,
+    }
+
+    impl SpanExporter for BlockingExporter {
+        fn export(&self, spans: Vec<SpanData<'static>>) {
+            self.started.lock().unwrap().send(()).unwrap();
+            self.release.lock().unwrap().recv().unwrap();
+            self.inner.export(spans);
+        }
+    }
+
+    #[test]
+    fn test_drops_spans_when_queue_is_full() {
+        let (started, on_started) = mpsc::channel();
+        let (on_release, release) = mpsc::channel();
+        let inner = TestExporter::default();
+        let exporter = BlockingExporter {
+            started: Mutex::new(started),
+            release: Mutex::new(release),
+            inner: inner.clone(),
+        };
+        let config = BatchConfig::default().max_queue_size(2).max_export_batch_size(1);
+        let processor = BatchSpanProcessor::new(exporter, config);
+
+        // Wait for the worker to be busy exporting the first span, leaving the queue empty
+        processor.on_end(span_data(1, TraceOptions::IS_SAMPLED));
+        on_started.recv().unwrap();
+        for span_id in 2..=4 {
+            processor.on_end(span_data(span_id, TraceOptions::IS_SAMPLED));
+        }
+        assert_eq!(processor.dropped_span_count(), 1);
+
+        for _ in 0..3 {
+            on_release.send(()).unwrap();
+        }
+        drop(processor);
+        assert_eq!(*inner.batches.lock().unwrap(), vec![vec![1], vec![2], vec![3]]);
+    }
+}
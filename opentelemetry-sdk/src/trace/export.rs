@@ -0,0 +1,25 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use opentelemetry_api::trace::span_data::SpanData;
+
+/// Sends finished spans to a tracing backend.
+///
+/// Exporters are called by `SpanProcessor`s, possibly from a background thread.
+pub trait SpanExporter: Send + Sync {
+    /// Exports a batch of finished spans.
+    fn export(&self, spans: Vec<SpanData<'static>>);
+}
@@ -0,0 +1,59 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//!
+//! Generation of the `TraceId`s and `SpanId`s of new spans, replaceable for deterministic tests or
+//! custom id schemes.
+use opentelemetry_api::trace::span_id::SpanId;
+use opentelemetry_api::trace::trace_id::TraceId;
+
+/// Generates the ids of new spans.
+///
+/// Generated ids should be valid, i.e. not all zeros.
+pub trait IdGenerator: Send + Sync {
+    /// Returns the `TraceId` of a new root span.
+    fn new_trace_id(&self) -> TraceId;
+
+    fn new_span_id(&self) -> SpanId;
+}
+
+/// `IdGenerator` returning random ids from the thread local random number generator.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct RandomIdGenerator;
+
+impl IdGenerator for RandomIdGenerator {
+    fn new_trace_id(&self) -> TraceId {
+        TraceId::generate_random_id(&mut rand::thread_rng())
+    }
+
+    fn new_span_id(&self) -> SpanId {
+        SpanId::generate_random_id(&mut rand::thread_rng())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_ids_valid() {
+        for _ in 0..100 {
+            assert!(RandomIdGenerator.new_trace_id().is_valid());
+            assert!(RandomIdGenerator.new_span_id().is_valid());
+        }
+        assert_ne!(RandomIdGenerator.new_span_id(), RandomIdGenerator.new_span_id());
+    }
+}
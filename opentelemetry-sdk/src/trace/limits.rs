@@ -0,0 +1,164 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::borrow::Cow;
+use opentelemetry_api::trace::attribute_value::AttributeValue;
+use opentelemetry_api::trace::attributes::Attributes;
+
+/// Appended to string attribute values that were truncated.
+pub const TRUNCATION_MARKER: &str = "...";
+
+pub const DEFAULT_MAX_ATTRIBUTES: usize = 128;
+pub const DEFAULT_MAX_VALUE_LEN: usize = 2048;
+
+/// Limits applied to the attributes recorded on a span, protecting exporters from unbounded data.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct AttributeLimits {
+    /// Maximum number of distinct attributes, further new keys are dropped.
+    pub max_attributes: usize,
+    /// Maximum length in characters of string values, longer ones are truncated.
+    pub max_value_len: usize,
+}
+
+impl Default for AttributeLimits {
+    fn default() -> Self {
+        AttributeLimits {
+            max_attributes: DEFAULT_MAX_ATTRIBUTES,
+            max_value_len: DEFAULT_MAX_VALUE_LEN,
+        }
+    }
+}
+
+pub const DEFAULT_MAX_EVENTS: usize = 128;
+pub const DEFAULT_MAX_LINKS: usize = 128;
+
+/// Limits applied to the events and links recorded on a span, further ones are dropped.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SpanLimits {
+    /// Maximum number of events, defaults to `DEFAULT_MAX_EVENTS`.
+    pub max_events: usize,
+    /// Maximum number of links, defaults to `DEFAULT_MAX_LINKS`.
+    pub max_links: usize,
+}
+
+impl Default for SpanLimits {
+    fn default() -> Self {
+        SpanLimits {
+            max_events: DEFAULT_MAX_EVENTS,
+            max_links: DEFAULT_MAX_LINKS,
+        }
+    }
+}
+
+impl AttributeLimits {
+    /// Inserts an attribute while respecting these limits.
+    ///
+    /// Replacing an existing key is always allowed. Returns `false` if the attribute was dropped
+    /// because the count limit was reached.
+    pub fn insert(&self, attributes: &mut Attributes<'static>,
+                  key: String, value: AttributeValue<'static>) -> bool {
+        if attributes.len() >= self.max_attributes && !attributes.contains_key(&key) {
+            return false;
+        }
+        attributes.insert(key, self.truncate(value));
+        true
+    }
+
+    /// Truncates string values longer than `max_value_len`, ending them with `TRUNCATION_MARKER`
+    /// unless `max_value_len` is too small to fit it.
+    pub fn truncate(&self, value: AttributeValue<'static>) -> AttributeValue<'static> {
+        match value {
+            AttributeValue::String(s) => AttributeValue::String(self.truncate_str(s)),
+            AttributeValue::StringArray(v) => AttributeValue::StringArray(
+                v.into_iter().map(|s| self.truncate_str(s)).collect()
+            ),
+            other => other,
+        }
+    }
+
+    fn truncate_str(&self, s: Cow<'static, str>) -> Cow<'static, str> {
+        if s.chars().count() <= self.max_value_len {
+            return s;
+        }
+        // Limits too small to fit the marker are applied without it
+        if self.max_value_len < TRUNCATION_MARKER.len() {
+            return Cow::Owned(s.chars().take(self.max_value_len).collect());
+        }
+        let keep = self.max_value_len - TRUNCATION_MARKER.len();
+        let mut truncated: String = s.chars().take(keep).collect();
+        truncated.push_str(TRUNCATION_MARKER);
+        Cow::Owned(truncated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drops_over_count() {
+        let limits = AttributeLimits::default();
+        let mut attributes = Attributes::new();
+        for i in 0..DEFAULT_MAX_ATTRIBUTES {
+            assert!(limits.insert(&mut attributes, format!("key{}", i), AttributeValue::Long(i as i64)));
+        }
+        assert!(!limits.insert(&mut attributes, "key128".to_string(), AttributeValue::Long(128)));
+        assert_eq!(attributes.len(), DEFAULT_MAX_ATTRIBUTES);
+        assert!(!attributes.contains_key("key128"));
+
+        // Existing keys can still be updated
+        assert!(limits.insert(&mut attributes, "key0".to_string(), AttributeValue::Long(-1)));
+        assert_eq!(attributes["key0"], AttributeValue::Long(-1));
+    }
+
+    #[test]
+    fn test_truncates_strings() {
+        let limits = AttributeLimits { max_attributes: 10, max_value_len: 8 };
+        assert_eq!(limits.truncate("short".into()), AttributeValue::String("short".into()));
+        assert_eq!(limits.truncate("exactly8".into()), AttributeValue::String("exactly8".into()));
+        assert_eq!(limits.truncate("much too long".into()), AttributeValue::String("much ...".into()));
+        assert_eq!(
+            limits.truncate(vec!["ok", "also much too long"].into()),
+            AttributeValue::StringArray(vec!["ok".into(), "also ...".into()])
+        );
+        assert_eq!(limits.truncate(AttributeValue::Long(1)), AttributeValue::Long(1));
+
+        let default_limits = AttributeLimits::default();
+        let long = "x".repeat(5000);
+        match default_limits.truncate(long.into()) {
+            AttributeValue::String(s) => {
+                assert_eq!(s.chars().count(), DEFAULT_MAX_VALUE_LEN);
+                assert!(s.ends_with(TRUNCATION_MARKER));
+            }
+            other => panic!("Expected a string, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_truncates_without_marker_below_its_length() {
+        for &(max_value_len, expected) in &[(0, ""), (1, "a"), (2, "ab"), (3, "...")] {
+            let limits = AttributeLimits { max_attributes: 10, max_value_len };
+            assert_eq!(limits.truncate("abcdef".into()), AttributeValue::String(expected.into()));
+        }
+        let limits = AttributeLimits { max_attributes: 10, max_value_len: 2 };
+        assert_eq!(limits.truncate("ab".into()), AttributeValue::String("ab".into()));
+    }
+
+    #[test]
+    fn test_truncates_on_char_boundaries() {
+        let limits = AttributeLimits { max_attributes: 10, max_value_len: 5 };
+        assert_eq!(limits.truncate("ééééééé".into()), AttributeValue::String("éé...".into()));
+    }
+}
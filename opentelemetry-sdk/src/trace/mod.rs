@@ -0,0 +1,20 @@
+pub mod limits;
+pub use limits::{AttributeLimits, SpanLimits};
+
+pub mod span;
+pub use span::RecordingSpan;
+
+pub mod id_generator;
+pub use id_generator::{IdGenerator, RandomIdGenerator};
+
+pub mod tracer;
+pub use tracer::SdkTracer;
+
+pub mod export;
+pub use export::SpanExporter;
+
+pub mod processor;
+pub use processor::{SimpleSpanProcessor, SpanProcessor};
+
+pub mod batch;
+pub use batch::{BatchConfig, BatchSpanProcessor};
@@ -0,0 +1,94 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use opentelemetry_api::trace::span_data::SpanData;
+
+use super::export::SpanExporter;
+
+/// Hook notified of the lifecycle of the `RecordingSpan`s started by a `SdkTracer`, typically
+/// forwarding them to a `SpanExporter`.
+pub trait SpanProcessor: Send + Sync {
+    /// Called with a snapshot of a span when it starts, once the attributes given to the
+    /// `SpanBuilder` are set.
+    ///
+    /// Does nothing by default.
+    fn on_start(&self, _span: &SpanData<'_>) {}
+
+    /// Called with a snapshot of a span when it ends.
+    fn on_end(&self, span: SpanData<'static>);
+}
+
+/// A `SpanProcessor` exporting every sampled span as soon as it ends, on the thread ending it.
+///
+/// Ending a span blocks until the exporter returns, prefer the `BatchSpanProcessor` with remote
+/// backends.
+pub struct SimpleSpanProcessor<E> {
+    exporter: E,
+}
+
+impl <E: SpanExporter> SimpleSpanProcessor<E> {
+    pub fn new(exporter: E) -> Self {
+        SimpleSpanProcessor { exporter }
+    }
+}
+
+impl <E: SpanExporter> SpanProcessor for SimpleSpanProcessor<E> {
+    fn on_end(&self, span: SpanData<'static>) {
+        if span.context.options.is_sampled() {
+            self.exporter.export(vec![span]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use opentelemetry_api::trace::span_context::SpanContext;
+    use opentelemetry_api::trace::span_id::SpanId;
+    use opentelemetry_api::trace::trace_id::TraceId;
+    use opentelemetry_api::trace::trace_options::TraceOptions;
+    use opentelemetry_api::trace::trace_state::TraceState;
+
+    /// Records the names of the exported spans, one batch at a time.
+    #[derive(Clone, Default)]
+    struct TestExporter(Arc<Mutex<Vec<Vec<String>>>>);
+
+    impl SpanExporter for TestExporter {
+        fn export(&self, spans: Vec<SpanData<'static>>) {
+            self.0.lock().unwrap().push(spans.into_iter().map(|span| span.name.into_owned()).collect());
+        }
+    }
+
+    fn span_data(name: &'static str, options: TraceOptions) -> SpanData<'static> {
+        let context = SpanContext::new(TraceId::from_bytes([1; 16]), SpanId::new(1), options,
+                                       TraceState::default());
+        SpanData::new(name, context)
+    }
+
+    #[test]
+    fn test_simple_exports_sampled_spans() {
+        let exporter = TestExporter::default();
+        let processor = SimpleSpanProcessor::new(exporter.clone());
+        processor.on_start(&span_data("first", TraceOptions::IS_SAMPLED));
+        assert!(exporter.0.lock().unwrap().is_empty());
+
+        processor.on_end(span_data("first", TraceOptions::IS_SAMPLED));
+        processor.on_end(span_data("unsampled", TraceOptions::default()));
+        processor.on_end(span_data("second", TraceOptions::IS_SAMPLED));
+        assert_eq!(*exporter.0.lock().unwrap(), vec![vec!["first".to_string()], vec!["second".to_string()]]);
+    }
+}
@@ -0,0 +1,532 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use opentelemetry_api::trace::attribute_value::AttributeValue;
+use opentelemetry_api::trace::attributes::Attributes;
+use opentelemetry_api::trace::event::Event;
+use opentelemetry_api::trace::link::Link;
+use opentelemetry_api::trace::span::{Span, SpanKind};
+use opentelemetry_api::trace::span_context::SpanContext;
+use opentelemetry_api::trace::span_data::{SpanData, SpanEvent, SpanLink};
+use opentelemetry_api::trace::status::Status;
+
+use crate::clock::Clock;
+use super::limits::{AttributeLimits, SpanLimits};
+use super::processor::SpanProcessor;
+
+/// A `Span` that records everything done to it in memory.
+///
+/// Its start time is the time of creation. The end time is derived from a monotonic clock when
+/// `end` is called, so that the duration is not affected by adjustments of the system time.
+/// A span given a `Clock` reads both times from it instead.
+///
+/// Only the first call to `end` is recorded, the span ignores any change made after it ended.
+/// A span dropped before being ended is ended automatically.
+///
+/// When a `SpanProcessor` is attached, it is handed a `SpanData` snapshot of the span through
+/// `on_start` as soon as it is attached, and through `on_end` on `end`.
+pub struct RecordingSpan {
+    data: SpanData<'static>,
+    attribute_limits: AttributeLimits,
+    span_limits: SpanLimits,
+    processor: Option<Arc<dyn SpanProcessor>>,
+    clock: Option<Arc<dyn Clock>>,
+    start: Instant,
+    ended: bool,
+}
+
+impl RecordingSpan {
+    pub fn new<'a, N: Into<Cow<'a, str>>>(name: N, context: SpanContext<'static>) -> Self {
+        RecordingSpan::with_limits(name, context, AttributeLimits::default())
+    }
+
+    /// Creates a `RecordingSpan` that enforces the given `AttributeLimits`.
+    pub fn with_limits<'a, N>(name: N, context: SpanContext<'static>, attribute_limits: AttributeLimits) -> Self
+        where N: Into<Cow<'a, str>>
+    {
+        RecordingSpan {
+            data: SpanData::new(name.into().into_owned(), context),
+            attribute_limits,
+            span_limits: SpanLimits::default(),
+            processor: None,
+            clock: None,
+            start: Instant::now(),
+            ended: false,
+        }
+    }
+
+    /// Sets the `SpanKind`, default value is `SpanKind::Internal`.
+    pub fn with_kind(mut self, kind: SpanKind) -> Self {
+        self.data.kind = kind;
+        self
+    }
+
+    /// Sets the `SpanLimits` capping the number of events and links.
+    pub fn with_span_limits(mut self, span_limits: SpanLimits) -> Self {
+        self.span_limits = span_limits;
+        self
+    }
+
+    /// Sets the `Clock` giving the start time, read right away, and the end time of this span.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.data.start_time = clock.now();
+        self.data.end_time = self.data.start_time;
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Attaches the `SpanProcessor` notified when this span ends, calling its `on_start` right away.
+    pub fn with_processor(mut self, processor: Arc<dyn SpanProcessor>) -> Self {
+        processor.on_start(&self.data);
+        self.processor = Some(processor);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.data.name
+    }
+
+    pub fn kind(&self) -> SpanKind {
+        self.data.kind
+    }
+
+    pub fn start_time(&self) -> SystemTime {
+        self.data.start_time
+    }
+
+    /// Returns the attributes, in the order they were first set.
+    pub fn attributes(&self) -> &Attributes<'static> {
+        &self.data.attributes
+    }
+
+    pub fn status(&self) -> &Status<'static> {
+        &self.data.status
+    }
+
+    /// Returns the duration of the span, `None` until `end` has been called.
+    pub fn duration(&self) -> Option<Duration> {
+        if self.ended {
+            Some(self.data.duration())
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` once `end` has been called.
+    pub fn has_ended(&self) -> bool {
+        self.ended
+    }
+
+    /// Returns a snapshot of everything recorded so far.
+    pub fn span_data(&self) -> SpanData<'static> {
+        self.data.clone()
+    }
+
+    /// The current time of the `Clock`, or the start time advanced by the monotonic time elapsed
+    /// since the span started.
+    fn now(&self) -> SystemTime {
+        match &self.clock {
+            Some(clock) => clock.now(),
+            None => self.data.start_time + self.start.elapsed(),
+        }
+    }
+}
+
+impl Span for RecordingSpan {
+    fn set_attribute<'a, K, V>(&mut self, key: K, value: V)
+        where K: Into<Cow<'a, str>>,
+              V: Into<AttributeValue<'a>>
+    {
+        if self.ended {
+            return;
+        }
+        let key = key.into().into_owned();
+        let value = value.into().into_owned();
+        if !self.attribute_limits.insert(&mut self.data.attributes, key, value) {
+            self.data.dropped_attribute_count += 1;
+        }
+    }
+
+    fn add_event<E: Event>(&mut self, event: E) {
+        if self.ended {
+            return;
+        }
+        if self.data.events.len() >= self.span_limits.max_events {
+            self.data.dropped_event_count += 1;
+            return;
+        }
+        let timestamp = self.now();
+        self.data.events.push(SpanEvent::from_event(&event, timestamp));
+    }
+
+    fn add_link<L: Link>(&mut self, link: L) {
+        if self.ended {
+            return;
+        }
+        if self.data.links.len() >= self.span_limits.max_links {
+            self.data.dropped_link_count += 1;
+            return;
+        }
+        self.data.links.push(SpanLink::from_link(&link));
+    }
+
+    fn set_status(&mut self, status: Status) {
+        if self.ended {
+            return;
+        }
+        self.data.status = status.into_owned();
+    }
+
+    fn update_name<'a, N: Into<Cow<'a, str>>>(&mut self, name: N) {
+        if self.ended {
+            return;
+        }
+        self.data.name = Cow::Owned(name.into().into_owned());
+    }
+
+    fn end(&mut self) {
+        if self.ended {
+            return;
+        }
+        self.ended = true;
+        self.data.end_time = self.now();
+        if let Some(processor) = &self.processor {
+            processor.on_end(self.span_data());
+        }
+    }
+
+    fn context(&self) -> &SpanContext<'_> {
+        &self.data.context
+    }
+
+    fn is_recording_events(&self) -> bool {
+        true
+    }
+
+    fn attribute_count(&self) -> usize {
+        self.data.attributes.len()
+    }
+
+    fn dropped_attribute_count(&self) -> usize {
+        self.data.dropped_attribute_count
+    }
+
+    fn link_count(&self) -> usize {
+        self.data.links.len()
+    }
+
+    fn events(&self) -> &[SpanEvent] {
+        &self.data.events
+    }
+
+    fn links(&self) -> &[SpanLink] {
+        &self.data.links
+    }
+}
+
+impl Drop for RecordingSpan {
+    fn drop(&mut self) {
+        if !self.ended {
+            log::debug!("Span {:?} was dropped without being ended, ending it", self.data.name);
+            self.end();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::clock::ManualClock;
+    use crate::trace::limits::DEFAULT_MAX_EVENTS;
+    use std::sync::Mutex;
+    use std::thread;
+    use opentelemetry_api::trace::span_data::SpanData;
+    use opentelemetry_api::trace::span::DefaultSpan;
+    use opentelemetry_api::trace::span_id::SpanId;
+    use opentelemetry_api::trace::trace_id::TraceId;
+    use opentelemetry_api::trace::trace_options::TraceOptions;
+    use opentelemetry_api::trace::trace_state::TraceState;
+
+    fn context() -> SpanContext<'static> {
+        SpanContext::new(
+            TraceId::from_bytes([1; 16]),
+            SpanId::new(1),
+            TraceOptions::default(),
+            TraceState::default(),
+        )
+    }
+
+    fn span() -> RecordingSpan {
+        RecordingSpan::new("test", context())
+    }
+
+    #[test]
+    fn test_attribute_order() {
+        let mut span = span();
+        span.set_attribute("c", 1);
+        span.set_attribute("a", 2);
+        span.set_attribute("b", 3);
+        span.set_attribute("a", 4);
+
+        let data = span.span_data();
+        let keys: Vec<_> = data.attributes.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, vec!["c", "a", "b"]);
+        assert_eq!(data.attributes["a"], AttributeValue::Long(4));
+    }
+
+    #[test]
+    fn test_attribute_count() {
+        let mut span = span();
+        span.set_attribute("a", 1);
+        span.set_attribute("b", true);
+        span.set_attribute("c", "three");
+        assert_eq!(span.attribute_count(), 3);
+        assert_eq!(span.dropped_attribute_count(), 0);
+
+        span.set_attribute("a", 2);
+        assert_eq!(span.attribute_count(), 3);
+        assert_eq!(span.attributes()["a"], AttributeValue::Long(2));
+
+        let mut noop = DefaultSpan::invalid();
+        noop.set_attribute("a", 1);
+        assert_eq!(noop.attribute_count(), 0);
+    }
+
+    #[test]
+    fn test_attribute_limits() {
+        let limits = AttributeLimits { max_attributes: 2, max_value_len: 6 };
+        let mut span = RecordingSpan::with_limits("test", context(), limits);
+        span.set_attribute("a", "truncated");
+        span.set_attribute("b", 2);
+        span.set_attribute("c", 3);
+        span.set_attribute("d", 4);
+
+        assert_eq!(span.attribute_count(), 2);
+        assert_eq!(span.dropped_attribute_count(), 2);
+        assert_eq!(span.attributes()["a"], AttributeValue::String("tru...".into()));
+        assert!(!span.attributes().contains_key("c"));
+    }
+
+    struct TestEvent;
+
+    impl Event for TestEvent {
+        fn name(&self) -> &str {
+            "event"
+        }
+
+        fn attributes(&self) -> HashMap<&str, &AttributeValue<'_>> {
+            HashMap::new()
+        }
+    }
+
+    struct TestLink(u64);
+
+    impl Link for TestLink {
+        fn context(&self) -> SpanContext<'_> {
+            SpanContext { span_id: SpanId::new(self.0), ..context() }
+        }
+
+        fn attributes(&self) -> HashMap<&str, &AttributeValue<'_>> {
+            HashMap::new()
+        }
+    }
+
+    #[test]
+    fn test_event_limit() {
+        let mut span = span();
+        for _ in 0..DEFAULT_MAX_EVENTS + 1 {
+            span.add_event(TestEvent);
+        }
+        assert_eq!(span.events().len(), DEFAULT_MAX_EVENTS);
+
+        span.end();
+        let data = span.span_data();
+        assert_eq!(data.events.len(), DEFAULT_MAX_EVENTS);
+        assert_eq!(data.dropped_event_count, 1);
+    }
+
+    #[test]
+    fn test_link_limit() {
+        let mut span = span().with_span_limits(SpanLimits { max_links: 2, ..SpanLimits::default() });
+        for span_id in 2..=5 {
+            span.add_link(TestLink(span_id));
+        }
+        span.add_event(TestEvent);
+
+        let data = span.span_data();
+        let linked: Vec<_> = data.links.iter().map(|l| l.context.span_id).collect();
+        assert_eq!(linked, vec![SpanId::new(2), SpanId::new(3)]);
+        assert_eq!(data.dropped_link_count, 2);
+        assert_eq!(data.dropped_event_count, 0);
+        assert_eq!(span.link_count(), 2);
+    }
+
+    #[test]
+    fn test_links() {
+        let mut span = span();
+        assert_eq!(span.link_count(), 0);
+        span.add_link(TestLink(2));
+        span.add_link(TestLink(3));
+
+        assert_eq!(span.link_count(), 2);
+        let linked: Vec<_> = span.links().iter().map(|l| l.context.span_id).collect();
+        assert_eq!(linked, vec![SpanId::new(2), SpanId::new(3)]);
+        assert_eq!(DefaultSpan::invalid().link_count(), 0);
+
+        span.end();
+        let data = span.span_data();
+        assert_eq!(data.links.len(), 2);
+        assert_eq!(data.links[0].context, TestLink(2).context());
+        assert_eq!(data.links[1].context, TestLink(3).context());
+        assert!(data.links.iter().all(|link| link.attributes.is_empty()));
+    }
+
+    /// Reads the events and links through the `Span` trait.
+    fn recorded<S: Span>(span: &S) -> (Vec<&str>, Vec<SpanId>) {
+        (span.events().iter().map(|e| e.name.as_str()).collect(),
+         span.links().iter().map(|l| l.context.span_id).collect())
+    }
+
+    #[test]
+    fn test_span_accessors() {
+        let mut span = span();
+        assert_eq!(recorded(&span), (vec![], vec![]));
+        span.add_event(TestEvent);
+        span.add_link(TestLink(2));
+        span.add_event(TestEvent);
+        assert_eq!(recorded(&span), (vec!["event", "event"], vec![SpanId::new(2)]));
+
+        let mut default = DefaultSpan::invalid();
+        default.add_event(TestEvent);
+        default.add_link(TestLink(2));
+        assert_eq!(recorded(&default), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_duration() {
+        let mut span = span();
+        assert_eq!(span.duration(), None);
+        thread::sleep(Duration::from_millis(10));
+        span.end();
+
+        let duration = span.duration().unwrap();
+        assert!(duration >= Duration::from_millis(10));
+        assert_eq!(span.span_data().duration(), duration);
+        assert_eq!(span.span_data().end_time, span.start_time() + duration);
+    }
+
+    #[test]
+    fn test_clock() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let clock = ManualClock::new(start);
+        let mut span = span().with_clock(Arc::new(clock.clone()));
+        assert_eq!(span.start_time(), start);
+
+        clock.advance(Duration::from_millis(10));
+        span.add_event(TestEvent);
+        assert_eq!(span.events()[0].timestamp, start + Duration::from_millis(10));
+
+        clock.advance(Duration::from_millis(32));
+        span.end();
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(span.duration(), Some(Duration::from_millis(42)));
+        assert_eq!(span.span_data().end_time, start + Duration::from_millis(42));
+    }
+
+    /// Collects the ended spans.
+    #[derive(Clone, Default)]
+    struct TestProcessor(Arc<Mutex<Vec<SpanData<'static>>>>);
+
+    impl SpanProcessor for TestProcessor {
+        fn on_end(&self, span: SpanData<'static>) {
+            self.0.lock().unwrap().push(span);
+        }
+    }
+
+    fn processed_span(processor: &TestProcessor) -> RecordingSpan {
+        span().with_processor(Arc::new(processor.clone()))
+    }
+
+    #[test]
+    fn test_end_is_idempotent() {
+        let processor = TestProcessor::default();
+        let mut span = processed_span(&processor);
+        span.end();
+        let end_time = span.span_data().end_time;
+        thread::sleep(Duration::from_millis(1));
+        span.end();
+
+        let ended = processor.0.lock().unwrap();
+        assert_eq!(ended.len(), 1);
+        assert_eq!(ended[0].end_time, end_time);
+        assert_eq!(span.span_data().end_time, end_time);
+    }
+
+    #[test]
+    fn test_drop_ends_span() {
+        let processor = TestProcessor::default();
+        let mut span = processed_span(&processor);
+        span.set_attribute("a", 1);
+        drop(span);
+
+        {
+            let ended = processor.0.lock().unwrap();
+            assert_eq!(ended.len(), 1);
+            assert_eq!(ended[0].attributes["a"], AttributeValue::Long(1));
+        }
+
+        let mut span = processed_span(&processor);
+        span.end();
+        drop(span);
+        assert_eq!(processor.0.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_ignores_changes_after_end() {
+        let mut span = span();
+        span.set_attribute("before", 1);
+        span.end();
+        span.set_attribute("after", 2);
+        span.add_event(TestEvent);
+        span.add_link(TestLink(2));
+        span.set_status(Status::internal());
+        span.update_name("after");
+
+        assert_eq!(span.attribute_count(), 1);
+        assert!(span.events().is_empty());
+        assert_eq!(span.link_count(), 0);
+        assert!(span.status().is_ok());
+        assert_eq!(span.name(), "test");
+    }
+
+    #[test]
+    fn test_records_mutations() {
+        let mut span = span();
+        span.update_name("renamed");
+        span.set_status(Status::not_found().with_description("missing".to_string()));
+        span.end();
+
+        assert_eq!(span.name(), "renamed");
+        assert!(span.span_data().end_time >= span.start_time());
+        assert_eq!(span.status(), &Status::not_found().with_description("missing"));
+        assert!(span.has_ended());
+        assert!(span.is_recording_events());
+    }
+}
@@ -0,0 +1,515 @@
+/*
+ * Copyright 2019, OpenTelemetry Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::fmt;
+use std::sync::Arc;
+
+use opentelemetry_api::context;
+use opentelemetry_api::trace::sampler::{AlwaysOnSampler, Decision, ParentContext, Sampler};
+use opentelemetry_api::trace::span::Span;
+use opentelemetry_api::trace::span_builder::SpanBuilder;
+use opentelemetry_api::trace::span_context::SpanContext;
+use opentelemetry_api::trace::span_data::SpanData;
+use opentelemetry_api::trace::trace_options::TraceOptions;
+use opentelemetry_api::trace::trace_state::TraceState;
+use opentelemetry_api::trace::tracer::Tracer;
+
+use crate::clock::Clock;
+use super::id_generator::{IdGenerator, RandomIdGenerator};
+use super::limits::{AttributeLimits, SpanLimits};
+use super::processor::SpanProcessor;
+use super::span::RecordingSpan;
+
+/// A `Tracer` starting `RecordingSpan`s, whose sampled flag is decided by a `Sampler`.
+///
+/// New spans join the trace of their parent, or start a new trace with a `TraceId` from the
+/// `IdGenerator`, random by default, if they have none.
+///
+/// Spans are handed to the `SpanProcessor`, if any, when they end.
+#[derive(Clone)]
+pub struct SdkTracer<S = AlwaysOnSampler> {
+    sampler: S,
+    attribute_limits: AttributeLimits,
+    span_limits: SpanLimits,
+    processor: Option<Arc<dyn SpanProcessor>>,
+    clock: Option<Arc<dyn Clock>>,
+    id_generator: Arc<dyn IdGenerator>,
+}
+
+impl SdkTracer {
+    /// Creates a `SdkTracer` that samples every `Span`.
+    pub fn new() -> Self {
+        SdkTracer::with_sampler(AlwaysOnSampler)
+    }
+}
+
+impl Default for SdkTracer {
+    fn default() -> Self {
+        SdkTracer::new()
+    }
+}
+
+impl <S: Sampler> SdkTracer<S> {
+    pub fn with_sampler(sampler: S) -> Self {
+        SdkTracer {
+            sampler,
+            attribute_limits: AttributeLimits::default(),
+            span_limits: SpanLimits::default(),
+            processor: None,
+            clock: None,
+            id_generator: Arc::new(RandomIdGenerator),
+        }
+    }
+
+    /// Sets the `AttributeLimits` enforced by the started spans.
+    pub fn attribute_limits(mut self, attribute_limits: AttributeLimits) -> Self {
+        self.attribute_limits = attribute_limits;
+        self
+    }
+
+    /// Sets the `SpanLimits` enforced by the started spans.
+    pub fn span_limits(mut self, span_limits: SpanLimits) -> Self {
+        self.span_limits = span_limits;
+        self
+    }
+
+    /// Sets the `SpanProcessor` notified of the started spans.
+    pub fn span_processor<P: SpanProcessor + 'static>(mut self, processor: P) -> Self {
+        self.processor = Some(Arc::new(processor));
+        self
+    }
+
+    /// Sets the `Clock` timing the started spans, instead of the system time and a monotonic
+    /// clock.
+    pub fn clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Sets the `IdGenerator` of the `TraceId`s and `SpanId`s of the started spans.
+    pub fn id_generator<G: IdGenerator + 'static>(mut self, id_generator: G) -> Self {
+        self.id_generator = Arc::new(id_generator);
+        self
+    }
+
+    pub fn sampler(&self) -> &S {
+        &self.sampler
+    }
+}
+
+impl <S: fmt::Debug> fmt::Debug for SdkTracer<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SdkTracer")
+            .field("sampler", &self.sampler)
+            .field("attribute_limits", &self.attribute_limits)
+            .field("span_limits", &self.span_limits)
+            .field("has_processor", &self.processor.is_some())
+            .field("has_clock", &self.clock.is_some())
+            .finish()
+    }
+}
+
+impl <S: Sampler> Tracer for SdkTracer<S> {
+    type Span = RecordingSpan;
+
+    /// Starts a `RecordingSpan` with the sampled flag, attributes and `TraceState` returned by the
+    /// `Sampler`.
+    fn start_span(&self, builder: SpanBuilder<Self>) -> RecordingSpan {
+        let parent = match builder.parent {
+            Some(parent) => parent,
+            None => context::current_parent(),
+        };
+        let (trace_id, parent_state) = match &parent {
+            ParentContext::Parent(ctx) | ParentContext::RemoteParent(ctx) if ctx.trace_id.is_valid() =>
+                (ctx.trace_id, ctx.state.clone().into_owned()),
+            _ => (self.id_generator.new_trace_id(), TraceState::default()),
+        };
+        let span_id = self.id_generator.new_span_id();
+
+        let decision = self.sampler.should_sample(parent, trace_id, span_id, builder.name.clone(),
+                                                  &builder.links);
+        let options = TraceOptions::default().with_sampled(decision.is_sampled());
+        let state = decision.trace_state().map_or(parent_state, |state| state.clone().into_owned());
+        let context = SpanContext::new(trace_id, span_id, options, state);
+
+        let mut span = RecordingSpan::with_limits(builder.name, context, self.attribute_limits)
+            .with_span_limits(self.span_limits)
+            .with_kind(builder.kind);
+        if let Some(clock) = &self.clock {
+            span = span.with_clock(clock.clone());
+        }
+        for (key, value) in builder.attributes {
+            span.set_attribute(key, value);
+        }
+        for (key, value) in decision.attributes() {
+            span.set_attribute(key, value.clone());
+        }
+        for link in builder.links {
+            span.add_link(link);
+        }
+        if let Some(processor) = &self.processor {
+            span = span.with_processor(processor.clone());
+        }
+        span
+    }
+
+    /// Hands the `SpanData` to the `SpanProcessor` as an ended span, the sampler is not consulted.
+    fn record_span_data(&self, span: SpanData<'static>) {
+        if let Some(processor) = &self.processor {
+            processor.on_end(span);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+    use std::collections::HashMap;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::{Duration, SystemTime};
+    use crate::clock::ManualClock;
+    use crate::trace::export::SpanExporter;
+    use crate::trace::processor::SimpleSpanProcessor;
+    use opentelemetry_api::context;
+    use opentelemetry_api::trace::attribute_value::AttributeValue;
+    use opentelemetry_api::trace::event::Event;
+    use opentelemetry_api::trace::link::{Link, SimpleLink};
+    use opentelemetry_api::trace::sampler::{AlwaysOffSampler, SimpleDecision};
+    use opentelemetry_api::trace::span::SpanKind;
+    use opentelemetry_api::trace::span_id::SpanId;
+    use opentelemetry_api::trace::trace_id::TraceId;
+    use opentelemetry_api::trace::trace_state::TraceStateBuilder;
+
+    struct RateDecision(TraceState<'static>);
+
+    impl Decision for RateDecision {
+        fn is_sampled(&self) -> bool {
+            true
+        }
+
+        fn attributes(&self) -> HashMap<&str, &AttributeValue<'_>> {
+            HashMap::new()
+        }
+
+        fn trace_state(&self) -> Option<&TraceState<'_>> {
+            Some(&self.0)
+        }
+    }
+
+    /// Records its sampling rate in the `TraceState`.
+    struct RateSampler;
+
+    impl Sampler for RateSampler {
+        type Decision = RateDecision;
+
+        fn should_sample<'a, N, L>(&self, _parent_ctx: ParentContext, _trace_id: TraceId, _span_id: SpanId,
+                                   _name: N, _parent_links: &[L]) -> RateDecision
+            where N: Into<Cow<'a, str>>,
+                  L: Link
+        {
+            RateDecision(TraceStateBuilder::builder().set("ot", "r:5").build())
+        }
+
+        fn description(&self) -> &str {
+            "RateSampler"
+        }
+    }
+
+    /// Numbers the ids from 1.
+    #[derive(Default)]
+    struct CountingIdGenerator(AtomicU64);
+
+    impl CountingIdGenerator {
+        fn next(&self) -> u64 {
+            self.0.fetch_add(1, Ordering::SeqCst) + 1
+        }
+    }
+
+    impl IdGenerator for CountingIdGenerator {
+        fn new_trace_id(&self) -> TraceId {
+            let mut bytes = [0; 16];
+            bytes[8..].copy_from_slice(&self.next().to_be_bytes());
+            TraceId::from_bytes(bytes)
+        }
+
+        fn new_span_id(&self) -> SpanId {
+            SpanId::new(self.next())
+        }
+    }
+
+    fn parent() -> SpanContext<'static> {
+        let state = TraceStateBuilder::builder().set("congo", "t61rc").build();
+        SpanContext::new(TraceId::from_bytes([1; 16]), SpanId::new(1), TraceOptions::IS_SAMPLED, state)
+    }
+
+    #[test]
+    fn test_root_span() {
+        let tracer = SdkTracer::new();
+        let span = tracer.span_builder("root").no_parent().attribute("key", "value").start_span();
+        assert_eq!(span.name(), "root");
+        assert_eq!(span.kind(), SpanKind::Internal);
+        assert!(span.context().trace_id.is_valid());
+        assert!(span.context().span_id.is_valid());
+        assert_eq!(span.context().options, TraceOptions::IS_SAMPLED);
+        assert_eq!(span.attribute_count(), 1);
+    }
+
+    struct TestEvent;
+
+    impl Event for TestEvent {
+        fn name(&self) -> &str {
+            "event"
+        }
+
+        fn attributes(&self) -> HashMap<&str, &AttributeValue<'_>> {
+            HashMap::new()
+        }
+    }
+
+    #[test]
+    fn test_span_limits() {
+        let tracer = SdkTracer::new().span_limits(SpanLimits { max_events: 1, max_links: 0 });
+        let mut span = tracer.span_builder("limited").start_span();
+        span.add_event(TestEvent);
+        span.add_event(TestEvent);
+
+        let data = span.span_data();
+        assert_eq!(data.events.len(), 1);
+        assert_eq!(data.dropped_event_count, 1);
+    }
+
+    #[test]
+    fn test_builder_attributes_respect_limits() {
+        let limits = AttributeLimits { max_attributes: 1, max_value_len: 4 };
+        let tracer = SdkTracer::new().attribute_limits(limits);
+        let span = tracer.span_builder("root")
+            .attribute("a", "truncated")
+            .attribute("b", 2)
+            .start_span();
+
+        assert_eq!(span.attribute_count(), 1);
+        assert_eq!(span.dropped_attribute_count(), 1);
+        assert_eq!(span.attributes()["a"], AttributeValue::String("t...".into()));
+    }
+
+    #[test]
+    fn test_builder_links() {
+        let tracer = SdkTracer::new();
+        let linked = tracer.span_builder("linked").start_span();
+        let span = tracer.span_builder("batch")
+            .add_link_context(linked.context().clone())
+            .add_link(SimpleLink::new(linked.context().clone()).attribute("index", 1))
+            .start_span();
+
+        let links = &span.span_data().links;
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].context, *linked.context());
+        assert!(links[0].attributes.is_empty());
+        assert_eq!(links[1].attributes["index"], AttributeValue::Long(1));
+    }
+
+    #[test]
+    fn test_span_kind() {
+        let tracer = SdkTracer::new();
+        let span = tracer.span_builder("request").kind(SpanKind::Server).start_span();
+        assert_eq!(span.kind(), SpanKind::Server);
+        assert_eq!(span.span_data().kind, SpanKind::Server);
+    }
+
+    #[test]
+    fn test_child_span_joins_trace() {
+        let tracer = SdkTracer::with_sampler(AlwaysOffSampler);
+        let span = tracer.span_builder("child").parent(ParentContext::RemoteParent(parent())).start_span();
+        assert_eq!(span.context().trace_id, parent().trace_id);
+        assert_ne!(span.context().span_id, parent().span_id);
+        assert_eq!(span.context().options, TraceOptions::default());
+        assert_eq!(span.context().state, parent().state);
+    }
+
+    #[test]
+    fn test_sampler_trace_state() {
+        let tracer = SdkTracer::with_sampler(RateSampler);
+        let span = tracer.span_builder("child").parent(ParentContext::Parent(parent())).start_span();
+        let state = &span.context().state;
+        assert_eq!(state.get("ot").map(|e| e.value.as_ref()), Some("r:5"));
+        assert!(state.get("congo").is_none());
+    }
+
+    /// Samples only the spans created with a link.
+    struct LinkedSampler;
+
+    impl Sampler for LinkedSampler {
+        type Decision = SimpleDecision;
+
+        fn should_sample<'a, N, L>(&self, _parent_ctx: ParentContext, _trace_id: TraceId, _span_id: SpanId,
+                                   _name: N, parent_links: &[L]) -> SimpleDecision
+            where N: Into<Cow<'a, str>>,
+                  L: Link
+        {
+            SimpleDecision::new(!parent_links.is_empty())
+        }
+
+        fn description(&self) -> &str {
+            "LinkedSampler"
+        }
+    }
+
+    #[test]
+    fn test_sampler_sees_links() {
+        let tracer = SdkTracer::with_sampler(LinkedSampler);
+        assert!(!tracer.span_builder("single").start_span().context().options.is_sampled());
+
+        let span = tracer.span_builder("batch")
+            .add_link(SimpleLink::new(parent()))
+            .start_span();
+        assert!(span.context().options.is_sampled());
+    }
+
+    /// Records the exported spans.
+    #[derive(Clone, Default)]
+    struct TestExporter(Arc<Mutex<Vec<SpanData<'static>>>>);
+
+    impl SpanExporter for TestExporter {
+        fn export(&self, spans: Vec<SpanData<'static>>) {
+            self.0.lock().unwrap().extend(spans);
+        }
+    }
+
+    /// Records the names of the started and ended spans.
+    #[derive(Clone, Default)]
+    struct TestProcessor {
+        started: Arc<Mutex<Vec<String>>>,
+        ended: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl SpanProcessor for TestProcessor {
+        fn on_start(&self, span: &SpanData<'_>) {
+            assert!(span.attributes.contains_key("http.method"));
+            self.started.lock().unwrap().push(span.name.to_string());
+        }
+
+        fn on_end(&self, span: SpanData<'static>) {
+            self.ended.lock().unwrap().push(span.name.into_owned());
+        }
+    }
+
+    #[test]
+    fn test_span_processor() {
+        let processor = TestProcessor::default();
+        let tracer = SdkTracer::new().span_processor(processor.clone());
+        let mut span = tracer.span_builder("first").attribute("http.method", "GET").start_span();
+        let _unfinished = tracer.span_builder("unfinished").attribute("http.method", "GET").start_span();
+        assert_eq!(*processor.started.lock().unwrap(), vec!["first".to_string(), "unfinished".to_string()]);
+        assert!(processor.ended.lock().unwrap().is_empty());
+
+        span.end();
+        span.end();
+        assert_eq!(*processor.started.lock().unwrap(), vec!["first".to_string(), "unfinished".to_string()]);
+        assert_eq!(*processor.ended.lock().unwrap(), vec!["first".to_string()]);
+    }
+
+    #[test]
+    fn test_clock() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_544_712_660);
+        let clock = ManualClock::new(start);
+        let exporter = TestExporter::default();
+        let tracer = SdkTracer::new()
+            .clock(clock.clone())
+            .span_processor(SimpleSpanProcessor::new(exporter.clone()));
+
+        let mut span = tracer.span_builder("timed").start_span();
+        clock.advance(Duration::from_millis(250));
+        span.end();
+
+        let exported = exporter.0.lock().unwrap();
+        assert_eq!(exported[0].start_time, start);
+        assert_eq!(exported[0].end_time, start + Duration::from_millis(250));
+        assert_eq!(exported[0].duration(), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_id_generator() {
+        let tracer = SdkTracer::new().id_generator(CountingIdGenerator::default());
+
+        let root = tracer.span_builder("root").start_span();
+        assert_eq!(root.context().trace_id.as_hex(), "00000000000000000000000000000001");
+        assert_eq!(root.context().span_id, SpanId::new(2));
+
+        let child = tracer.span_builder("child")
+            .parent(ParentContext::from_local(root.context().clone()))
+            .start_span();
+        assert_eq!(child.context().trace_id, root.context().trace_id);
+        assert_eq!(child.context().span_id, SpanId::new(3));
+
+        let remote = tracer.span_builder("remote").parent(ParentContext::RemoteParent(parent())).start_span();
+        assert_eq!(remote.context().trace_id, parent().trace_id);
+        assert_eq!(remote.context().span_id, SpanId::new(4));
+    }
+
+    #[test]
+    fn test_in_span() {
+        let exporter = TestExporter::default();
+        let tracer = SdkTracer::new().span_processor(SimpleSpanProcessor::new(exporter.clone()));
+
+        let context = tracer.in_span("work", |span| {
+            assert_eq!(context::current_span_context().as_ref(), Some(span.context()));
+            assert!(exporter.0.lock().unwrap().is_empty());
+            span.context().clone().into_owned()
+        });
+
+        assert_eq!(context::current_span_context(), None);
+        let exported = exporter.0.lock().unwrap();
+        assert_eq!(exported.len(), 1);
+        assert_eq!(exported[0].name, "work");
+        assert_eq!(exported[0].context, context);
+    }
+
+    #[test]
+    fn test_in_span_panic() {
+        let exporter = TestExporter::default();
+        let tracer = SdkTracer::new().span_processor(SimpleSpanProcessor::new(exporter.clone()));
+
+        let outer = tracer.span_builder("outer").start_span();
+        let _scope = tracer.with_span(&outer);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            tracer.in_span("failing", |_span| panic!("failed"))
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(context::current_span_context().as_ref(), Some(outer.context()));
+        let exported = exporter.0.lock().unwrap();
+        assert_eq!(exported.len(), 1);
+        assert_eq!(exported[0].name, "failing");
+        assert_eq!(exported[0].context.trace_id, outer.context().trace_id);
+    }
+
+    #[test]
+    fn test_record_span_data() {
+        let exporter = TestExporter::default();
+        let tracer = SdkTracer::new().span_processor(SimpleSpanProcessor::new(exporter.clone()));
+
+        let mut data = SpanData::new("adapted", parent());
+        data.kind = SpanKind::Client;
+        data.end_time = data.start_time + Duration::from_millis(3);
+        tracer.record_span_data(data.clone());
+
+        assert_eq!(*exporter.0.lock().unwrap(), vec![data]);
+        SdkTracer::new().record_span_data(SpanData::new("no processor", parent()));
+    }
+}